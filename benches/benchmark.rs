@@ -1,6 +1,48 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use criterion::{criterion_group, criterion_main, Criterion};
+use yowl::compact;
 use yowl::graph::Builder;
 use yowl::read::read;
+use yowl::walk::walk;
+use yowl::write::Writer;
+
+/// The system allocator, counting allocations so that builders can be
+/// compared by more than time.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Returns the allocations made by `f`, including reallocations.
+fn allocations(f: impl FnOnce()) -> usize {
+    let start = ALLOCATIONS.load(Ordering::Relaxed);
+
+    f();
+
+    ALLOCATIONS.load(Ordering::Relaxed) - start
+}
 
 const SMILES_STRINGS: [&str; 13] = [
     "CO",                                                                        // Simple molecule
@@ -29,5 +71,100 @@ fn benchmark_smiles_parsing(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark_smiles_parsing);
+fn benchmark_graph_building(c: &mut Criterion) {
+    c.bench_function("build_graph", |b| {
+        b.iter(|| {
+            for smiles in &SMILES_STRINGS {
+                let mut builder = Builder::default();
+                read(smiles, &mut builder, None).unwrap();
+                builder.build().unwrap();
+            }
+        });
+    });
+}
+
+fn benchmark_compact_building(c: &mut Criterion) {
+    c.bench_function("build_compact", |b| {
+        let mut builder = compact::Builder::default();
+
+        b.iter(|| {
+            for smiles in &SMILES_STRINGS {
+                read(smiles, &mut builder, None).unwrap();
+                builder.take().unwrap();
+            }
+        });
+    });
+}
+
+fn benchmark_building_allocations(_: &mut Criterion) {
+    const ROUNDS: usize = 100;
+
+    let graph = allocations(|| {
+        for _ in 0..ROUNDS {
+            for smiles in &SMILES_STRINGS {
+                let mut builder = Builder::default();
+                read(smiles, &mut builder, None).unwrap();
+                builder.build().unwrap();
+            }
+        }
+    });
+    let mut builder = compact::Builder::default();
+
+    // the first round sizes the buffers that later rounds reuse
+    for smiles in &SMILES_STRINGS {
+        read(smiles, &mut builder, None).unwrap();
+        builder.take().unwrap();
+    }
+
+    let compact = allocations(|| {
+        for _ in 0..ROUNDS {
+            for smiles in &SMILES_STRINGS {
+                read(smiles, &mut builder, None).unwrap();
+                builder.take().unwrap();
+            }
+        }
+    });
+    let builds = (ROUNDS * SMILES_STRINGS.len()) as f64;
+
+    println!("allocations per build");
+    println!("{:>18}{:>18}", "graph::Builder", "compact::Builder");
+    println!(
+        "{:>18.1}{:>18.1}",
+        graph as f64 / builds,
+        compact as f64 / builds
+    );
+}
+
+fn benchmark_smiles_writing(c: &mut Criterion) {
+    let graphs = SMILES_STRINGS
+        .iter()
+        .map(|smiles| {
+            let mut builder = Builder::default();
+            read(smiles, &mut builder, None).unwrap();
+            builder.build().unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    c.bench_function("write_smiles", |b| {
+        let mut output = String::new();
+
+        b.iter(|| {
+            for atoms in &graphs {
+                let mut writer = Writer::default();
+                walk(atoms, &mut writer).unwrap();
+                output.clear();
+                writer.write_to(&mut output).unwrap();
+            }
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    benchmark_smiles_writing,
+    benchmark_smiles_parsing,
+    benchmark_graph_building,
+    benchmark_compact_building,
+    benchmark_building_allocations
+);
 criterion_main!(benches);
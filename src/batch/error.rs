@@ -0,0 +1,14 @@
+use thiserror::Error;
+
+use crate::graph;
+use crate::read::ReadError;
+
+/// An error that occurs when building one item of a batch.
+#[derive(Debug, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Error {
+    #[error(transparent)]
+    Read(#[from] ReadError),
+    #[error(transparent)]
+    Build(#[from] graph::Error),
+}
@@ -0,0 +1,7 @@
+mod error;
+mod read_many;
+mod write_many;
+
+pub use error::Error;
+pub use read_many::read_many;
+pub use write_many::write_many;
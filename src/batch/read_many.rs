@@ -0,0 +1,86 @@
+use rayon::prelude::*;
+
+use super::Error;
+use crate::graph::{Atom, Builder};
+use crate::read::{read, ReadError};
+
+/// Reads each of `inputs` in parallel, returning results in input order.
+/// Each worker reuses a single `Builder` across the inputs it handles.
+///
+/// ```
+/// use yowl::batch::{read_many, Error};
+/// use yowl::read::ReadError;
+///
+/// let results = read_many(&["CCO", "C?", "c1ccccc1"]);
+///
+/// assert_eq!(results[0].as_ref().map(Vec::len), Ok(3));
+/// assert_eq!(results[1], Err(Error::Read(ReadError::Character(1))));
+/// assert_eq!(results[2].as_ref().map(Vec::len), Ok(6));
+/// ```
+pub fn read_many<S: AsRef<str> + Sync>(inputs: &[S]) -> Vec<Result<Vec<Atom>, Error>> {
+    inputs
+        .par_iter()
+        .map_init(Builder::default, |builder, input| {
+            let input = input.as_ref();
+
+            // the scanner only reads ASCII
+            if let Some(cursor) = input.bytes().position(|byte| !byte.is_ascii()) {
+                return Err(Error::Read(ReadError::Character(cursor)));
+            }
+
+            let result = read(input, builder, None);
+            // always take, so that a failed read leaves nothing behind
+            let atoms = builder.take();
+
+            result?;
+
+            Ok(atoms?)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn empty() {
+        assert_eq!(read_many::<&str>(&[]), vec![])
+    }
+
+    #[test]
+    fn errors_in_order() {
+        let mut inputs = vec!["C"; 1000];
+
+        inputs[10] = "C(";
+        inputs[500] = "C1CC";
+
+        let results = read_many(&inputs);
+
+        assert_eq!(results.len(), 1000);
+        assert_eq!(results[10], Err(Error::Read(ReadError::EndOfLine)));
+        assert_eq!(results[500], Err(Error::Build(graph::Error::Rnum(0))));
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 998)
+    }
+
+    #[test]
+    fn non_ascii() {
+        let results = read_many(&["CC", "Cé", "O"]);
+
+        assert_eq!(results[0].as_ref().map(Vec::len), Ok(2));
+        assert_eq!(results[1], Err(Error::Read(ReadError::Character(1))));
+        assert_eq!(results[2].as_ref().map(Vec::len), Ok(1))
+    }
+
+    #[test]
+    fn matches_sequential() {
+        let inputs = (1..200).map(|n| "C".repeat(n)).collect::<Vec<_>>();
+        let results = read_many(&inputs);
+
+        for (n, result) in results.into_iter().enumerate() {
+            assert_eq!(result.unwrap().len(), n + 1)
+        }
+    }
+}
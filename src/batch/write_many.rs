@@ -0,0 +1,77 @@
+use rayon::prelude::*;
+
+use crate::graph::Atom;
+use crate::walk::{walk, Error};
+use crate::write::Writer;
+
+/// Writes each of `graphs` in parallel with `walk` and `Writer`, returning
+/// results in input order. Each worker reuses a single `Writer` across the
+/// graphs it handles.
+///
+/// ```
+/// use yowl::batch::{read_many, write_many};
+///
+/// let graphs = read_many(&["CC(=O)O", "N#N"])
+///     .into_iter()
+///     .collect::<Result<Vec<_>, _>>()
+///     .expect("graphs");
+///
+/// assert_eq!(write_many(&graphs), vec![Ok("CC(=O)O".to_string()), Ok("N#N".to_string())]);
+/// ```
+pub fn write_many(graphs: &[Vec<Atom>]) -> Vec<Result<String, Error>> {
+    graphs
+        .par_iter()
+        .map_init(Writer::default, |writer, atoms| {
+            let mut result = String::new();
+
+            writer.clear();
+            walk(atoms.as_slice(), writer)?;
+            writer.write_to(&mut result).expect("write to string");
+
+            Ok(result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::read_many;
+    use crate::feature::{AtomKind, BondKind, Symbol};
+    use crate::graph::Bond;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn reused_writer() {
+        let graphs = read_many(&vec!["C(C)O"; 100])
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert!(write_many(&graphs)
+            .into_iter()
+            .all(|result| result == Ok("C(C)O".to_string())))
+    }
+
+    #[test]
+    fn per_item_error() {
+        let star = AtomKind::Symbol(Symbol::Star);
+        let graphs = vec![
+            vec![Atom::new(star)],
+            vec![Atom {
+                kind: star,
+                bonds: vec![Bond::new(BondKind::Single, 1)],
+            }],
+            vec![Atom::new(star), Atom::new(star)],
+        ];
+
+        assert_eq!(
+            write_many(&graphs),
+            vec![
+                Ok("*".to_string()),
+                Err(Error::UnknownTarget(0, 1)),
+                Ok("*.*".to_string())
+            ]
+        )
+    }
+}
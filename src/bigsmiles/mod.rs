@@ -0,0 +1,9 @@
+mod error;
+mod polymer;
+mod reader;
+
+pub use error::Error;
+pub use polymer::{
+    Attachment, BondingDescriptor, DescriptorKind, Part, Polymer, StochasticObject, Unit,
+};
+pub use reader::read;
@@ -0,0 +1,85 @@
+use crate::graph::Atom;
+
+/// A polymer read from BigSMILES, as its SMILES fragments and stochastic
+/// objects in written order. Each part bonds to the next through the
+/// terminal descriptors of the stochastic objects.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polymer {
+    pub parts: Vec<Part>,
+}
+
+impl Polymer {
+    /// Returns the stochastic objects, in written order.
+    pub fn stochastic_objects(&self) -> impl Iterator<Item = &StochasticObject> {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Smiles(_) => None,
+            Part::Stochastic(object) => Some(object),
+        })
+    }
+}
+
+/// A part of a BigSMILES string.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Part {
+    /// A SMILES fragment outside any stochastic object.
+    Smiles(Vec<Atom>),
+    /// A stochastic object, `{...}`.
+    Stochastic(StochasticObject),
+}
+
+/// A stochastic object: repeat units and end groups joined in no given
+/// order, between two terminal descriptors.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StochasticObject {
+    /// The left terminal descriptor, or `None` for `[]`.
+    pub left: Option<BondingDescriptor>,
+    pub repeat_units: Vec<Unit>,
+    /// The units after `;`, which cap chains.
+    pub end_groups: Vec<Unit>,
+    /// The right terminal descriptor, or `None` for `[]`.
+    pub right: Option<BondingDescriptor>,
+}
+
+/// A repeat unit or end group. Each bonding descriptor is read as a star
+/// atom, so `[$]CC[$]` gives the atoms of `*CC*`, with the descriptors
+/// listed in `attachments`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Unit {
+    pub atoms: Vec<Atom>,
+    /// The attachment points, in written order.
+    pub attachments: Vec<Attachment>,
+}
+
+/// A bonding descriptor at a star atom of a unit.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attachment {
+    /// The index of the star atom.
+    pub id: usize,
+    pub descriptor: BondingDescriptor,
+}
+
+/// A bonding descriptor such as `[$]`, `[<]` or `[>2]`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BondingDescriptor {
+    pub kind: DescriptorKind,
+    /// The index written after the symbol, if any.
+    pub index: Option<u16>,
+}
+
+/// The kind of a bonding descriptor.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DescriptorKind {
+    /// `$`, which bonds to any other `$` of the same index.
+    Dollar,
+    /// `<`, which bonds to `>` of the same index.
+    Less,
+    /// `>`, which bonds to `<` of the same index.
+    Greater,
+}
@@ -0,0 +1,402 @@
+use std::ops::Range;
+
+use super::{
+    Attachment, BondingDescriptor, DescriptorKind, Error, Part, Polymer, StochasticObject, Unit,
+};
+use crate::graph::{self, Builder};
+use crate::read::{self, ReadError, Trace};
+
+/// Reads a BigSMILES string of SMILES fragments and stochastic objects
+/// `{[left] units ; end groups [right]}`, where units are separated by
+/// commas and terminal descriptors may be empty. Stochastic objects can't
+/// be nested.
+///
+/// ```
+/// use yowl::bigsmiles::{read, DescriptorKind, Error, Part};
+///
+/// fn main() -> Result<(), Error> {
+///     let polymer = read("CC{[>][<]CC(C)[>],[<]CC[>][<]}CC")?;
+///     let object = polymer.stochastic_objects().next().expect("object");
+///
+///     assert_eq!(polymer.parts.len(), 3);
+///     assert!(matches!(&polymer.parts[0], Part::Smiles(atoms) if atoms.len() == 2));
+///     assert_eq!(object.repeat_units.len(), 2);
+///     assert_eq!(object.repeat_units[0].atoms.len(), 5);
+///     assert_eq!(object.repeat_units[0].attachments[1].id, 4);
+///     assert_eq!(
+///         object.repeat_units[0].attachments[1].descriptor.kind,
+///         DescriptorKind::Greater
+///     );
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if a stochastic object is unclosed or lacks a terminal
+/// descriptor, if a unit lacks a bonding descriptor, if a bonding
+/// descriptor is invalid or outside a stochastic object, or if a fragment
+/// isn't valid SMILES.
+pub fn read(polymer: &str) -> Result<Polymer, Error> {
+    if let Some(cursor) = polymer.bytes().position(|byte| !byte.is_ascii()) {
+        return Err(Error::Character(cursor));
+    }
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+
+    while start < polymer.len() {
+        let Some(open) = polymer[start..].find('{').map(|offset| start + offset) else {
+            parts.push(Part::Smiles(read_smiles(polymer, start..polymer.len())?));
+
+            break;
+        };
+
+        if open > start {
+            parts.push(Part::Smiles(read_smiles(polymer, start..open)?));
+        }
+
+        let close = polymer[open..]
+            .find('}')
+            .map(|offset| open + offset)
+            .ok_or(Error::EndOfLine)?;
+
+        parts.push(Part::Stochastic(read_object(polymer, open + 1..close)?));
+        start = close + 1;
+    }
+
+    if parts.is_empty() {
+        return Err(Error::EndOfLine);
+    }
+
+    Ok(Polymer { parts })
+}
+
+fn read_smiles(polymer: &str, range: Range<usize>) -> Result<Vec<graph::Atom>, Error> {
+    read_unit(polymer, range, false).map(|unit| unit.atoms)
+}
+
+fn read_object(polymer: &str, range: Range<usize>) -> Result<StochasticObject, Error> {
+    let text = &polymer[range.clone()];
+    let left_end = match text.find(']') {
+        Some(end) if text.starts_with('[') => end + 1,
+        _ => return Err(Error::Character(range.start)),
+    };
+    let right_start = match text.rfind('[') {
+        Some(start) if text.ends_with(']') && start >= left_end => start,
+        _ => return Err(Error::Character(range.end)),
+    };
+    let left = read_descriptor(polymer, range.start + 1..range.start + left_end - 1)?;
+    let right = read_descriptor(polymer, range.start + right_start + 1..range.end - 1)?;
+    let units = range.start + left_end..range.start + right_start;
+    let mut groups = split(polymer, units, ';');
+    let repeat_units = groups.next().expect("group");
+    let end_groups = groups.next();
+
+    if let Some(extra) = groups.next() {
+        return Err(Error::Character(extra.start - 1));
+    }
+
+    Ok(StochasticObject {
+        left,
+        repeat_units: read_units(polymer, repeat_units)?,
+        end_groups: match end_groups {
+            Some(end_groups) => read_units(polymer, end_groups)?,
+            None => Vec::new(),
+        },
+        right,
+    })
+}
+
+/// Reads the comma-separated units at `range`, each of which must have a
+/// bonding descriptor.
+fn read_units(polymer: &str, range: Range<usize>) -> Result<Vec<Unit>, Error> {
+    split(polymer, range, ',')
+        .map(|range| {
+            let unit = read_unit(polymer, range.clone(), true)?;
+
+            if unit.attachments.is_empty() {
+                return Err(Error::Character(range.start));
+            }
+
+            Ok(unit)
+        })
+        .collect()
+}
+
+/// Splits `range` of `polymer` at each `separator`.
+fn split(
+    polymer: &str,
+    range: Range<usize>,
+    separator: char,
+) -> impl Iterator<Item = Range<usize>> + '_ {
+    let start = range.start;
+
+    polymer[range]
+        .split(separator)
+        .scan(start, move |start, piece| {
+            let result = *start..*start + piece.len();
+
+            *start = result.end + 1;
+
+            Some(result)
+        })
+}
+
+/// Reads the descriptor between brackets at `range`, or `None` if empty.
+fn read_descriptor(polymer: &str, range: Range<usize>) -> Result<Option<BondingDescriptor>, Error> {
+    let text = &polymer[range.clone()];
+    let kind = match text.chars().next() {
+        None => return Ok(None),
+        Some('$') => DescriptorKind::Dollar,
+        Some('<') => DescriptorKind::Less,
+        Some('>') => DescriptorKind::Greater,
+        Some(_) => return Err(Error::Character(range.start)),
+    };
+    let index = &text[1..];
+
+    if let Some(offset) = index.find(|c: char| !c.is_ascii_digit()) {
+        return Err(Error::Character(range.start + 1 + offset));
+    }
+
+    Ok(Some(BondingDescriptor {
+        kind,
+        index: match index {
+            "" => None,
+            index => Some(
+                index
+                    .parse()
+                    .map_err(|_| Error::Character(range.start + 1))?,
+            ),
+        },
+    }))
+}
+
+/// Reads the SMILES at `range` with each bonding descriptor as a star
+/// atom, if `descriptors` are allowed.
+fn read_unit(polymer: &str, range: Range<usize>, descriptors: bool) -> Result<Unit, Error> {
+    let mut smiles = String::new();
+    // the cursor in `polymer` of each byte of `smiles`, and of its end
+    let mut origins = Vec::new();
+    let mut stars = Vec::new();
+    let mut cursor = range.start;
+
+    while cursor < range.end {
+        let rest = &polymer[cursor..range.end];
+
+        if rest.starts_with('[') && rest[1..].starts_with(['$', '<', '>']) {
+            if !descriptors {
+                return Err(Error::Character(cursor + 1));
+            }
+
+            let end = rest.find(']').ok_or(Error::Character(range.end))?;
+            let descriptor =
+                read_descriptor(polymer, cursor + 1..cursor + end)?.expect("descriptor");
+
+            stars.push((smiles.len(), descriptor));
+            smiles.push('*');
+            origins.push(cursor);
+            cursor += end + 1;
+        } else {
+            smiles.push_str(&rest[..1]);
+            origins.push(cursor);
+            cursor += 1;
+        }
+    }
+
+    origins.push(range.end);
+
+    let mut builder = Builder::default();
+    let mut trace = Trace::default();
+
+    read::read(&smiles, &mut builder, Some(&mut trace)).map_err(|error| match error {
+        // a unit ending early is cut off by what follows
+        ReadError::EndOfLine if range.end < polymer.len() => Error::Character(range.end),
+        ReadError::EndOfLine => Error::EndOfLine,
+        ReadError::Character(cursor) => Error::Character(origins[cursor]),
+    })?;
+
+    let atoms = builder.build().map_err(|error| match error {
+        graph::Error::Rnum(rid) => Error::Rnum(origins[trace.rnum(rid).expect("rnum").start]),
+        graph::Error::Join(sid, tid) => Error::Join(origins[trace.bond(sid, tid).expect("bond")]),
+    })?;
+    let attachments = stars
+        .into_iter()
+        .map(|(position, descriptor)| Attachment {
+            id: (0..atoms.len())
+                .find(|&id| trace.atom(id).is_some_and(|atom| atom.start == position))
+                .expect("star atom"),
+            descriptor,
+        })
+        .collect();
+
+    Ok(Unit { atoms, attachments })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::walk::walk;
+    use crate::write::Writer;
+    use pretty_assertions::assert_eq;
+
+    fn write(atoms: &[graph::Atom]) -> String {
+        let mut writer = Writer::default();
+
+        walk(atoms.to_vec(), &mut writer).unwrap();
+
+        writer.write()
+    }
+
+    fn descriptor(kind: DescriptorKind, index: Option<u16>) -> BondingDescriptor {
+        BondingDescriptor { kind, index }
+    }
+
+    fn object(polymer: &str) -> StochasticObject {
+        match read(polymer).unwrap().parts.as_slice() {
+            [Part::Stochastic(object)] => object.clone(),
+            parts => panic!("{parts:?}"),
+        }
+    }
+
+    #[test]
+    fn polyethylene() {
+        let object = object("{[][$]CC[$][]}");
+        let unit = &object.repeat_units[0];
+
+        assert_eq!(object.left, None);
+        assert_eq!(object.right, None);
+        assert_eq!(object.repeat_units.len(), 1);
+        assert_eq!(object.end_groups, []);
+        assert_eq!(write(&unit.atoms), "*CC*");
+        assert_eq!(
+            unit.attachments,
+            [
+                Attachment {
+                    id: 0,
+                    descriptor: descriptor(DescriptorKind::Dollar, None)
+                },
+                Attachment {
+                    id: 3,
+                    descriptor: descriptor(DescriptorKind::Dollar, None)
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn terminal_descriptors() {
+        let object = object("{[>1][<1]CC(=O)O[>1][<1]}");
+
+        assert_eq!(
+            object.left,
+            Some(descriptor(DescriptorKind::Greater, Some(1)))
+        );
+        assert_eq!(
+            object.right,
+            Some(descriptor(DescriptorKind::Less, Some(1)))
+        );
+        assert_eq!(write(&object.repeat_units[0].atoms), "*CC(=O)O*")
+    }
+
+    #[test]
+    fn branched_descriptor() {
+        let object = object("{[][$]CC([$])c1ccccc1[]}");
+        let unit = &object.repeat_units[0];
+
+        assert_eq!(
+            unit.attachments.iter().map(|a| a.id).collect::<Vec<_>>(),
+            [0, 3]
+        );
+        assert_eq!(unit.atoms[3].bonds.len(), 1)
+    }
+
+    #[test]
+    fn bond_to_descriptor() {
+        let object = object("{[][$]=CC=[$][]}");
+
+        assert_eq!(write(&object.repeat_units[0].atoms), "*=CC=*")
+    }
+
+    #[test]
+    fn copolymer_and_end_groups() {
+        let object = object("{[][<]CC[>],[<]CC(C)[>];[<][H],[>]O[]}");
+
+        assert_eq!(object.repeat_units.len(), 2);
+        assert_eq!(object.end_groups.len(), 2);
+        assert_eq!(write(&object.end_groups[0].atoms), "*[H]");
+        assert_eq!(
+            object.end_groups[1].attachments[0].descriptor,
+            descriptor(DescriptorKind::Greater, None)
+        )
+    }
+
+    #[test]
+    fn fragments() {
+        let polymer = read("CC{[$][$]CC[$][$]}CC{[$][$]C(F)C[$][]}").unwrap();
+        let parts = polymer
+            .parts
+            .iter()
+            .map(|part| match part {
+                Part::Smiles(atoms) => write(atoms),
+                Part::Stochastic(object) => format!("{{{}}}", write(&object.repeat_units[0].atoms)),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(parts, ["CC", "{*CC*}", "CC", "{*C(F)C*}"]);
+        assert_eq!(polymer.stochastic_objects().count(), 2)
+    }
+
+    #[test]
+    fn plain_smiles() {
+        assert_eq!(
+            read("CCO").unwrap().parts,
+            [Part::Smiles(read_smiles("CCO", 0..3).unwrap())]
+        )
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(read(""), Err(Error::EndOfLine))
+    }
+
+    #[test]
+    fn unclosed() {
+        assert_eq!(read("{[][$]CC[$][]"), Err(Error::EndOfLine))
+    }
+
+    #[test]
+    fn missing_terminal() {
+        assert_eq!(read("{[$]CC[$]}"), Err(Error::Character(4)));
+        assert_eq!(read("{CC}"), Err(Error::Character(1)))
+    }
+
+    #[test]
+    fn invalid_descriptor() {
+        assert_eq!(read("{[][$x]CC[$][]}"), Err(Error::Character(5)));
+        assert_eq!(read("{[Fe][$]CC[$][]}"), Err(Error::Character(2)))
+    }
+
+    #[test]
+    fn descriptor_outside_object() {
+        assert_eq!(read("[$]CC"), Err(Error::Character(1)))
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(
+            read("{[][$]C{[][$]CC[$][]}C[$][]}"),
+            Err(Error::Character(7))
+        )
+    }
+
+    #[test]
+    fn invalid_unit() {
+        assert_eq!(read("{[][$]C?C[$][]}"), Err(Error::Character(7)));
+        assert_eq!(read("{[][$]CC(,[$]CC[$][]}"), Err(Error::Character(9)));
+        assert_eq!(read("{[][$]C1CC[$][]}"), Err(Error::Rnum(7)));
+        assert_eq!(read("{[][]}"), Err(Error::Character(3)));
+        assert_eq!(read("{[][$]CC[$];;[]}"), Err(Error::Character(12)))
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+/// A CIP stereodescriptor of a tetrahedral center. Pseudoasymmetric
+/// centers, whose ranking depends on two enantiomorphic neighbors, take
+/// lowercase `r` and `s`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Descriptor {
+    R,
+    S,
+    PseudoR,
+    PseudoS,
+}
+
+impl Descriptor {
+    /// Returns true for `R` and `r`.
+    pub const fn is_right(&self) -> bool {
+        matches!(self, Self::R | Self::PseudoR)
+    }
+}
+
+impl fmt::Display for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::R => write!(f, "R"),
+            Self::S => write!(f, "S"),
+            Self::PseudoR => write!(f, "r"),
+            Self::PseudoS => write!(f, "s"),
+        }
+    }
+}
+
+/// A CIP stereodescriptor of a double bond: `Z` when the top-ranked
+/// neighbors of each end are on the same side, and `E` otherwise.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BondDescriptor {
+    E,
+    Z,
+}
+
+impl fmt::Display for BondDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::E => write!(f, "E"),
+            Self::Z => write!(f, "Z"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn display() {
+        let labels = [
+            Descriptor::R,
+            Descriptor::S,
+            Descriptor::PseudoR,
+            Descriptor::PseudoS,
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+        assert_eq!(labels, vec!["R", "S", "r", "s"]);
+        assert_eq!(BondDescriptor::E.to_string(), "E");
+        assert_eq!(BondDescriptor::Z.to_string(), "Z")
+    }
+}
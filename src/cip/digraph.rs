@@ -0,0 +1,361 @@
+use std::cmp::Ordering;
+
+use super::Descriptor;
+use crate::feature::{AtomKind, Symbol};
+use crate::graph::Atom;
+use crate::Element;
+
+/// Nodes beyond which a digraph isn't expanded, leaving deeper ties
+/// unbroken.
+const LIMIT: usize = 1 << 16;
+
+/// The hierarchical digraph of a molecule from one root atom, expanded as
+/// comparisons need it.
+///
+/// Every path from the root is a branch of its own. An atom met again
+/// along a path, and either end of a multiple bond, is represented by a
+/// duplicate node, whose own substituents are phantom atoms.
+pub(crate) struct Digraph<'a> {
+    atoms: &'a [Atom],
+    descriptors: &'a [Option<Descriptor>],
+    nodes: Vec<Node>,
+}
+
+struct Node {
+    atom: Option<usize>,
+    parent: Option<usize>,
+    number: u32,
+    mass: u32,
+    duplicate: bool,
+    children: Option<Vec<usize>>,
+}
+
+/// A property compared by one of the sequence rules.
+#[derive(Clone, Copy)]
+enum Key {
+    Number,
+    Mass,
+}
+
+/// The outcome of comparing two branches.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct Rank {
+    pub ordering: Ordering,
+    /// True if only the chirality of stereocenters told the branches apart.
+    pub by_chirality: bool,
+}
+
+impl<'a> Digraph<'a> {
+    /// Constructs a digraph rooted at atom `root`, with the known
+    /// descriptors of other atoms.
+    pub fn new(atoms: &'a [Atom], root: usize, descriptors: &'a [Option<Descriptor>]) -> Self {
+        let mut result = Self {
+            atoms,
+            descriptors,
+            nodes: Vec::new(),
+        };
+
+        result.push(Some(root), None, false);
+
+        result
+    }
+
+    /// Returns the root node.
+    pub const fn root(&self) -> usize {
+        0
+    }
+
+    /// Adds a node for an implicit hydrogen or, if `hydrogen` is false, a
+    /// lone pair, as a child of the root.
+    pub fn add_implicit(&mut self, hydrogen: bool) -> usize {
+        let (number, mass) = if hydrogen {
+            (1, weight(Element::H))
+        } else {
+            (0, 0)
+        };
+
+        self.nodes.push(Node {
+            atom: None,
+            parent: Some(self.root()),
+            number,
+            mass,
+            duplicate: false,
+            children: Some(Vec::new()),
+        });
+
+        self.nodes.len() - 1
+    }
+
+    /// Adds a node for atom `id` as a child of the root.
+    pub fn add_neighbor(&mut self, id: usize) -> usize {
+        self.push(Some(id), Some(self.root()), false)
+    }
+
+    /// Compares the branches from nodes `left` and `right` by atomic
+    /// number, then mass, then like and unlike pairs of descriptors, then
+    /// descriptors alone.
+    pub fn rank(&mut self, left: usize, right: usize) -> Rank {
+        let mut ordering = self.compare(left, right, Key::Number);
+
+        if ordering == Ordering::Equal {
+            ordering = self.compare(left, right, Key::Mass);
+        }
+
+        if ordering == Ordering::Equal {
+            ordering = self.compare_pairs(left, right);
+        }
+
+        if ordering != Ordering::Equal {
+            return Rank {
+                ordering,
+                by_chirality: false,
+            };
+        }
+
+        Rank {
+            ordering: self.compare_chirality(left, right),
+            by_chirality: true,
+        }
+    }
+
+    fn push(&mut self, atom: Option<usize>, parent: Option<usize>, duplicate: bool) -> usize {
+        let (number, mass) = atom.map_or((0, 0), |id| properties(&self.atoms[id].kind));
+
+        self.nodes.push(Node {
+            atom,
+            parent,
+            number,
+            mass,
+            duplicate,
+            children: duplicate.then(Vec::new),
+        });
+
+        self.nodes.len() - 1
+    }
+
+    fn key(&self, node: usize, key: Key) -> u32 {
+        match key {
+            Key::Number => self.nodes[node].number,
+            Key::Mass => self.nodes[node].mass,
+        }
+    }
+
+    /// Compares branches sphere by sphere, each sphere as the sets of
+    /// substituents of the nodes before it, in order of precedence.
+    fn compare(&mut self, left: usize, right: usize, key: Key) -> Ordering {
+        let ordering = self.key(left, key).cmp(&self.key(right, key));
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        let mut lefts = vec![left];
+        let mut rights = vec![right];
+
+        while !lefts.is_empty() || !rights.is_empty() {
+            let mut next_lefts = Vec::new();
+            let mut next_rights = Vec::new();
+
+            for idx in 0..lefts.len().max(rights.len()) {
+                let left_set = lefts.get(idx).map_or(Vec::new(), |&n| self.children(n));
+                let right_set = rights.get(idx).map_or(Vec::new(), |&n| self.children(n));
+
+                // missing substituents are phantom atoms, which rank lowest
+                for set_idx in 0..left_set.len().max(right_set.len()) {
+                    let left_key = left_set.get(set_idx).map_or(0, |&n| self.key(n, key));
+                    let right_key = right_set.get(set_idx).map_or(0, |&n| self.key(n, key));
+                    let ordering = left_key.cmp(&right_key);
+
+                    if ordering != Ordering::Equal {
+                        return ordering;
+                    }
+                }
+
+                next_lefts.extend(left_set);
+                next_rights.extend(right_set);
+            }
+
+            lefts = next_lefts;
+            rights = next_rights;
+        }
+
+        Ordering::Equal
+    }
+
+    /// Compares the descriptors of branches in order of precedence, as
+    /// pairs with the first: like pairs rank above unlike ones, and
+    /// stereocenters above other atoms.
+    fn compare_pairs(&mut self, left: usize, right: usize) -> Ordering {
+        let left = self.descriptors_of(left);
+        let right = self.descriptors_of(right);
+        let likes = |descriptors: &[Descriptor]| {
+            descriptors
+                .iter()
+                .map(|descriptor| descriptor.is_right() == descriptors[0].is_right())
+                .collect::<Vec<_>>()
+        };
+        let (left, right) = (likes(&left), likes(&right));
+
+        for idx in 0..left.len().max(right.len()) {
+            let ordering = left.get(idx).cmp(&right.get(idx));
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+
+        Ordering::Equal
+    }
+
+    /// Compares the descriptors of branches in order of precedence, with
+    /// `R` above `S`.
+    fn compare_chirality(&mut self, left: usize, right: usize) -> Ordering {
+        let left = self.descriptors_of(left);
+        let right = self.descriptors_of(right);
+
+        left.iter()
+            .map(Descriptor::is_right)
+            .cmp(right.iter().map(Descriptor::is_right))
+    }
+
+    /// Returns the descriptors of the atoms in the branch from `node`, in
+    /// order of precedence.
+    fn descriptors_of(&mut self, node: usize) -> Vec<Descriptor> {
+        let mut result = Vec::new();
+        let mut sphere = vec![node];
+
+        while !sphere.is_empty() {
+            let mut next = Vec::new();
+
+            for node in sphere {
+                let Node {
+                    atom, duplicate, ..
+                } = self.nodes[node];
+
+                if let (Some(id), false) = (atom, duplicate) {
+                    result.extend(self.descriptors[id]);
+                }
+
+                next.extend(self.children(node));
+            }
+
+            sphere = next;
+        }
+
+        result
+    }
+
+    /// Returns the substituents of `node` in decreasing order of
+    /// precedence, expanding it if needed.
+    fn children(&mut self, node: usize) -> Vec<usize> {
+        if let Some(children) = &self.nodes[node].children {
+            return children.clone();
+        }
+
+        let mut children = self.expand(node);
+
+        // insertion sort, as comparisons expand the digraph
+        for idx in 1..children.len() {
+            let mut position = idx;
+
+            while position > 0
+                && self
+                    .rank(children[position - 1], children[position])
+                    .ordering
+                    == Ordering::Less
+            {
+                children.swap(position - 1, position);
+                position -= 1;
+            }
+        }
+
+        self.nodes[node].children = Some(children.clone());
+
+        children
+    }
+
+    /// Adds the substituents of `node`, unordered.
+    fn expand(&mut self, node: usize) -> Vec<usize> {
+        let id = self.nodes[node].atom.expect("atom");
+        let parent = self.nodes[node]
+            .parent
+            .and_then(|parent| self.nodes[parent].atom);
+        let atom = &self.atoms[id];
+        let mut children = Vec::new();
+
+        if self.nodes.len() > LIMIT {
+            return children;
+        }
+
+        for bond in &atom.bonds {
+            let extra = usize::from(bond.order().saturating_sub(1));
+
+            if Some(bond.tid) == parent {
+                for _ in 0..extra {
+                    children.push(self.push(Some(bond.tid), Some(node), true));
+                }
+
+                continue;
+            }
+
+            let duplicate = self.on_path(node, bond.tid);
+
+            children.push(self.push(Some(bond.tid), Some(node), duplicate));
+
+            for _ in 0..extra {
+                children.push(self.push(Some(bond.tid), Some(node), true));
+            }
+        }
+
+        for _ in 0..atom.suppressed_hydrogens() {
+            self.nodes.push(Node {
+                atom: None,
+                parent: Some(node),
+                number: 1,
+                mass: weight(Element::H),
+                duplicate: false,
+                children: Some(Vec::new()),
+            });
+            children.push(self.nodes.len() - 1);
+        }
+
+        children
+    }
+
+    /// Returns true if atom `id` is at `node` or one of its ancestors.
+    fn on_path(&self, node: usize, id: usize) -> bool {
+        let mut current = Some(node);
+
+        while let Some(node) = current {
+            if self.nodes[node].atom == Some(id) {
+                return true;
+            }
+
+            current = self.nodes[node].parent;
+        }
+
+        false
+    }
+}
+
+/// Returns the atomic number and the mass, in thousandths, of atoms of
+/// `kind`. Atoms without an isotope take the standard atomic weight.
+fn properties(kind: &AtomKind) -> (u32, u32) {
+    let (symbol, isotope) = match *kind {
+        AtomKind::Symbol(symbol) => (symbol, None),
+        AtomKind::Bracket {
+            symbol, isotope, ..
+        } => (symbol, isotope),
+    };
+    let element = match symbol {
+        Symbol::Star => return (0, 0),
+        Symbol::Aliphatic(element) | Symbol::Aromatic(element) => element,
+    };
+    let mass = isotope.map_or(weight(element), |isotope| isotope.mass_number() * 1000);
+
+    (element.atomic_number(), mass)
+}
+
+fn weight(element: Element) -> u32 {
+    (f64::from(element.atomic_weight()) * 1000.0).round() as u32
+}
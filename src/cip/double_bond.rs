@@ -0,0 +1,212 @@
+use std::cmp::Ordering;
+
+use super::digraph::Digraph;
+use super::tetrahedral::{descriptors, kekule};
+use super::{BondDescriptor, Descriptor};
+use crate::feature::BondKind;
+use crate::graph::{cis_trans, geometry, Atom};
+
+/// The CIP descriptor of a double bond with specified geometry.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DoubleBond {
+    /// The lower atom index.
+    pub sid: usize,
+    /// The higher atom index.
+    pub tid: usize,
+    /// The descriptor, or `None` if the specification is ambiguous because
+    /// the neighbors of one end rank the same.
+    pub descriptor: Option<BondDescriptor>,
+}
+
+/// Returns the CIP descriptor of every double bond whose geometry is given
+/// by directional bonds, including those of ring closures, ordered by atom
+/// pair.
+///
+/// Neighbors of each end are ranked as for [`centers`](super::centers).
+/// The atom pair locates the bond in the original string with
+/// [`Trace`](crate::read::Trace).
+///
+/// ```
+/// use yowl::cip::{double_bonds, BondDescriptor};
+/// use yowl::graph::Builder;
+/// use yowl::read::{read, Trace};
+///
+/// let mut builder = Builder::default();
+/// let mut trace = Trace::default();
+///
+/// read("C/C=C(F)/C", &mut builder, Some(&mut trace)).expect("read");
+///
+/// let atoms = builder.build().expect("atoms");
+/// let bonds = double_bonds(&atoms)
+///     .into_iter()
+///     .map(|bond| (trace.bond(bond.sid, bond.tid), bond.descriptor))
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(bonds, vec![(Some(3), Some(BondDescriptor::Z))]);
+/// ```
+pub fn double_bonds(atoms: &[Atom]) -> Vec<DoubleBond> {
+    let kekule = kekule(atoms);
+    let descriptors = descriptors(&kekule);
+    let mut result = Vec::new();
+
+    for (sid, atom) in atoms.iter().enumerate() {
+        for bond in &atom.bonds {
+            let tid = bond.tid;
+
+            if sid > tid || bond.kind != BondKind::Double || geometry(atoms, sid, tid).is_none() {
+                continue;
+            }
+
+            result.push(DoubleBond {
+                sid,
+                tid,
+                descriptor: describe(atoms, &kekule, &descriptors, sid, tid),
+            })
+        }
+    }
+
+    result
+}
+
+/// The highest ranked substituent of one end of a double bond.
+enum Top {
+    Neighbor(usize),
+    /// An implicit hydrogen, above the one explicit neighbor.
+    Hydrogen(usize),
+}
+
+fn describe(
+    atoms: &[Atom],
+    kekule: &[Atom],
+    descriptors: &[Option<Descriptor>],
+    sid: usize,
+    tid: usize,
+) -> Option<BondDescriptor> {
+    let (x, left) = top(atoms, kekule, descriptors, sid, tid)?;
+    let (y, right) = top(atoms, kekule, descriptors, tid, sid)?;
+    let cis = cis_trans(atoms, sid, tid, x, y)? != (left != right);
+
+    Some(if cis {
+        BondDescriptor::Z
+    } else {
+        BondDescriptor::E
+    })
+}
+
+/// Returns the explicit neighbor of `sid` used for geometry, and whether
+/// the highest ranked substituent is on the opposite side of it.
+fn top(
+    atoms: &[Atom],
+    kekule: &[Atom],
+    descriptors: &[Option<Descriptor>],
+    sid: usize,
+    tid: usize,
+) -> Option<(usize, bool)> {
+    let mut digraph = Digraph::new(kekule, sid, descriptors);
+    let mut ligands = Vec::new();
+
+    for bond in &atoms[sid].bonds {
+        if bond.tid != tid {
+            ligands.push((digraph.add_neighbor(bond.tid), Top::Neighbor(bond.tid)));
+        }
+    }
+
+    if let ([(_, Top::Neighbor(id))], 1..) = (&ligands[..], atoms[sid].suppressed_hydrogens()) {
+        ligands.push((digraph.add_implicit(true), Top::Hydrogen(*id)));
+    }
+
+    let [(first, _), (second, _)] = ligands[..] else {
+        return None;
+    };
+    let top = match digraph.rank(first, second).ordering {
+        Ordering::Greater => &ligands[0].1,
+        Ordering::Less => &ligands[1].1,
+        Ordering::Equal => return None,
+    };
+
+    Some(match *top {
+        Top::Neighbor(id) => (id, false),
+        Top::Hydrogen(id) => (id, true),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Builder;
+    use crate::read::read;
+    use pretty_assertions::assert_eq;
+
+    fn labels(smiles: &str) -> Vec<Option<String>> {
+        let mut builder = Builder::default();
+
+        read(smiles, &mut builder, None).unwrap();
+
+        double_bonds(&builder.build().unwrap())
+            .into_iter()
+            .map(|bond| bond.descriptor.map(|d| d.to_string()))
+            .collect()
+    }
+
+    fn e() -> Vec<Option<String>> {
+        vec![Some("E".to_string())]
+    }
+
+    fn z() -> Vec<Option<String>> {
+        vec![Some("Z".to_string())]
+    }
+
+    #[test]
+    fn disubstituted() {
+        assert_eq!(labels("F/C=C/F"), e());
+        assert_eq!(labels("F/C=C\\F"), z());
+        assert_eq!(labels("C(/F)=C/F"), z());
+        assert_eq!(labels("C/C=C/C"), e())
+    }
+
+    #[test]
+    fn ranked_ends() {
+        // the fluorines outrank the methyls
+        assert_eq!(labels("C/C(F)=C/C"), z());
+        assert_eq!(labels("C/C(F)=C(/C)Cl"), e());
+        assert_eq!(labels("C/C(F)=C(/Cl)C"), z());
+        // 2-butenoic acid written through the hydrogen-free end
+        assert_eq!(labels("OC(=O)/C=C/C"), e())
+    }
+
+    #[test]
+    fn implicit_hydrogen() {
+        // the hydrogen outranks a star, on the far side of the slash
+        assert_eq!(labels("*/C=C/F"), z());
+        assert_eq!(labels("*/C=C\\F"), e())
+    }
+
+    #[test]
+    fn ring_closures() {
+        assert_eq!(labels("F/C=C/1.Cl1"), labels("F/C=C/Cl"));
+        assert_eq!(labels("F/C=C/1.Cl1"), e());
+        assert_eq!(labels("C1CCC/C=C/CC1"), e());
+        assert_eq!(labels("C1CCC/C=C\\CC1"), z())
+    }
+
+    #[test]
+    fn several() {
+        assert_eq!(
+            labels("C/C=C/C=C\\C"),
+            vec![Some("E".to_string()), Some("Z".to_string())]
+        )
+    }
+
+    #[test]
+    fn ambiguous() {
+        assert_eq!(labels("C/C(C)=C/F"), vec![None]);
+        assert_eq!(labels("C/C=C(/F)F"), vec![None])
+    }
+
+    #[test]
+    fn unspecified() {
+        assert_eq!(labels("FC=CF"), vec![]);
+        assert_eq!(labels("F/C=CF"), vec![])
+    }
+}
@@ -0,0 +1,8 @@
+mod descriptor;
+mod digraph;
+mod double_bond;
+mod tetrahedral;
+
+pub use descriptor::{BondDescriptor, Descriptor};
+pub use double_bond::{double_bonds, DoubleBond};
+pub use tetrahedral::{centers, Center};
@@ -0,0 +1,310 @@
+use std::cmp::Ordering;
+
+use super::digraph::Digraph;
+use super::Descriptor;
+use crate::feature::{AtomKind, Configuration};
+use crate::graph::{kekulize, Atom};
+use crate::walk::is_odd;
+
+/// The CIP descriptor of a tetrahedral center.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Center {
+    pub id: usize,
+    /// The descriptor, or `None` if the specification is ambiguous because
+    /// two neighbors rank the same.
+    pub descriptor: Option<Descriptor>,
+}
+
+/// Returns the CIP descriptor of every atom with a `TH1` or `TH2`
+/// configuration, in atom order.
+///
+/// Neighbors are ranked on the hierarchical digraph from each center, by
+/// atomic number, then mass, then like and unlike pairs of descriptors in
+/// each branch, then `R` over `S`. Aromatic rings are ranked in a Kekulé
+/// form. An implicit hydrogen ranks as hydrogen, and the missing neighbor
+/// of a center with three, such as a lone pair, ranks lowest.
+///
+/// ```
+/// use yowl::cip::{centers, Center, Descriptor};
+/// use yowl::graph::Builder;
+/// use yowl::read::read;
+///
+/// let mut builder = Builder::default();
+///
+/// read("N[C@@H](C)C(=O)O", &mut builder, None).expect("read");
+///
+/// let atoms = builder.build().expect("atoms");
+///
+/// assert_eq!(
+///     centers(&atoms),
+///     vec![Center {
+///         id: 1,
+///         descriptor: Some(Descriptor::S)
+///     }]
+/// );
+/// ```
+pub fn centers(atoms: &[Atom]) -> Vec<Center> {
+    let descriptors = descriptors(&kekule(atoms));
+
+    (0..atoms.len())
+        .filter(|&id| tetrahedral(&atoms[id].kind).is_some())
+        .map(|id| Center {
+            id,
+            descriptor: descriptors[id],
+        })
+        .collect()
+}
+
+/// Returns a Kekulé form of `atoms` for ranking, or a copy if there's none.
+pub(crate) fn kekule(atoms: &[Atom]) -> Vec<Atom> {
+    let mut result = atoms.to_vec();
+
+    if kekulize(&mut result).is_err() {
+        result = atoms.to_vec();
+    }
+
+    result
+}
+
+/// Returns the descriptor of each atom of `atoms` that has one.
+pub(crate) fn descriptors(atoms: &[Atom]) -> Vec<Option<Descriptor>> {
+    let ids = (0..atoms.len())
+        .filter(|&id| tetrahedral(&atoms[id].kind).is_some())
+        .collect::<Vec<_>>();
+    let mut result = vec![None; atoms.len()];
+
+    // descriptors found in one pass can rank neighbors in the next
+    for _ in 0..=ids.len() {
+        let next = ids
+            .iter()
+            .map(|&id| describe(atoms, id, &result))
+            .collect::<Vec<_>>();
+        let mut changed = false;
+
+        for (&id, descriptor) in ids.iter().zip(next) {
+            changed |= result[id] != descriptor;
+            result[id] = descriptor;
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    result
+}
+
+fn tetrahedral(kind: &AtomKind) -> Option<Configuration> {
+    match kind {
+        AtomKind::Bracket {
+            configuration: Some(configuration @ (Configuration::TH1 | Configuration::TH2)),
+            ..
+        } => Some(*configuration),
+        _ => None,
+    }
+}
+
+fn describe(atoms: &[Atom], id: usize, descriptors: &[Option<Descriptor>]) -> Option<Descriptor> {
+    let atom = &atoms[id];
+    let configuration = tetrahedral(&atom.kind)?;
+    let mut digraph = Digraph::new(atoms, id, descriptors);
+    let hydrogens = match atom.kind {
+        AtomKind::Bracket { hcount, .. } => hcount.as_ref().map_or(0, u8::from),
+        AtomKind::Symbol(_) => 0,
+    };
+    let mut ligands = Vec::new();
+
+    // in stored order, with a virtual hydrogen or lone pair first
+    if hydrogens > 0 || atom.bonds.len() == 3 {
+        ligands.push(digraph.add_implicit(hydrogens > 0));
+    }
+
+    for _ in 1..hydrogens {
+        ligands.push(digraph.add_implicit(true));
+    }
+
+    for bond in &atom.bonds {
+        ligands.push(digraph.add_neighbor(bond.tid));
+    }
+
+    if ligands.len() != 4 {
+        return None;
+    }
+
+    let mut ranked = (0..4).collect::<Vec<_>>();
+
+    ranked.sort_by(|&left, &right| digraph.rank(ligands[right], ligands[left]).ordering);
+
+    let mut pseudo = false;
+
+    for pair in ranked.windows(2) {
+        let rank = digraph.rank(ligands[pair[0]], ligands[pair[1]]);
+
+        if rank.ordering != Ordering::Greater {
+            return None;
+        }
+
+        pseudo |= rank.by_chirality;
+    }
+
+    // TH1 with neighbors by decreasing rank is S
+    let right = (configuration == Configuration::TH2) != is_odd(&ranked);
+
+    Some(match (right, pseudo) {
+        (true, false) => Descriptor::R,
+        (false, false) => Descriptor::S,
+        (true, true) => Descriptor::PseudoR,
+        (false, true) => Descriptor::PseudoS,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Builder;
+    use crate::read::read;
+    use pretty_assertions::assert_eq;
+
+    fn labels(smiles: &str) -> Vec<(usize, Option<String>)> {
+        let mut builder = Builder::default();
+
+        read(smiles, &mut builder, None).unwrap();
+
+        centers(&builder.build().unwrap())
+            .into_iter()
+            .map(|center| (center.id, center.descriptor.map(|d| d.to_string())))
+            .collect()
+    }
+
+    fn label(smiles: &str) -> Option<String> {
+        let labels = labels(smiles);
+
+        assert_eq!(labels.len(), 1, "{smiles}");
+
+        labels[0].1.clone()
+    }
+
+    fn r() -> Option<String> {
+        Some("R".to_string())
+    }
+
+    fn s() -> Option<String> {
+        Some("S".to_string())
+    }
+
+    #[test]
+    fn halomethane() {
+        assert_eq!(label("F[C@H](Cl)Br"), r());
+        assert_eq!(label("F[C@@H](Cl)Br"), s());
+        assert_eq!(label("[C@@H](F)(Cl)Br"), r());
+        assert_eq!(label("Br[C@@H](Cl)F"), r())
+    }
+
+    #[test]
+    fn amino_acids() {
+        // L-alanine, L-serine and L-cysteine, which is R
+        assert_eq!(label("N[C@@H](C)C(=O)O"), s());
+        assert_eq!(label("N[C@@H](CO)C(=O)O"), s());
+        assert_eq!(label("N[C@@H](CS)C(=O)O"), r())
+    }
+
+    #[test]
+    fn duplicate_atoms() {
+        // (R)-limonene ranks ring branches by their double bond
+        assert_eq!(label("CC1=CC[C@@H](CC1)C(=C)C"), r());
+        assert_eq!(label("CC1=CC[C@H](CC1)C(=C)C"), s());
+        // the aldehyde outranks the hydroxymethyl group as O, O, H
+        assert_eq!(label("OC[C@@H](O)C=O"), r())
+    }
+
+    #[test]
+    fn cholesterol() {
+        // 3S, 8S, 9S, 10R, 13R, 14S, 17R, 20R
+        let smiles =
+            "C[C@H](CCCC(C)C)[C@H]1CC[C@@H]2[C@@]1(CC[C@H]3[C@H]2CC=C4[C@@]3(CC[C@@H](C4)O)C)C";
+        let labels = labels(smiles)
+            .into_iter()
+            .map(|(id, label)| (id, label.unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            labels,
+            [
+                (1, "R"),
+                (8, "R"),
+                (11, "S"),
+                (12, "R"),
+                (15, "S"),
+                (16, "S"),
+                (20, "R"),
+                (23, "S")
+            ]
+            .map(|(id, label)| (id, label.to_string()))
+        )
+    }
+
+    #[test]
+    fn aromatic() {
+        assert_eq!(label("C[C@H](O)c1ccccc1"), s());
+        assert_eq!(label("C[C@@H](O)c1ccccc1"), r());
+        assert_eq!(label("C[C@H](c1ccccc1)O"), r())
+    }
+
+    #[test]
+    fn isotopes() {
+        assert_eq!(label("O[C@@H]([2H])C"), s());
+        assert_eq!(label("O[C@H]([2H])C"), r());
+        assert_eq!(label("O[C@@H]([H])C"), None)
+    }
+
+    #[test]
+    fn lone_pair() {
+        // the lone pair of a sulfoxide ranks lowest
+        let left = label("C[S@](=O)CC");
+        let right = label("C[S@@](=O)CC");
+
+        assert!(left.is_some());
+        assert!(right.is_some());
+        assert_ne!(left, right)
+    }
+
+    #[test]
+    fn ambiguous() {
+        assert_eq!(label("C[C@H](C)CC"), None);
+        assert_eq!(label("[C@H](C)(C)C"), None);
+        assert_eq!(labels("C[C@H]1CC[C@@H](C)CC1"), vec![(1, None), (4, None)]);
+        assert_eq!(labels("CC[C@H](O)CC"), vec![(2, None)])
+    }
+
+    #[test]
+    fn unspecified() {
+        assert_eq!(labels("CC(O)CC"), vec![])
+    }
+
+    #[test]
+    fn pseudoasymmetric() {
+        let smiles = "C[C@@H](O)[C@@H](O)[C@@H](O)C";
+        let mirror = "C[C@H](O)[C@H](O)[C@H](O)C";
+        let inverted = "C[C@@H](O)[C@H](O)[C@@H](O)C";
+        let labels = labels(smiles);
+
+        assert_eq!(labels[0].1, r());
+        assert_eq!(labels[2].1, s());
+        assert!(matches!(labels[1].1.as_deref(), Some("r" | "s")));
+        assert_eq!(self::labels(mirror)[1], labels[1]);
+        assert_ne!(self::labels(inverted)[1], labels[1]);
+        assert_eq!(self::labels(inverted)[0].1, r())
+    }
+
+    #[test]
+    fn like_pairs() {
+        // a like pair of centers outranks an unlike one
+        let smiles = "C[C@@H](O)[C@@H](C)[C@H](O)[C@H](C)[C@@H](O)C";
+        let mirror = "C[C@H](O)[C@H](C)[C@@H](O)[C@@H](C)[C@H](O)C";
+        let center = |smiles| labels(smiles)[2].1.clone();
+
+        assert!(matches!(center(smiles).as_deref(), Some("R" | "S")));
+        assert_ne!(center(smiles), center(mirror))
+    }
+}
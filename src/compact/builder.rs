@@ -0,0 +1,282 @@
+use super::Molecule;
+use crate::feature::{AtomKind, BondKind, Rnum};
+use crate::graph::{reconcile, Bond, Error};
+use crate::walk::Follower;
+
+/// A `Follower` that builds a `Molecule`, producing the same atoms and
+/// bond order as `graph::Builder` does.
+///
+/// Bonds are collected into a single list and sorted into rows on
+/// `build`, and open ring closures are kept in a fixed table indexed by
+/// ring closure number, so a Builder allocates a handful of buffers
+/// whatever the size of the molecule. Using `take` keeps those buffers
+/// for the next molecule.
+///
+/// ```
+/// use yowl::compact::{Builder, Molecule};
+/// use yowl::graph;
+/// use yowl::read::read;
+///
+/// let mut builder = Builder::default();
+/// let mut graph_builder = graph::Builder::default();
+///
+/// read("CC(=O)N", &mut builder, None).expect("read");
+/// read("CC(=O)N", &mut graph_builder, None).expect("read");
+///
+/// let atoms = graph_builder.build().expect("atoms");
+///
+/// assert_eq!(builder.build(), Ok(Molecule::from(atoms)));
+/// ```
+#[derive(Debug, PartialEq, Default)]
+pub struct Builder {
+    kinds: Vec<AtomKind>,
+    edges: Vec<Edge>,
+    stack: Vec<usize>,
+    opens: Vec<Option<usize>>,
+    error: Option<Error>,
+    ring_idx: usize,
+}
+
+impl Builder {
+    /// Builds the representation created by using the `Follower` trait
+    /// methods.
+    pub fn build(mut self) -> Result<Molecule, Error> {
+        self.take()
+    }
+
+    /// Builds the representation created by using the `Follower` trait
+    /// methods, leaving this Builder empty but with its allocations
+    /// retained for reuse.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an atom has more than one `Up` or `Down` bond, as
+    /// `graph::Builder` does.
+    pub fn take(&mut self) -> Result<Molecule, Error> {
+        let result = self.assemble();
+
+        self.kinds.clear();
+        self.edges.clear();
+        self.stack.clear();
+        self.opens.iter_mut().for_each(|open| *open = None);
+        self.error = None;
+        self.ring_idx = 0;
+
+        result
+    }
+
+    fn assemble(&mut self) -> Result<Molecule, Error> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        // report the first unclosed ring in atom order, as graph::Builder does
+        if let Some(edge) = self
+            .edges
+            .iter()
+            .filter(|edge| edge.ring.is_some())
+            .min_by_key(|edge| edge.sid)
+        {
+            return Err(Error::Rnum(edge.ring.expect("ring")));
+        }
+
+        let size = self.kinds.len();
+        let mut offsets = vec![0; size + 1];
+
+        for edge in &self.edges {
+            offsets[edge.sid + 1] += 1;
+        }
+
+        for idx in 0..size {
+            offsets[idx + 1] += offsets[idx];
+        }
+
+        let mut cursors = offsets[..size].to_vec();
+        let mut bonds = vec![Bond::new(BondKind::Elided, 0); self.edges.len()];
+
+        for edge in &self.edges {
+            bonds[cursors[edge.sid]] = Bond::new(edge.kind, edge.tid);
+            cursors[edge.sid] += 1;
+        }
+
+        for (id, window) in offsets.windows(2).enumerate() {
+            check_stereo(id, &bonds[window[0]..window[1]]);
+        }
+
+        Ok(Molecule::new(self.kinds.clone(), offsets, bonds))
+    }
+}
+
+impl Follower for Builder {
+    fn root(&mut self, root: AtomKind) {
+        self.stack.push(self.kinds.len());
+        self.kinds.push(root);
+    }
+
+    fn extend(&mut self, bond_kind: BondKind, mut atom_kind: AtomKind) {
+        let sid = *self.stack.last().expect("last on stack");
+        let tid = self.kinds.len();
+
+        atom_kind.invert_configuration();
+
+        self.edges.push(Edge::new(sid, bond_kind, tid));
+        self.edges.push(Edge::new(tid, bond_kind.reverse(), sid));
+        self.stack.push(tid);
+        self.kinds.push(atom_kind);
+    }
+
+    fn join(&mut self, bond_kind: BondKind, rnum: Rnum) {
+        let sid = *self.stack.last().expect("last on stack");
+        let slot = usize::from(rnum.value());
+
+        if self.opens.len() <= slot {
+            self.opens.resize(slot + 1, None);
+        }
+
+        if let Some(edge_idx) = self.opens[slot].take() {
+            let edge = &mut self.edges[edge_idx];
+            let tid = edge.sid;
+
+            match reconcile(edge.kind, bond_kind) {
+                Some((left_kind, right_kind)) => {
+                    edge.kind = left_kind;
+                    edge.tid = sid;
+                    edge.ring = None;
+
+                    self.edges.push(Edge::new(sid, right_kind, tid));
+                }
+                None => {
+                    self.error.get_or_insert(Error::Join(sid, tid));
+                }
+            }
+        } else {
+            self.opens[slot] = Some(self.edges.len());
+            self.edges.push(Edge {
+                sid,
+                kind: bond_kind,
+                tid: sid,
+                ring: Some(self.ring_idx),
+            });
+        }
+
+        self.ring_idx += 1;
+    }
+
+    fn pop(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.stack.pop();
+        }
+    }
+}
+
+/// A bond from `sid`. While `ring` is present, the bond is an open ring
+/// closure and `tid` is meaningless.
+#[derive(Debug, PartialEq)]
+struct Edge {
+    sid: usize,
+    kind: BondKind,
+    tid: usize,
+    ring: Option<usize>,
+}
+
+impl Edge {
+    const fn new(sid: usize, kind: BondKind, tid: usize) -> Self {
+        Self {
+            sid,
+            kind,
+            tid,
+            ring: None,
+        }
+    }
+}
+
+fn check_stereo(id: usize, bonds: &[Bond]) {
+    let count = |kind| bonds.iter().filter(|bond| bond.kind == kind).count();
+
+    assert!(
+        count(BondKind::Up) <= 1,
+        "Conflicting stereochemistry (multiple Up bonds) at atom index {id}"
+    );
+    assert!(
+        count(BondKind::Down) <= 1,
+        "Conflicting stereochemistry (multiple Down bonds) at atom index {id}"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph;
+    use crate::read::read;
+    use pretty_assertions::assert_eq;
+
+    fn compare(smiles: &str) {
+        let mut builder = Builder::default();
+        let mut graph_builder = graph::Builder::default();
+
+        read(smiles, &mut builder, None).unwrap();
+        read(smiles, &mut graph_builder, None).unwrap();
+
+        assert_eq!(
+            builder.build().map(Vec::from),
+            graph_builder.build(),
+            "{smiles}"
+        )
+    }
+
+    #[test]
+    fn matches_graph_builder() {
+        for smiles in [
+            "C",
+            "C.O",
+            "CC(=O)N",
+            "C1CC1",
+            "C=1CC1",
+            "C1CC=1",
+            "C/1CC\\1",
+            "c1ccccc1-c1ccccc1",
+            "C12CC1C2",
+            "C1CC2CC1CC2",
+            "F[C@H](Cl)Br",
+            "N[C@@H]1CC[C@H](O)CC1",
+            "C%10CC%10",
+        ] {
+            compare(smiles)
+        }
+    }
+
+    #[test]
+    fn join_error() {
+        let mut builder = Builder::default();
+
+        read("C=1CC-1", &mut builder, None).unwrap();
+
+        assert_eq!(builder.build(), Err(Error::Join(2, 0)))
+    }
+
+    #[test]
+    fn rnum_error() {
+        let mut graph_builder = graph::Builder::default();
+        let mut builder = Builder::default();
+
+        read("C1CC2CC3C", &mut builder, None).unwrap();
+        read("C1CC2CC3C", &mut graph_builder, None).unwrap();
+
+        assert_eq!(builder.build(), Err(Error::Rnum(0)));
+        assert_eq!(graph_builder.build(), Err(Error::Rnum(0)))
+    }
+
+    #[test]
+    fn take_reuses() {
+        let mut builder = Builder::default();
+
+        read("C1CC", &mut builder, None).unwrap();
+
+        assert!(builder.take().is_err());
+
+        read("C1CC1", &mut builder, None).unwrap();
+
+        assert_eq!(builder.take().map(|molecule| molecule.len()), Ok(3));
+        assert_eq!(builder.take(), Ok(Molecule::default()))
+    }
+}
@@ -0,0 +1,5 @@
+mod builder;
+mod molecule;
+
+pub use builder::Builder;
+pub use molecule::Molecule;
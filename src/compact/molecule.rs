@@ -0,0 +1,188 @@
+use std::ops::Range;
+
+use crate::feature::AtomKind;
+use crate::graph::{Atom, Bond};
+use crate::walk::Graph;
+
+/// An adjacency representation held in three flat arrays, using
+/// compressed sparse rows: atom kinds, bonds of every atom in turn, and
+/// the offset at which each atom's bonds start.
+///
+/// ```
+/// use yowl::compact::{Builder, Molecule};
+/// use yowl::read::read;
+/// use yowl::walk::walk;
+/// use yowl::write::Writer;
+///
+/// let mut builder = Builder::default();
+///
+/// read("C1CC1O", &mut builder, None).expect("read");
+///
+/// let molecule = builder.build().expect("molecule");
+/// let mut writer = Writer::default();
+///
+/// assert_eq!(molecule.len(), 4);
+/// assert_eq!(molecule.bonds(0).len(), 2);
+///
+/// walk(&molecule, &mut writer).expect("walk");
+///
+/// assert_eq!(writer.write(), "C(C(C1)O)1");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Molecule {
+    kinds: Vec<AtomKind>,
+    offsets: Vec<usize>,
+    bonds: Vec<Bond>,
+}
+
+impl Molecule {
+    pub(crate) fn new(kinds: Vec<AtomKind>, offsets: Vec<usize>, bonds: Vec<Bond>) -> Self {
+        debug_assert_eq!(offsets.len(), kinds.len() + 1);
+
+        Self {
+            kinds,
+            offsets,
+            bonds,
+        }
+    }
+
+    /// Returns the number of atoms.
+    pub fn len(&self) -> usize {
+        self.kinds.len()
+    }
+
+    /// Returns true if there are no atoms.
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty()
+    }
+
+    /// Returns the kinds of all atoms, in order.
+    pub fn kinds(&self) -> &[AtomKind] {
+        &self.kinds
+    }
+
+    /// Returns the bonds of atom `id`, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of range.
+    pub fn bonds(&self, id: usize) -> &[Bond] {
+        &self.bonds[self.range(id)]
+    }
+
+    /// Returns the bond entries of every atom, in turn. Each bond appears
+    /// twice, once for each of its atoms.
+    pub fn all_bonds(&self) -> &[Bond] {
+        &self.bonds
+    }
+
+    fn range(&self, id: usize) -> Range<usize> {
+        self.offsets[id]..self.offsets[id + 1]
+    }
+}
+
+impl Default for Molecule {
+    fn default() -> Self {
+        Self::new(Vec::new(), vec![0], Vec::new())
+    }
+}
+
+impl Graph for Molecule {
+    fn atom_count(&self) -> usize {
+        self.len()
+    }
+
+    fn kind(&self, id: usize) -> AtomKind {
+        self.kinds[id]
+    }
+
+    fn bonds(&self, id: usize) -> &[Bond] {
+        self.bonds(id)
+    }
+}
+
+impl From<&[Atom]> for Molecule {
+    fn from(atoms: &[Atom]) -> Self {
+        let mut offsets = Vec::with_capacity(atoms.len() + 1);
+        let mut bonds = Vec::with_capacity(atoms.iter().map(|atom| atom.bonds.len()).sum());
+
+        offsets.push(0);
+
+        for atom in atoms {
+            bonds.extend_from_slice(&atom.bonds);
+            offsets.push(bonds.len());
+        }
+
+        Self::new(atoms.iter().map(|atom| atom.kind).collect(), offsets, bonds)
+    }
+}
+
+impl From<Vec<Atom>> for Molecule {
+    fn from(atoms: Vec<Atom>) -> Self {
+        Self::from(atoms.as_slice())
+    }
+}
+
+impl From<&Molecule> for Vec<Atom> {
+    fn from(molecule: &Molecule) -> Self {
+        (0..molecule.len())
+            .map(|id| Atom {
+                kind: molecule.kinds[id],
+                bonds: molecule.bonds(id).to_vec(),
+            })
+            .collect()
+    }
+}
+
+impl From<Molecule> for Vec<Atom> {
+    fn from(molecule: Molecule) -> Self {
+        Self::from(&molecule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::{BondKind, Symbol};
+    use pretty_assertions::assert_eq;
+
+    fn atoms() -> Vec<Atom> {
+        vec![
+            Atom {
+                kind: AtomKind::Symbol(Symbol::Star),
+                bonds: vec![Bond::new(BondKind::Double, 1)],
+            },
+            Atom {
+                kind: AtomKind::Symbol(Symbol::Star),
+                bonds: vec![
+                    Bond::new(BondKind::Double, 0),
+                    Bond::new(BondKind::Single, 2),
+                ],
+            },
+            Atom::new(AtomKind::Symbol(Symbol::Star)),
+        ]
+    }
+
+    #[test]
+    fn empty() {
+        let molecule = Molecule::default();
+
+        assert!(molecule.is_empty());
+        assert_eq!(Vec::from(molecule), vec![])
+    }
+
+    #[test]
+    fn from_atoms() {
+        let molecule = Molecule::from(atoms());
+
+        assert_eq!(molecule.len(), 3);
+        assert_eq!(molecule.all_bonds().len(), 3);
+        assert_eq!(molecule.bonds(1), &atoms()[1].bonds[..]);
+        assert_eq!(molecule.bonds(2), &[])
+    }
+
+    #[test]
+    fn roundtrip() {
+        assert_eq!(Vec::from(Molecule::from(atoms())), atoms())
+    }
+}
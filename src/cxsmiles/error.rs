@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+use crate::read::ReadError;
+
+/// An error that occurs when writing a CXSMILES extension block.
+#[derive(Debug, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WriteError {
+    #[error("Invalid SMILES: {0}")]
+    Smiles(#[from] ReadError),
+    #[error("Unknown atom: {0}")]
+    UnknownAtom(usize),
+    #[error("Unknown bond: ({0}, {1})")]
+    UnknownBond(usize, usize),
+    #[error("Coordinate count doesn't match atom count: {0}")]
+    Coordinates(usize),
+}
@@ -0,0 +1,219 @@
+/// The radical kinds of the `^n` CXSMILES field.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Radical {
+    Monovalent,
+    Divalent,
+    DivalentSinglet,
+    DivalentTriplet,
+    Trivalent,
+    TrivalentDoublet,
+    TrivalentQuartet,
+}
+
+impl Radical {
+    /// Returns the Radical for a `^n` field number.
+    pub const fn from_number(number: u8) -> Option<Self> {
+        Some(match number {
+            1 => Self::Monovalent,
+            2 => Self::Divalent,
+            3 => Self::DivalentSinglet,
+            4 => Self::DivalentTriplet,
+            5 => Self::Trivalent,
+            6 => Self::TrivalentDoublet,
+            7 => Self::TrivalentQuartet,
+            _ => return None,
+        })
+    }
+
+    /// Returns the `^n` field number.
+    pub const fn number(&self) -> u8 {
+        match self {
+            Self::Monovalent => 1,
+            Self::Divalent => 2,
+            Self::DivalentSinglet => 3,
+            Self::DivalentTriplet => 4,
+            Self::Trivalent => 5,
+            Self::TrivalentDoublet => 6,
+            Self::TrivalentQuartet => 7,
+        }
+    }
+}
+
+/// An enhanced stereo group: `a`, `o<n>` or `&<n>`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StereoGroup {
+    Absolute,
+    Or(u16),
+    And(u16),
+}
+
+/// A wedge (`wU`) or hash (`wD`) drawn at `atom`. The wedged bond, given
+/// as a pair of atom indices, is absent when the field omits it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Wedge {
+    pub atom: usize,
+    pub bond: Option<(usize, usize)>,
+}
+
+/// A link node (`LN`): `atom` repeats between `min` and `max` times,
+/// optionally between the `outer` pair of atoms.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LinkNode {
+    pub atom: usize,
+    pub min: u16,
+    pub max: u16,
+    pub outer: Option<(usize, usize)>,
+}
+
+/// A polymer SGroup (`Sg`). `fields` holds any fields following the
+/// connectivity, verbatim.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct SGroup {
+    pub kind: String,
+    pub atoms: Vec<usize>,
+    pub subscript: String,
+    pub connectivity: String,
+    pub fields: Vec<String>,
+}
+
+/// The contents of a CXSMILES extension block. Atoms are indexed in
+/// order of appearance in the SMILES string, as with `Builder`. Bonds are
+/// given as pairs of atom indices.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Extension {
+    /// Coordinates of every atom, or none.
+    pub coordinates: Vec<[f64; 3]>,
+    pub labels: Vec<(usize, String)>,
+    pub values: Vec<(usize, String)>,
+    pub cis: Vec<(usize, usize)>,
+    pub trans: Vec<(usize, usize)>,
+    pub unknown: Vec<(usize, usize)>,
+    pub wedges_up: Vec<Wedge>,
+    pub wedges_down: Vec<Wedge>,
+    pub radicals: Vec<(Radical, Vec<usize>)>,
+    pub stereo_groups: Vec<(StereoGroup, Vec<usize>)>,
+    pub link_nodes: Vec<LinkNode>,
+    pub sgroups: Vec<SGroup>,
+}
+
+impl Extension {
+    /// Returns true if the Extension holds no fields.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Returns a copy with atoms renumbered, such that the atom at output
+    /// position `i` is the atom at original index `order[i]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an atom is missing from `order`.
+    pub fn reorder(&self, order: &[usize]) -> Self {
+        let mut index = vec![usize::MAX; order.len()];
+
+        for (new, &old) in order.iter().enumerate() {
+            index[old] = new;
+        }
+
+        let atom = |old: &usize| {
+            let new = index[*old];
+
+            assert!(new != usize::MAX, "atom {old} missing from order");
+
+            new
+        };
+        let pair = |(sid, tid): &(usize, usize)| (atom(sid), atom(tid));
+        let entries = |entries: &[(usize, String)]| {
+            entries
+                .iter()
+                .map(|(idx, value)| (atom(idx), value.clone()))
+                .collect::<Vec<_>>()
+        };
+        let wedges = |wedges: &[Wedge]| {
+            wedges
+                .iter()
+                .map(|wedge| Wedge {
+                    atom: atom(&wedge.atom),
+                    bond: wedge.bond.as_ref().map(pair),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        Self {
+            coordinates: if self.coordinates.is_empty() {
+                Vec::new()
+            } else {
+                order.iter().map(|&old| self.coordinates[old]).collect()
+            },
+            labels: entries(&self.labels),
+            values: entries(&self.values),
+            cis: self.cis.iter().map(pair).collect(),
+            trans: self.trans.iter().map(pair).collect(),
+            unknown: self.unknown.iter().map(pair).collect(),
+            wedges_up: wedges(&self.wedges_up),
+            wedges_down: wedges(&self.wedges_down),
+            radicals: self
+                .radicals
+                .iter()
+                .map(|(radical, atoms)| (*radical, atoms.iter().map(atom).collect()))
+                .collect(),
+            stereo_groups: self
+                .stereo_groups
+                .iter()
+                .map(|(group, atoms)| (*group, atoms.iter().map(atom).collect()))
+                .collect(),
+            link_nodes: self
+                .link_nodes
+                .iter()
+                .map(|node| LinkNode {
+                    atom: atom(&node.atom),
+                    outer: node.outer.as_ref().map(pair),
+                    ..*node
+                })
+                .collect(),
+            sgroups: self
+                .sgroups
+                .iter()
+                .map(|sgroup| SGroup {
+                    atoms: sgroup.atoms.iter().map(atom).collect(),
+                    ..sgroup.clone()
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn radical_numbers() {
+        for number in 1..=7 {
+            assert_eq!(Radical::from_number(number).unwrap().number(), number)
+        }
+
+        assert_eq!(Radical::from_number(8), None)
+    }
+
+    #[test]
+    fn reorder() {
+        let extension = Extension {
+            labels: vec![(0, "R".to_string())],
+            cis: vec![(1, 2)],
+            stereo_groups: vec![(StereoGroup::And(1), vec![2])],
+            ..Extension::default()
+        };
+
+        assert_eq!(
+            extension.reorder(&[2, 0, 1]),
+            Extension {
+                labels: vec![(1, "R".to_string())],
+                cis: vec![(2, 0)],
+                stereo_groups: vec![(StereoGroup::And(1), vec![0])],
+                ..Extension::default()
+            }
+        )
+    }
+}
@@ -0,0 +1,11 @@
+mod error;
+mod extension;
+mod reader;
+mod tracker;
+mod writer;
+
+pub use error::WriteError;
+pub use extension::{Extension, LinkNode, Radical, SGroup, StereoGroup, Wedge};
+pub use reader::read;
+pub(crate) use tracker::{Discard, Tracker};
+pub use writer::write;
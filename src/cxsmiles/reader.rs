@@ -0,0 +1,648 @@
+use super::{Extension, LinkNode, Radical, SGroup, StereoGroup, Tracker, Wedge};
+use crate::read::{ReadError, Trace};
+use crate::walk::Follower;
+
+/// Reads a SMILES string optionally followed by whitespace and a CXSMILES
+/// extension block, using a `Follower` and optional `Trace`. Error
+/// positions refer to the whole of `input`.
+///
+/// ```
+/// use yowl::cxsmiles::{read, Wedge};
+/// use yowl::graph::Builder;
+/// use yowl::read::ReadError;
+///
+/// fn main() -> Result<(), ReadError> {
+///     let mut builder = Builder::default();
+///     let extension = read("CCO |$;;label$,c:1,wU:2|", &mut builder, None)?;
+///
+///     assert_eq!(extension.labels, vec![(2, "label".to_string())]);
+///     assert_eq!(extension.cis, vec![(1, 2)]);
+///     assert_eq!(extension.wedges_up, vec![Wedge { atom: 2, bond: None }]);
+///     assert_eq!(builder.build().expect("atoms").len(), 3);
+///
+///     Ok(())
+/// }
+/// ```
+pub fn read<F: Follower>(
+    input: &str,
+    follower: &mut F,
+    trace: Option<&mut Trace>,
+) -> Result<Extension, ReadError> {
+    let end = input.find([' ', '\t']).unwrap_or(input.len());
+    let mut tracker = Tracker::new(follower);
+
+    crate::read::read(&input[..end], &mut tracker, trace)?;
+
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        cursor: end,
+        atom_count: tracker.atom_count(),
+        bonds: tracker.bonds(),
+    };
+
+    parser.skip_whitespace();
+
+    if parser.is_done() {
+        return Ok(Extension::default());
+    }
+
+    let extension = parser.block()?;
+
+    parser.skip_whitespace();
+
+    if parser.is_done() {
+        Ok(extension)
+    } else {
+        Err(ReadError::Character(parser.cursor))
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    cursor: usize,
+    atom_count: usize,
+    bonds: &'a [(usize, usize)],
+}
+
+impl Parser<'_> {
+    fn block(&mut self) -> Result<Extension, ReadError> {
+        let mut extension = Extension::default();
+
+        self.expect(b'|')?;
+
+        if self.take(b'|') {
+            return Ok(extension);
+        }
+
+        loop {
+            self.section(&mut extension)?;
+
+            if self.take(b'|') {
+                break Ok(extension);
+            }
+
+            self.expect(b',')?;
+        }
+    }
+
+    fn section(&mut self, extension: &mut Extension) -> Result<(), ReadError> {
+        match self.peek() {
+            Some(b'(') => extension.coordinates = self.coordinates()?,
+            Some(b'$') => {
+                if self.take_str("$_AV:") {
+                    extension.values = self.entries()?;
+                } else {
+                    self.cursor += 1;
+                    extension.labels = self.entries()?;
+                }
+            }
+            Some(b'^') => {
+                self.cursor += 1;
+
+                let cursor = self.cursor;
+                let radical = u8::try_from(self.number()?)
+                    .ok()
+                    .and_then(Radical::from_number)
+                    .ok_or(ReadError::Character(cursor))?;
+
+                self.expect(b':')?;
+                extension.radicals.push((radical, self.atoms()?));
+            }
+            Some(b'o' | b'&') => {
+                let or = self.peek() == Some(b'o');
+
+                self.cursor += 1;
+
+                let cursor = self.cursor;
+                let number =
+                    u16::try_from(self.number()?).map_err(|_| ReadError::Character(cursor))?;
+                let group = if or {
+                    StereoGroup::Or(number)
+                } else {
+                    StereoGroup::And(number)
+                };
+
+                self.expect(b':')?;
+                extension.stereo_groups.push((group, self.atoms()?));
+            }
+            _ => {
+                if self.take_str("ctu:") {
+                    extension.unknown.extend(self.bonds()?);
+                } else if self.take_str("c:") {
+                    extension.cis.extend(self.bonds()?);
+                } else if self.take_str("t:") {
+                    extension.trans.extend(self.bonds()?);
+                } else if self.take_str("wU:") {
+                    extension.wedges_up.extend(self.wedges()?);
+                } else if self.take_str("wD:") {
+                    extension.wedges_down.extend(self.wedges()?);
+                } else if self.take_str("a:") {
+                    extension
+                        .stereo_groups
+                        .push((StereoGroup::Absolute, self.atoms()?));
+                } else if self.take_str("LN:") {
+                    extension.link_nodes.extend(self.link_nodes()?);
+                } else if self.take_str("Sg:") {
+                    extension.sgroups.push(self.sgroup()?);
+                } else {
+                    return Err(self.unexpected());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // "(" x "," y "," z ( ";" x "," y "," z )* ")"
+    fn coordinates(&mut self) -> Result<Vec<[f64; 3]>, ReadError> {
+        let mut result = Vec::new();
+
+        self.expect(b'(')?;
+
+        loop {
+            let mut point = [0.; 3];
+
+            for (idx, value) in point.iter_mut().enumerate() {
+                if idx > 0 {
+                    self.expect(b',')?;
+                }
+
+                let start = self.cursor;
+                let text = self.until(|byte| matches!(byte, b',' | b';' | b')' | b'|'));
+
+                if !text.is_empty() {
+                    *value = text.parse().map_err(|_| ReadError::Character(start))?;
+                }
+            }
+
+            result.push(point);
+
+            if self.take(b')') {
+                break;
+            }
+
+            self.expect(b';')?;
+        }
+
+        if result.len() == self.atom_count {
+            Ok(result)
+        } else {
+            Err(ReadError::Character(self.cursor - 1))
+        }
+    }
+
+    // entry ( ";" entry )* "$"
+    fn entries(&mut self) -> Result<Vec<(usize, String)>, ReadError> {
+        let mut result = Vec::new();
+        let atom_count = self.atom_count;
+        let mut idx = 0;
+
+        loop {
+            let start = self.cursor;
+            let text = self.until(|byte| matches!(byte, b';' | b'$'));
+
+            if idx >= atom_count {
+                return Err(ReadError::Character(start));
+            }
+
+            if !text.is_empty() {
+                result.push((idx, text.to_string()));
+            }
+
+            idx += 1;
+
+            if self.take(b'$') {
+                break Ok(result);
+            }
+
+            self.expect(b';')?;
+        }
+    }
+
+    fn wedges(&mut self) -> Result<Vec<Wedge>, ReadError> {
+        self.list(|parser| {
+            let atom = parser.atom()?;
+            let bond = if parser.take(b'.') {
+                Some(parser.bond()?)
+            } else {
+                None
+            };
+
+            Ok(Wedge { atom, bond })
+        })
+    }
+
+    // atom ":" min "." max ( "." outer "." outer )?
+    fn link_nodes(&mut self) -> Result<Vec<LinkNode>, ReadError> {
+        self.list(|parser| {
+            let atom = parser.atom()?;
+
+            parser.expect(b':')?;
+
+            let min = parser.small()?;
+
+            parser.expect(b'.')?;
+
+            let max = parser.small()?;
+            let outer = if parser.take(b'.') {
+                let left = parser.atom()?;
+
+                parser.expect(b'.')?;
+
+                Some((left, parser.atom()?))
+            } else {
+                None
+            };
+
+            Ok(LinkNode {
+                atom,
+                min,
+                max,
+                outer,
+            })
+        })
+    }
+
+    // kind ":" atoms ( ":" field )*
+    fn sgroup(&mut self) -> Result<SGroup, ReadError> {
+        let kind = self.field().to_string();
+
+        self.expect(b':')?;
+
+        let atoms = self.atoms()?;
+        let mut fields = Vec::new();
+
+        while self.take(b':') {
+            fields.push(self.field().to_string());
+        }
+
+        let mut fields = fields.into_iter();
+
+        Ok(SGroup {
+            kind,
+            atoms,
+            subscript: fields.next().unwrap_or_default(),
+            connectivity: fields.next().unwrap_or_default(),
+            fields: fields.collect(),
+        })
+    }
+
+    /// Reads up to a colon, pipe, or a comma that doesn't continue a
+    /// list.
+    fn field(&mut self) -> &str {
+        let start = self.cursor;
+
+        while let Some(byte) = self.peek() {
+            if byte == b':' || byte == b'|' || (byte == b',' && !self.continues()) {
+                break;
+            }
+
+            self.cursor += 1;
+        }
+
+        std::str::from_utf8(&self.input[start..self.cursor]).expect("utf8")
+    }
+
+    fn atoms(&mut self) -> Result<Vec<usize>, ReadError> {
+        self.list(Self::atom)
+    }
+
+    fn bonds(&mut self) -> Result<Vec<(usize, usize)>, ReadError> {
+        self.list(Self::bond)
+    }
+
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ReadError>,
+    ) -> Result<Vec<T>, ReadError> {
+        let mut result = vec![item(self)?];
+
+        while self.continues() {
+            self.cursor += 1;
+            result.push(item(self)?);
+        }
+
+        Ok(result)
+    }
+
+    /// Returns true if at a comma followed by a digit.
+    fn continues(&self) -> bool {
+        self.peek() == Some(b',')
+            && self
+                .input
+                .get(self.cursor + 1)
+                .is_some_and(u8::is_ascii_digit)
+    }
+
+    fn atom(&mut self) -> Result<usize, ReadError> {
+        let cursor = self.cursor;
+        let atom = self.number()?;
+
+        if atom < self.atom_count {
+            Ok(atom)
+        } else {
+            Err(ReadError::Character(cursor))
+        }
+    }
+
+    fn bond(&mut self) -> Result<(usize, usize), ReadError> {
+        let cursor = self.cursor;
+
+        self.bonds
+            .get(self.number()?)
+            .copied()
+            .ok_or(ReadError::Character(cursor))
+    }
+
+    fn small(&mut self) -> Result<u16, ReadError> {
+        let cursor = self.cursor;
+
+        u16::try_from(self.number()?).map_err(|_| ReadError::Character(cursor))
+    }
+
+    fn number(&mut self) -> Result<usize, ReadError> {
+        let start = self.cursor;
+        let digits = self.until(|byte| !byte.is_ascii_digit());
+
+        if digits.is_empty() {
+            return Err(self.unexpected());
+        }
+
+        digits.parse().map_err(|_| ReadError::Character(start))
+    }
+
+    fn until(&mut self, stop: impl Fn(u8) -> bool) -> &str {
+        let start = self.cursor;
+
+        while let Some(byte) = self.peek() {
+            if stop(byte) {
+                break;
+            }
+
+            self.cursor += 1;
+        }
+
+        std::str::from_utf8(&self.input[start..self.cursor]).expect("utf8")
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.cursor += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ReadError> {
+        if self.take(byte) {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn take(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.cursor += 1;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_str(&mut self, prefix: &str) -> bool {
+        if self.input[self.cursor..].starts_with(prefix.as_bytes()) {
+            self.cursor += prefix.len();
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.cursor).copied()
+    }
+
+    const fn is_done(&self) -> bool {
+        self.cursor == self.input.len()
+    }
+
+    const fn unexpected(&self) -> ReadError {
+        if self.is_done() {
+            ReadError::EndOfLine
+        } else {
+            ReadError::Character(self.cursor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Builder;
+    use pretty_assertions::assert_eq;
+
+    fn extension(input: &str) -> Result<Extension, ReadError> {
+        read(input, &mut Builder::default(), None)
+    }
+
+    #[test]
+    fn no_block() {
+        assert_eq!(extension("CCO"), Ok(Extension::default()))
+    }
+
+    #[test]
+    fn empty_block() {
+        assert_eq!(extension("CCO ||"), Ok(Extension::default()))
+    }
+
+    #[test]
+    fn invalid_smiles() {
+        assert_eq!(extension("C? |c:0|"), Err(ReadError::Character(1)))
+    }
+
+    #[test]
+    fn missing_pipe() {
+        assert_eq!(extension("CC |c:0"), Err(ReadError::EndOfLine))
+    }
+
+    #[test]
+    fn trailing_junk() {
+        assert_eq!(extension("CC |c:0| x"), Err(ReadError::Character(9)))
+    }
+
+    #[test]
+    fn unknown_section() {
+        assert_eq!(extension("CC |q:0|"), Err(ReadError::Character(4)))
+    }
+
+    #[test]
+    fn atom_out_of_range() {
+        assert_eq!(extension("CC |a:0,2|"), Err(ReadError::Character(8)))
+    }
+
+    #[test]
+    fn bond_out_of_range() {
+        assert_eq!(extension("CC |c:1|"), Err(ReadError::Character(6)))
+    }
+
+    #[test]
+    fn too_many_labels() {
+        assert_eq!(extension("CC |$;;X$|"), Err(ReadError::Character(7)))
+    }
+
+    #[test]
+    fn labels_and_values() {
+        assert_eq!(
+            extension("CCO |$R1;;_R2$,$_AV:;1.5;$|"),
+            Ok(Extension {
+                labels: vec![(0, "R1".to_string()), (2, "_R2".to_string())],
+                values: vec![(1, "1.5".to_string())],
+                ..Extension::default()
+            })
+        )
+    }
+
+    #[test]
+    fn coordinates() {
+        assert_eq!(
+            extension("CO |(0,0,;1.5,-0.25,1)|"),
+            Ok(Extension {
+                coordinates: vec![[0., 0., 0.], [1.5, -0.25, 1.]],
+                ..Extension::default()
+            })
+        )
+    }
+
+    #[test]
+    fn coordinates_wrong_count() {
+        assert_eq!(extension("CO |(0,0,0)|"), Err(ReadError::Character(10)))
+    }
+
+    #[test]
+    fn ring_bonds_indexed_on_close() {
+        assert_eq!(
+            extension("C1CC=C1C |c:3,t:4|"),
+            Ok(Extension {
+                cis: vec![(0, 3)],
+                trans: vec![(3, 4)],
+                ..Extension::default()
+            })
+        )
+    }
+
+    #[test]
+    fn wedges() {
+        assert_eq!(
+            extension("CC(O)N |wU:1.0,wD:1.2,2.1|"),
+            Ok(Extension {
+                wedges_up: vec![Wedge {
+                    atom: 1,
+                    bond: Some((0, 1))
+                }],
+                wedges_down: vec![
+                    Wedge {
+                        atom: 1,
+                        bond: Some((1, 3))
+                    },
+                    Wedge {
+                        atom: 2,
+                        bond: Some((1, 2))
+                    }
+                ],
+                ..Extension::default()
+            })
+        )
+    }
+
+    #[test]
+    fn radicals_and_unknown() {
+        assert_eq!(
+            extension("[CH2]C=C[CH] |^1:0,^2:3,ctu:1|"),
+            Ok(Extension {
+                radicals: vec![(Radical::Monovalent, vec![0]), (Radical::Divalent, vec![3])],
+                unknown: vec![(1, 2)],
+                ..Extension::default()
+            })
+        )
+    }
+
+    #[test]
+    fn bad_radical() {
+        assert_eq!(extension("[CH3] |^8:0|"), Err(ReadError::Character(8)))
+    }
+
+    #[test]
+    fn stereo_groups() {
+        assert_eq!(
+            extension("C[C@H](O)[C@@H](N)[C@H](F)Cl |a:1,o1:3,&2:5|"),
+            Ok(Extension {
+                stereo_groups: vec![
+                    (StereoGroup::Absolute, vec![1]),
+                    (StereoGroup::Or(1), vec![3]),
+                    (StereoGroup::And(2), vec![5])
+                ],
+                ..Extension::default()
+            })
+        )
+    }
+
+    #[test]
+    fn link_nodes() {
+        assert_eq!(
+            extension("OC1CCC(F)CC1 |LN:1:1.3.2.6,4:2.5|"),
+            Ok(Extension {
+                link_nodes: vec![
+                    LinkNode {
+                        atom: 1,
+                        min: 1,
+                        max: 3,
+                        outer: Some((2, 6))
+                    },
+                    LinkNode {
+                        atom: 4,
+                        min: 2,
+                        max: 5,
+                        outer: None
+                    }
+                ],
+                ..Extension::default()
+            })
+        )
+    }
+
+    #[test]
+    fn polymer_sgroup() {
+        assert_eq!(
+            extension("*CC(*)C |Sg:n:1,2,4:n:ht,$;;;;X$|"),
+            Ok(Extension {
+                labels: vec![(4, "X".to_string())],
+                sgroups: vec![SGroup {
+                    kind: "n".to_string(),
+                    atoms: vec![1, 2, 4],
+                    subscript: "n".to_string(),
+                    connectivity: "ht".to_string(),
+                    fields: Vec::new()
+                }],
+                ..Extension::default()
+            })
+        )
+    }
+
+    #[test]
+    fn sgroup_extra_fields() {
+        assert_eq!(
+            extension("*CC* |Sg:n:1,2::eu:0,2:1|"),
+            Ok(Extension {
+                sgroups: vec![SGroup {
+                    kind: "n".to_string(),
+                    atoms: vec![1, 2],
+                    subscript: String::new(),
+                    connectivity: "eu".to_string(),
+                    fields: vec!["0,2".to_string(), "1".to_string()]
+                }],
+                ..Extension::default()
+            })
+        )
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::feature::{AtomKind, BondKind, Rnum};
+use crate::walk::Follower;
+
+/// A `Follower` that forwards every call while recording the atom count
+/// and the order in which bonds are created. A ring bond is created when
+/// its closing digit is found.
+#[derive(Debug)]
+pub struct Tracker<F> {
+    follower: F,
+    stack: Vec<usize>,
+    opens: HashMap<Rnum, usize>,
+    atom_count: usize,
+    bonds: Vec<(usize, usize)>,
+}
+
+impl<F: Follower> Tracker<F> {
+    pub fn new(follower: F) -> Self {
+        Self {
+            follower,
+            stack: Vec::new(),
+            opens: HashMap::new(),
+            atom_count: 0,
+            bonds: Vec::new(),
+        }
+    }
+
+    pub const fn atom_count(&self) -> usize {
+        self.atom_count
+    }
+
+    /// Returns the bonds in order of creation.
+    pub fn bonds(&self) -> &[(usize, usize)] {
+        &self.bonds
+    }
+}
+
+impl<F: Follower> Follower for Tracker<F> {
+    fn root(&mut self, root: AtomKind) {
+        self.stack.push(self.atom_count);
+        self.atom_count += 1;
+        self.follower.root(root);
+    }
+
+    fn extend(&mut self, bond_kind: BondKind, atom_kind: AtomKind) {
+        let sid = *self.stack.last().expect("last on stack");
+
+        self.bonds.push((sid, self.atom_count));
+        self.stack.push(self.atom_count);
+        self.atom_count += 1;
+        self.follower.extend(bond_kind, atom_kind);
+    }
+
+    fn join(&mut self, bond_kind: BondKind, rnum: Rnum) {
+        let sid = *self.stack.last().expect("last on stack");
+
+        match self.opens.remove(&rnum) {
+            Some(tid) => self.bonds.push((tid, sid)),
+            None => {
+                self.opens.insert(rnum, sid);
+            }
+        }
+
+        self.follower.join(bond_kind, rnum);
+    }
+
+    fn pop(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.stack.pop();
+        }
+
+        self.follower.pop(depth);
+    }
+}
+
+/// A `Follower` that ignores everything.
+#[derive(Debug, Default)]
+pub struct Discard;
+
+impl Follower for Discard {
+    fn root(&mut self, _: AtomKind) {}
+
+    fn extend(&mut self, _: BondKind, _: AtomKind) {}
+
+    fn join(&mut self, _: BondKind, _: Rnum) {}
+
+    fn pop(&mut self, _: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::read;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn ring_bond_created_on_close() {
+        let mut tracker = Tracker::new(Discard);
+
+        read("C1CC(O)C1", &mut tracker, None).unwrap();
+
+        assert_eq!(tracker.atom_count(), 5);
+        assert_eq!(tracker.bonds(), &[(0, 1), (1, 2), (2, 3), (2, 4), (0, 4)])
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::{Discard, Extension, StereoGroup, Tracker, Wedge, WriteError};
+
+/// Appends `extension` to `smiles` as a CXSMILES extension block, separated
+/// by a space. Bonds are renumbered by reading `smiles`, so any atom order
+/// must match it. An empty extension leaves `smiles` unchanged.
+///
+/// ```
+/// use yowl::cxsmiles::{read, write};
+/// use yowl::graph::Builder;
+/// use yowl::walk::walk;
+/// use yowl::write::Writer;
+///
+/// let mut builder = Builder::default();
+/// let extension = read("CC=CC |c:1,$R;;;$|", &mut builder, None).expect("read");
+/// let mut writer = Writer::default();
+///
+/// walk(builder.build().expect("atoms"), &mut writer).expect("walk");
+///
+/// let smiles = writer.write();
+///
+/// assert_eq!(write(&smiles, &extension), Ok("CC=CC |$R;;;$,c:1|".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if `smiles` can't be read, or if `extension` refers to
+/// an atom or bond it lacks.
+pub fn write(smiles: &str, extension: &Extension) -> Result<String, WriteError> {
+    if extension.is_empty() {
+        return Ok(smiles.to_string());
+    }
+
+    let mut tracker = Tracker::new(Discard);
+
+    crate::read::read(smiles, &mut tracker, None)?;
+
+    let context = Context {
+        atom_count: tracker.atom_count(),
+        bonds: tracker
+            .bonds()
+            .iter()
+            .enumerate()
+            .map(|(idx, &(sid, tid))| ((sid.min(tid), sid.max(tid)), idx))
+            .collect(),
+    };
+    let mut sections = Vec::new();
+
+    if !extension.coordinates.is_empty() {
+        if extension.coordinates.len() != context.atom_count {
+            return Err(WriteError::Coordinates(extension.coordinates.len()));
+        }
+
+        let points = extension
+            .coordinates
+            .iter()
+            .map(|[x, y, z]| format!("{x},{y},{z}"))
+            .collect::<Vec<_>>();
+
+        sections.push(format!("({})", points.join(";")));
+    }
+
+    if !extension.labels.is_empty() {
+        sections.push(format!("${}$", context.entries(&extension.labels)?));
+    }
+
+    if !extension.values.is_empty() {
+        sections.push(format!("$_AV:{}$", context.entries(&extension.values)?));
+    }
+
+    for (name, bonds) in [
+        ("c", &extension.cis),
+        ("t", &extension.trans),
+        ("ctu", &extension.unknown),
+    ] {
+        if !bonds.is_empty() {
+            sections.push(format!("{name}:{}", context.bonds(bonds)?));
+        }
+    }
+
+    for (name, wedges) in [("wU", &extension.wedges_up), ("wD", &extension.wedges_down)] {
+        if !wedges.is_empty() {
+            sections.push(format!("{name}:{}", context.wedges(wedges)?));
+        }
+    }
+
+    for (radical, atoms) in &extension.radicals {
+        sections.push(format!("^{}:{}", radical.number(), context.atoms(atoms)?));
+    }
+
+    for (group, atoms) in &extension.stereo_groups {
+        let atoms = context.atoms(atoms)?;
+
+        sections.push(match group {
+            StereoGroup::Absolute => format!("a:{atoms}"),
+            StereoGroup::Or(number) => format!("o{number}:{atoms}"),
+            StereoGroup::And(number) => format!("&{number}:{atoms}"),
+        });
+    }
+
+    if !extension.link_nodes.is_empty() {
+        let mut nodes = Vec::new();
+
+        for node in &extension.link_nodes {
+            let mut entry = format!("{}:{}.{}", context.atom(node.atom)?, node.min, node.max);
+
+            if let Some((left, right)) = node.outer {
+                write!(entry, ".{}.{}", context.atom(left)?, context.atom(right)?).expect("write");
+            }
+
+            nodes.push(entry);
+        }
+
+        sections.push(format!("LN:{}", nodes.join(",")));
+    }
+
+    for sgroup in &extension.sgroups {
+        let mut entry = format!(
+            "Sg:{}:{}:{}:{}",
+            sgroup.kind,
+            context.atoms(&sgroup.atoms)?,
+            sgroup.subscript,
+            sgroup.connectivity
+        );
+
+        for field in &sgroup.fields {
+            write!(entry, ":{field}").expect("write");
+        }
+
+        sections.push(entry);
+    }
+
+    Ok(format!("{smiles} |{}|", sections.join(",")))
+}
+
+struct Context {
+    atom_count: usize,
+    bonds: HashMap<(usize, usize), usize>,
+}
+
+impl Context {
+    fn atom(&self, atom: usize) -> Result<usize, WriteError> {
+        if atom < self.atom_count {
+            Ok(atom)
+        } else {
+            Err(WriteError::UnknownAtom(atom))
+        }
+    }
+
+    fn bond(&self, (sid, tid): (usize, usize)) -> Result<usize, WriteError> {
+        self.bonds
+            .get(&(sid.min(tid), sid.max(tid)))
+            .copied()
+            .ok_or(WriteError::UnknownBond(sid, tid))
+    }
+
+    fn atoms(&self, atoms: &[usize]) -> Result<String, WriteError> {
+        join(atoms.iter().map(|&atom| self.atom(atom)))
+    }
+
+    fn bonds(&self, bonds: &[(usize, usize)]) -> Result<String, WriteError> {
+        join(bonds.iter().map(|&bond| self.bond(bond)))
+    }
+
+    fn wedges(&self, wedges: &[Wedge]) -> Result<String, WriteError> {
+        let mut entries = Vec::new();
+
+        for wedge in wedges {
+            let atom = self.atom(wedge.atom)?;
+
+            entries.push(match wedge.bond {
+                Some(bond) => format!("{atom}.{}", self.bond(bond)?),
+                None => atom.to_string(),
+            });
+        }
+
+        Ok(entries.join(","))
+    }
+
+    fn entries(&self, entries: &[(usize, String)]) -> Result<String, WriteError> {
+        let mut result = vec![""; self.atom_count];
+
+        for (atom, value) in entries {
+            result[self.atom(*atom)?] = value;
+        }
+
+        Ok(result.join(";"))
+    }
+}
+
+fn join(items: impl Iterator<Item = Result<usize, WriteError>>) -> Result<String, WriteError> {
+    Ok(items
+        .map(|item| item.map(|item| item.to_string()))
+        .collect::<Result<Vec<_>, _>>()?
+        .join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cxsmiles::{read, LinkNode, Radical, SGroup};
+    use crate::graph::Builder;
+    use crate::read::ReadError;
+    use pretty_assertions::assert_eq;
+
+    fn roundtrip(input: &str) -> String {
+        let end = input.find(' ').unwrap_or(input.len());
+        let extension = read(input, &mut Builder::default(), None).unwrap();
+
+        write(&input[..end], &extension).unwrap()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(write("CC", &Extension::default()), Ok("CC".to_string()))
+    }
+
+    #[test]
+    fn bad_smiles() {
+        let extension = Extension {
+            cis: vec![(0, 1)],
+            ..Extension::default()
+        };
+
+        assert_eq!(
+            write("C(", &extension),
+            Err(WriteError::Smiles(ReadError::EndOfLine))
+        )
+    }
+
+    #[test]
+    fn unknown_atom() {
+        let extension = Extension {
+            radicals: vec![(Radical::Monovalent, vec![2])],
+            ..Extension::default()
+        };
+
+        assert_eq!(write("CC", &extension), Err(WriteError::UnknownAtom(2)))
+    }
+
+    #[test]
+    fn unknown_bond() {
+        let extension = Extension {
+            cis: vec![(0, 2)],
+            ..Extension::default()
+        };
+
+        assert_eq!(write("CCC", &extension), Err(WriteError::UnknownBond(0, 2)))
+    }
+
+    #[test]
+    fn coordinates_wrong_count() {
+        let extension = Extension {
+            coordinates: vec![[0.; 3]],
+            ..Extension::default()
+        };
+
+        assert_eq!(write("CC", &extension), Err(WriteError::Coordinates(1)))
+    }
+
+    #[test]
+    fn bond_pair_order_ignored() {
+        let extension = Extension {
+            trans: vec![(3, 0)],
+            ..Extension::default()
+        };
+
+        assert_eq!(
+            write("C1CC=C1", &extension),
+            Ok("C1CC=C1 |t:3|".to_string())
+        )
+    }
+
+    #[test]
+    fn link_node_and_sgroup() {
+        let extension = Extension {
+            link_nodes: vec![LinkNode {
+                atom: 1,
+                min: 1,
+                max: 4,
+                outer: Some((0, 2)),
+            }],
+            sgroups: vec![SGroup {
+                kind: "n".to_string(),
+                atoms: vec![1],
+                subscript: "n".to_string(),
+                connectivity: "ht".to_string(),
+                fields: Vec::new(),
+            }],
+            ..Extension::default()
+        };
+
+        assert_eq!(
+            write("*C*", &extension),
+            Ok("*C* |LN:1:1.4.0.2,Sg:n:1:n:ht|".to_string())
+        )
+    }
+
+    #[test]
+    fn roundtrips() {
+        for input in [
+            "CCO |$;;label$,c:1,wU:2|",
+            "CO |(0,0,0;1.5,-0.25,1)|",
+            "[CH2]C=C[CH] |$_AV:a;;;b$,ctu:1,^1:0,^2:3|",
+            "C[C@H](O)[C@@H](N)F |wD:1.0,a:1,&1:3|",
+            "OC1CCC(F)CC1 |LN:1:1.3.2.6,4:2.5|",
+            "*CC(*)C |Sg:n:1,2,4:n:ht:0,2|",
+        ] {
+            assert_eq!(roundtrip(input), input)
+        }
+    }
+}
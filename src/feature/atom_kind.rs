@@ -2,9 +2,14 @@ use std::fmt;
 
 use crate::{Element, Isotope};
 
+use super::valence::{standard_valences, ValenceModel};
 use super::{Charge, Configuration, VirtualHydrogen};
-use crate::feature::element_ext::ElementExt;
 
+/// An element symbol, aromatic or aliphatic, or the star (`*`).
+///
+/// With the `serde` feature, serializes as the string used in SMILES:
+/// `"*"`, an element symbol such as `"Cl"`, or a lowercase element symbol
+/// such as `"c"` for aromatic atoms, of the elements SMILES writes that way.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Symbol {
     Star,
@@ -12,11 +17,47 @@ pub enum Symbol {
     Aromatic(Element),
 }
 
+impl Symbol {
+    /// Returns true if the symbol belongs to the organic subset, and so
+    /// can be written without brackets.
+    pub const fn is_organic(&self) -> bool {
+        match self {
+            Self::Star => true,
+            Self::Aliphatic(element) => matches!(
+                element,
+                Element::B
+                    | Element::C
+                    | Element::N
+                    | Element::O
+                    | Element::P
+                    | Element::S
+                    | Element::F
+                    | Element::Cl
+                    | Element::Br
+                    | Element::I
+            ),
+            Self::Aromatic(element) => matches!(
+                element,
+                Element::B | Element::C | Element::N | Element::O | Element::P | Element::S
+            ),
+        }
+    }
+}
+
 /// Minimal context-sensitive representation of an atom kind.
+///
+/// With the `serde` feature, serializes as an externally tagged enum, such
+/// as `{"Symbol": "C"}`, or `{"Bracket": {...}}` with one entry per field.
+/// An isotope is its mass number followed by its element symbol, such as
+/// `"13C"`, and must match the element of the symbol. An atom outside
+/// brackets must be in the organic subset.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawAtomKind"))]
 pub enum AtomKind {
     Symbol(Symbol),
     Bracket {
+        #[cfg_attr(feature = "serde", serde(with = "super::periodic::optional_isotope"))]
         isotope: Option<Isotope>,
         symbol: Symbol,
         configuration: Option<Configuration>,
@@ -41,14 +82,20 @@ impl AtomKind {
 
     /// Returns the valence targets for this atom kind.
     pub fn targets(&self) -> &[u8] {
+        self.targets_with(standard_valences())
+    }
+
+    /// Returns the valence targets for this atom kind given by `model`.
+    pub fn targets_with<'a, M: ValenceModel + ?Sized>(&self, model: &'a M) -> &'a [u8] {
         match self {
             Self::Symbol(Symbol::Star) => &[],
-            Self::Symbol(Symbol::Aliphatic(aliphatic)) => aliphatic.targets(),
-            Self::Symbol(Symbol::Aromatic(aromatic)) => aromatic.targets(),
+            Self::Symbol(Symbol::Aliphatic(element) | Symbol::Aromatic(element)) => {
+                model.targets(*element, 0)
+            }
             Self::Bracket { symbol, charge, .. } => match symbol {
                 Symbol::Star => &[],
                 Symbol::Aromatic(element) | Symbol::Aliphatic(element) => {
-                    elemental_targets(*element, *charge)
+                    model.targets(*element, charge.map_or(0, Charge::value))
                 }
             },
         }
@@ -89,55 +136,124 @@ impl AtomKind {
     }
 }
 
-pub const fn elemental_targets(element: Element, charge: Option<Charge>) -> &'static [u8] {
-    match element {
-        Element::B => match charge {
-            Some(Charge { value: -3 }) => &OXYGEN_TARGET,
-            Some(Charge { value: -2 }) => &NITROGEN_TARGET,
-            Some(Charge { value: -1 }) => &CARBON_TARGET,
-            None => &BORON_TARGET,
-            _ => &EMPTY_TARGET,
-        },
-        Element::C | Element::Si => match charge {
-            Some(Charge { value: -2 }) => &OXYGEN_TARGET,
-            Some(Charge { value: -1 }) => &NITROGEN_TARGET,
-            Some(Charge { value: 1 }) => &BORON_TARGET,
-            None => &CARBON_TARGET,
-            _ => &EMPTY_TARGET,
-        },
-        Element::N | Element::P | Element::As => match charge {
-            Some(Charge { value: 1 }) => &CARBON_TARGET,
-            Some(Charge { value: -1 }) => &SULFUR_TARGET,
-            None => &NITROGEN_TARGET,
-            _ => &EMPTY_TARGET,
-        },
-        Element::O => match charge {
-            Some(Charge { value: 1 }) => &NITROGEN_TARGET,
-            None => &OXYGEN_TARGET,
-            _ => &EMPTY_TARGET,
-        },
-        Element::S | Element::Se | Element::Te => match charge {
-            Some(Charge { value: 1 }) => &NITROGEN_TARGET,
-            None => &SULFUR_TARGET,
-            _ => &EMPTY_TARGET,
-        },
-        Element::F | Element::Cl | Element::Br | Element::I | Element::At | Element::Ts => {
-            match charge {
-                None => &HALOGEN_TARGET,
-                _ => &EMPTY_TARGET,
-            }
+#[cfg(feature = "serde")]
+impl serde::Serialize for Symbol {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Star => serializer.serialize_str("*"),
+            Self::Aliphatic(element) => serializer.serialize_str(element.symbol()),
+            Self::Aromatic(element) => serializer.serialize_str(&element.symbol().to_lowercase()),
         }
-        _ => &EMPTY_TARGET,
     }
 }
 
-const BORON_TARGET: [u8; 1] = [3];
-const HALOGEN_TARGET: [u8; 1] = [1];
-const CARBON_TARGET: [u8; 1] = [4];
-const NITROGEN_TARGET: [u8; 2] = [3, 5];
-const OXYGEN_TARGET: [u8; 1] = [2];
-const SULFUR_TARGET: [u8; 3] = [2, 4, 6];
-const EMPTY_TARGET: [u8; 0] = [];
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Symbol {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+
+        if text == "*" {
+            return Ok(Self::Star);
+        }
+
+        if let Some(element) = super::periodic::element_from_symbol(&text) {
+            return Ok(Self::Aliphatic(element));
+        }
+
+        let mut chars = text.chars();
+        let capitalized = chars
+            .next()
+            .filter(char::is_ascii_lowercase)
+            .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str());
+
+        capitalized
+            .and_then(|symbol| super::periodic::element_from_symbol(&symbol))
+            // the aromatic symbols the reader accepts
+            .filter(|element| {
+                matches!(
+                    element,
+                    Element::B
+                        | Element::C
+                        | Element::N
+                        | Element::O
+                        | Element::P
+                        | Element::S
+                        | Element::As
+                        | Element::Se
+                        | Element::Si
+                        | Element::Te
+                )
+            })
+            .map(Self::Aromatic)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown symbol: {text}")))
+    }
+}
+
+/// The unvalidated form of `AtomKind`, as deserialized.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+enum RawAtomKind {
+    Symbol(Symbol),
+    Bracket {
+        #[serde(with = "super::periodic::optional_isotope")]
+        isotope: Option<Isotope>,
+        symbol: Symbol,
+        configuration: Option<Configuration>,
+        hcount: Option<VirtualHydrogen>,
+        charge: Option<Charge>,
+        map: Option<u16>,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawAtomKind> for AtomKind {
+    type Error = String;
+
+    fn try_from(raw: RawAtomKind) -> Result<Self, Self::Error> {
+        match raw {
+            RawAtomKind::Symbol(symbol) => {
+                if symbol.is_organic() {
+                    Ok(Self::Symbol(symbol))
+                } else {
+                    Err(format!("{} requires brackets", Self::Symbol(symbol)))
+                }
+            }
+            RawAtomKind::Bracket {
+                isotope,
+                symbol,
+                configuration,
+                hcount,
+                charge,
+                map,
+            } => {
+                if let Some(isotope) = isotope {
+                    let matches = match symbol {
+                        Symbol::Star => false,
+                        Symbol::Aliphatic(element) | Symbol::Aromatic(element) => {
+                            element == isotope.element()
+                        }
+                    };
+
+                    if !matches {
+                        return Err(format!(
+                            "isotope {} doesn't match symbol",
+                            super::periodic::isotope_to_string(&isotope)
+                        ));
+                    }
+                }
+
+                Ok(Self::Bracket {
+                    isotope,
+                    symbol,
+                    configuration,
+                    hcount,
+                    charge,
+                    map,
+                })
+            }
+        }
+    }
+}
 
 impl fmt::Display for AtomKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -276,6 +392,15 @@ mod invert {
         assert!(AtomKind::Symbol(Symbol::Aromatic(Element::N)).is_aromatic());
     }
 
+    #[test]
+    fn is_organic_symbols() {
+        assert!(Symbol::Star.is_organic());
+        assert!(Symbol::Aliphatic(Element::Cl).is_organic());
+        assert!(Symbol::Aromatic(Element::S).is_organic());
+        assert!(!Symbol::Aliphatic(Element::Na).is_organic());
+        assert!(!Symbol::Aromatic(Element::Se).is_organic());
+    }
+
     #[test]
     fn display_simple_kinds() {
         assert_eq!(AtomKind::Symbol(Symbol::Star).to_string(), "*");
@@ -303,3 +428,97 @@ mod invert {
         );
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod serialization {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::{from_str, to_string};
+
+    #[test]
+    fn symbols() {
+        assert_eq!(to_string(&Symbol::Star).unwrap(), r#""*""#);
+        assert_eq!(
+            to_string(&Symbol::Aliphatic(Element::Cl)).unwrap(),
+            r#""Cl""#
+        );
+        assert_eq!(
+            to_string(&Symbol::Aromatic(Element::Se)).unwrap(),
+            r#""se""#
+        );
+        assert_eq!(
+            from_str::<Symbol>(r#""se""#).unwrap(),
+            Symbol::Aromatic(Element::Se)
+        );
+        assert_eq!(
+            from_str::<Symbol>(r#""Se""#).unwrap(),
+            Symbol::Aliphatic(Element::Se)
+        );
+        assert!(from_str::<Symbol>(r#""Xx""#).is_err());
+        assert!(from_str::<Symbol>(r#""fe""#).is_err());
+        assert!(from_str::<Symbol>(r#""""#).is_err())
+    }
+
+    #[test]
+    fn bracket() {
+        let kind = AtomKind::Bracket {
+            isotope: Isotope::list()
+                .iter()
+                .find(|isotope| isotope.element() == Element::C && isotope.mass_number() == 13)
+                .copied(),
+            symbol: Symbol::Aliphatic(Element::C),
+            configuration: Some(Configuration::TH1),
+            hcount: Some(VirtualHydrogen::H1),
+            charge: Charge::new(-1),
+            map: Some(7),
+        };
+        let json = to_string(&kind).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"Bracket":{"isotope":"13C","symbol":"C","configuration":"TH1","hcount":1,"charge":-1,"map":7}}"#
+        );
+        assert_eq!(from_str::<AtomKind>(&json).unwrap(), kind)
+    }
+
+    #[test]
+    fn organic() {
+        let kind = AtomKind::Symbol(Symbol::Aromatic(Element::N));
+
+        assert_eq!(to_string(&kind).unwrap(), r#"{"Symbol":"n"}"#);
+        assert_eq!(from_str::<AtomKind>(r#"{"Symbol":"n"}"#).unwrap(), kind)
+    }
+
+    #[test]
+    fn organic_subset_only() {
+        assert!(from_str::<AtomKind>(r#"{"Symbol":"Na"}"#).is_err());
+        assert!(from_str::<AtomKind>(r#"{"Symbol":"se"}"#).is_err());
+        assert!(from_str::<AtomKind>(r#"{"Symbol":"fe"}"#).is_err());
+        assert_eq!(
+            from_str::<AtomKind>(r#"{"Bracket":{"isotope":null,"symbol":"se","configuration":null,"hcount":null,"charge":null,"map":null}}"#)
+                .unwrap(),
+            AtomKind::Bracket {
+                isotope: None,
+                symbol: Symbol::Aromatic(Element::Se),
+                configuration: None,
+                hcount: None,
+                charge: None,
+                map: None
+            }
+        )
+    }
+
+    #[test]
+    fn invalid_bracket() {
+        let bracket = |isotope: &str, hcount: u8, charge: i8| {
+            format!(
+                r#"{{"Bracket":{{"isotope":{isotope},"symbol":"C","configuration":null,"hcount":{hcount},"charge":{charge},"map":null}}}}"#
+            )
+        };
+
+        assert!(from_str::<AtomKind>(&bracket("null", 4, 0)).is_ok());
+        assert!(from_str::<AtomKind>(&bracket(r#""15N""#, 4, 0)).is_err());
+        assert!(from_str::<AtomKind>(&bracket("null", 10, 0)).is_err());
+        assert!(from_str::<AtomKind>(&bracket("null", 4, 16)).is_err())
+    }
+}
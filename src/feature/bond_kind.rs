@@ -2,7 +2,15 @@ use std::fmt;
 
 /// A kind of bond. Elided bonds are not present in the corresponding
 /// string representation.
+///
+/// Like `Up` and `Down`, dative bonds are read from the atom holding the
+/// bond, so `N->[Fe]` gives the nitrogen a `DativeRight` bond and the iron
+/// a `DativeLeft` one.
+///
+/// With the `serde` feature, serializes as the variant name, such as
+/// `"Double"`.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BondKind {
     Elided,
     Single,
@@ -12,10 +20,16 @@ pub enum BondKind {
     Aromatic,
     Up,
     Down,
+    /// A dative bond `->` donated by this atom.
+    DativeRight,
+    /// A dative bond `<-` accepted by this atom.
+    DativeLeft,
+    /// A zero-order bond `~`, such as an ionic or hydrogen bond.
+    Zero,
 }
 
 impl BondKind {
-    /// Directional bonds (Up and Down) return the complementary item.
+    /// Directional and dative bonds return the complementary item.
     /// Everything else returns self.
     pub const fn reverse(&self) -> Self {
         match self {
@@ -27,6 +41,9 @@ impl BondKind {
             Self::Aromatic => Self::Aromatic,
             Self::Up => Self::Down,
             Self::Down => Self::Up,
+            Self::DativeRight => Self::DativeLeft,
+            Self::DativeLeft => Self::DativeRight,
+            Self::Zero => Self::Zero,
         }
     }
 }
@@ -42,6 +59,9 @@ impl fmt::Display for BondKind {
             Self::Up => write!(f, "/"),
             Self::Down => write!(f, "\\"),
             Self::Aromatic => write!(f, ":"),
+            Self::DativeRight => write!(f, "->"),
+            Self::DativeLeft => write!(f, "<-"),
+            Self::Zero => write!(f, "~"),
         }
     }
 }
@@ -1,6 +1,9 @@
 use core::fmt;
 
 /// A formal atomic charge between -15 and +15, inclusive.
+///
+/// With the `serde` feature, serializes as an integer. Deserialization
+/// rejects values out of range.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Charge {
     pub(crate) value: i8,
@@ -56,3 +59,20 @@ impl fmt::Display for Charge {
         }
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Charge {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i8(self.value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Charge {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = i8::deserialize(deserializer)?;
+
+        Self::new(value)
+            .ok_or_else(|| serde::de::Error::custom(format!("charge out of range: {value}")))
+    }
+}
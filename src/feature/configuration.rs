@@ -2,7 +2,11 @@ use std::fmt;
 
 /// Representation of a configurational template. Most applications
 /// will use only `TH1` (counterclockwise) and `TH2` (clockwise).
+///
+/// With the `serde` feature, serializes as the variant name, such as
+/// `"TH1"`.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Configuration {
     AL1,
     AL2,
@@ -1,6 +1,6 @@
 use crate::Element;
 
-use super::atom_kind;
+use super::valence::{standard_valences, ValenceModel};
 
 pub trait ElementExt {
     fn targets(&self) -> &[u8];
@@ -8,6 +8,6 @@ pub trait ElementExt {
 
 impl ElementExt for Element {
     fn targets(&self) -> &[u8] {
-        atom_kind::elemental_targets(*self, None)
+        standard_valences().targets(*self, 0)
     }
 }
@@ -3,7 +3,9 @@ mod bond_kind;
 mod charge;
 mod configuration;
 mod element_ext;
+mod periodic;
 mod rnum;
+mod valence;
 mod virtual_hydrogen;
 
 pub use atom_kind::AtomKind;
@@ -12,5 +14,10 @@ pub use bond_kind::BondKind;
 pub use charge::Charge;
 pub use configuration::Configuration;
 pub use element_ext::ElementExt;
+pub(crate) use periodic::element_from_symbol;
+#[cfg(feature = "serde")]
+pub use periodic::{element, isotope, optional_isotope};
 pub use rnum::Rnum;
+pub(crate) use valence::standard_valences;
+pub use valence::{ValenceModel, ValenceTable};
 pub use virtual_hydrogen::VirtualHydrogen;
@@ -0,0 +1,146 @@
+//! Lookup of `Element` by symbol, and serde representations of `Element`
+//! and `Isotope`, which are foreign types. An `Element` is its symbol, such
+//! as `"Cl"`. An `Isotope` is its mass number followed by its element
+//! symbol, such as `"13C"`.
+//!
+//! The `element`, `isotope` and `optional_isotope` modules are re-exported
+//! from `feature` for use as `#[serde(with = "yowl::feature::element")]`.
+
+#[cfg(feature = "serde")]
+use serde::de::{self, Deserialize, Deserializer};
+#[cfg(feature = "serde")]
+use serde::ser::Serializer;
+
+use crate::Element;
+#[cfg(feature = "serde")]
+use crate::Isotope;
+
+/// Returns the Element with `symbol`.
+pub fn element_from_symbol(symbol: &str) -> Option<Element> {
+    Element::list()
+        .iter()
+        .find(|element| element.symbol() == symbol)
+        .copied()
+}
+
+/// Returns the Isotope written as mass number and element symbol.
+#[cfg(feature = "serde")]
+pub fn isotope_from_str(text: &str) -> Option<Isotope> {
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let mass_number = text[..split].parse::<u32>().ok()?;
+    let element = element_from_symbol(&text[split..])?;
+
+    Isotope::list()
+        .iter()
+        .find(|isotope| isotope.element() == element && isotope.mass_number() == mass_number)
+        .copied()
+}
+
+#[cfg(feature = "serde")]
+pub fn isotope_to_string(isotope: &Isotope) -> String {
+    format!("{}{}", isotope.mass_number(), isotope.element().symbol())
+}
+
+#[cfg(feature = "serde")]
+pub mod element {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(element: &Element, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(element.symbol())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Element, D::Error> {
+        let symbol = String::deserialize(deserializer)?;
+
+        element_from_symbol(&symbol)
+            .ok_or_else(|| de::Error::custom(format!("unknown element: {symbol}")))
+    }
+}
+
+#[cfg(feature = "serde")]
+pub mod isotope {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(isotope: &Isotope, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&isotope_to_string(isotope))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Isotope, D::Error> {
+        let text = String::deserialize(deserializer)?;
+
+        isotope_from_str(&text).ok_or_else(|| de::Error::custom(format!("unknown isotope: {text}")))
+    }
+}
+
+#[cfg(feature = "serde")]
+pub mod optional_isotope {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        isotope: &Option<Isotope>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match isotope {
+            Some(isotope) => serializer.serialize_some(&isotope_to_string(isotope)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Isotope>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| {
+                isotope_from_str(&text)
+                    .ok_or_else(|| de::Error::custom(format!("unknown isotope: {text}")))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn element_symbols() {
+        assert_eq!(element_from_symbol("Cl"), Some(Element::Cl));
+        assert_eq!(element_from_symbol("cl"), None)
+    }
+
+    #[cfg(feature = "serde")]
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Sample {
+        #[serde(with = "element")]
+        element: Element,
+        #[serde(with = "isotope")]
+        isotope: Isotope,
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn adapters() {
+        let sample = Sample {
+            element: Element::Br,
+            isotope: isotope_from_str("2H").unwrap(),
+        };
+        let json = serde_json::to_string(&sample).unwrap();
+
+        assert_eq!(json, r#"{"element":"Br","isotope":"2H"}"#);
+        assert_eq!(serde_json::from_str::<Sample>(&json).unwrap(), sample);
+        assert!(serde_json::from_str::<Sample>(r#"{"element":"Q","isotope":"2H"}"#).is_err())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn isotope_strings() {
+        let isotope = isotope_from_str("13C").unwrap();
+
+        assert_eq!(isotope.element(), Element::C);
+        assert_eq!(isotope_to_string(&isotope), "13C");
+        assert_eq!(isotope_from_str("C"), None);
+        assert_eq!(isotope_from_str("13"), None);
+        assert_eq!(isotope_from_str("999C"), None)
+    }
+}
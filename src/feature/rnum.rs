@@ -3,6 +3,8 @@ use std::fmt;
 
 // A ring closure digit (rnum), as described in
 /// [OpenSMILES](http://opensmiles.org/opensmiles.html).
+///
+/// With the `serde` feature, serializes as an integer from 0 to 99.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
 pub struct Rnum(u8);
 
@@ -11,6 +13,11 @@ impl Rnum {
         assert!(n <= 99, "Rnum must be in 0..=99");
         Self(n)
     }
+
+    /// Returns the ring closure number.
+    pub const fn value(&self) -> u8 {
+        self.0
+    }
 }
 
 impl TryFrom<u16> for Rnum {
@@ -34,3 +41,33 @@ impl fmt::Display for Rnum {
         }
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Rnum {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Rnum {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+
+        Self::try_from(u16::from(value))
+            .map_err(|()| serde::de::Error::custom(format!("rnum out of range: {value}")))
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serialization {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn range() {
+        assert_eq!(serde_json::to_string(&Rnum::new(12)).unwrap(), "12");
+        assert_eq!(serde_json::from_str::<Rnum>("99").unwrap(), Rnum::new(99));
+        assert!(serde_json::from_str::<Rnum>("100").is_err())
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use mendeleev::OxidationStateCategory;
+
+use super::Charge;
+use crate::Element;
+
+/// A source of the valences an atom may take, by element and charge.
+pub trait ValenceModel {
+    /// Returns the valence targets of `element` with `charge`, in
+    /// increasing order. An empty slice means any valence is allowed, so
+    /// no hydrogens are implied and no atom is hypervalent.
+    fn targets(&self, element: Element, charge: i8) -> &[u8];
+}
+
+/// A table of valence targets for each element and charge.
+///
+/// The default table follows OpenSMILES for the organic subset and keeps
+/// the other elements' targets as used before. Other p-block atoms take
+/// the targets of the isoelectronic element in the same period, so `[I+]`
+/// is like tellurium, and metals take their common oxidation states less
+/// their charge, so `[Fe+2]` takes 0 or 1.
+///
+/// ```
+/// use yowl::feature::{ValenceModel, ValenceTable};
+/// use yowl::Element;
+///
+/// let mut table = ValenceTable::default();
+///
+/// assert_eq!(table.targets(Element::Al, 0), &[3]);
+/// assert_eq!(table.targets(Element::I, 1), &[2, 4, 6]);
+///
+/// table.insert(Element::Al, 0, vec![3, 4]);
+///
+/// assert_eq!(table.targets(Element::Al, 0), &[3, 4]);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValenceTable {
+    targets: HashMap<(Element, i8), Vec<u8>>,
+}
+
+impl ValenceTable {
+    /// Constructs a table without targets.
+    pub fn empty() -> Self {
+        Self {
+            targets: HashMap::new(),
+        }
+    }
+
+    /// Constructs a table like RDKit's default valence list. Charged atoms
+    /// take the targets of the isoelectronic neutral element, and
+    /// transition metals, lanthanides and actinides allow any valence.
+    pub fn rdkit() -> Self {
+        Self::from_fn(|element, charge| {
+            let number = element.atomic_number() as i16 - i16::from(charge);
+
+            match number {
+                0 => vec![0],
+                1..=118 => rdkit_base(Element::list()[number as usize - 1]).to_vec(),
+                _ => Vec::new(),
+            }
+        })
+    }
+
+    /// Sets the targets of `element` with `charge`, returning the previous
+    /// ones. An empty list allows any valence.
+    pub fn insert(
+        &mut self,
+        element: Element,
+        charge: i8,
+        mut targets: Vec<u8>,
+    ) -> Option<Vec<u8>> {
+        targets.sort_unstable();
+        targets.dedup();
+
+        self.targets.insert((element, charge), targets)
+    }
+
+    fn from_fn(f: impl Fn(Element, i8) -> Vec<u8>) -> Self {
+        let mut result = Self::empty();
+
+        for element in Element::iter() {
+            for charge in Charge::MIN..=Charge::MAX {
+                let targets = f(element, charge);
+
+                if !targets.is_empty() {
+                    result.targets.insert((element, charge), targets);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+impl Default for ValenceTable {
+    fn default() -> Self {
+        Self::from_fn(standard_targets)
+    }
+}
+
+impl ValenceModel for ValenceTable {
+    fn targets(&self, element: Element, charge: i8) -> &[u8] {
+        self.targets
+            .get(&(element, charge))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<M: ValenceModel + ?Sized> ValenceModel for &M {
+    fn targets(&self, element: Element, charge: i8) -> &[u8] {
+        (**self).targets(element, charge)
+    }
+}
+
+/// Returns the default table, built once.
+pub(crate) fn standard_valences() -> &'static ValenceTable {
+    static TABLE: OnceLock<ValenceTable> = OnceLock::new();
+
+    TABLE.get_or_init(ValenceTable::default)
+}
+
+fn standard_targets(element: Element, charge: i8) -> Vec<u8> {
+    if let Some(targets) = organic_targets(element, charge) {
+        return targets.to_vec();
+    }
+
+    if charge == 0 {
+        return base(element);
+    }
+
+    if element == Element::H {
+        return if charge.abs() == 1 {
+            vec![0]
+        } else {
+            Vec::new()
+        };
+    }
+
+    if (13..=17).contains(&group(element)) {
+        let number = element.atomic_number() as i16 - i16::from(charge);
+
+        if let Some(&other) = Element::list().get((number - 1) as usize) {
+            if other.period() == element.period() && (13..=18).contains(&group(other)) {
+                return base(other);
+            }
+        }
+    }
+
+    base(element)
+        .into_iter()
+        .filter_map(|target| u8::try_from(i16::from(target) - i16::from(charge)).ok())
+        .collect()
+}
+
+/// Returns the targets of the organic subset and its heavier congeners
+/// as given by OpenSMILES, and charged forms of them.
+fn organic_targets(element: Element, charge: i8) -> Option<&'static [u8]> {
+    Some(match (element, charge) {
+        (Element::B, -3) => &[2],
+        (Element::B, -2) => &[3, 5],
+        (Element::B, -1) => &[4],
+        (Element::B, 0) => &[3],
+        (Element::C | Element::Si, -2) => &[2],
+        (Element::C | Element::Si, -1) => &[3, 5],
+        (Element::C | Element::Si, 0) => &[4],
+        (Element::C | Element::Si, 1) => &[3],
+        (Element::N | Element::P | Element::As, -1) => &[2, 4, 6],
+        (Element::N | Element::P | Element::As, 0) => &[3, 5],
+        (Element::N | Element::P | Element::As, 1) => &[4],
+        (Element::O, 0) => &[2],
+        (Element::O, 1) => &[3, 5],
+        (Element::S | Element::Se | Element::Te, 0) => &[2, 4, 6],
+        (Element::S | Element::Se | Element::Te, 1) => &[3, 5],
+        (Element::F | Element::Cl | Element::Br | Element::I | Element::At | Element::Ts, 0) => {
+            &[1]
+        }
+        _ => return None,
+    })
+}
+
+/// Returns the targets of a neutral atom of `element`.
+fn base(element: Element) -> Vec<u8> {
+    if let Some(targets) = organic_targets(element, 0) {
+        return targets.to_vec();
+    }
+
+    let targets: &[u8] = match element {
+        Element::H => &[1],
+        Element::Al | Element::Ga | Element::In => &[3],
+        Element::Tl | Element::Nh => &[1, 3],
+        Element::Ge => &[4],
+        Element::Sn | Element::Pb | Element::Fl => &[2, 4],
+        Element::Sb | Element::Bi | Element::Mc => &[3, 5],
+        Element::Po | Element::Lv => &[2, 4, 6],
+        Element::He | Element::Ne | Element::Ar | Element::Rn | Element::Og => &[0],
+        Element::Kr => &[0, 2],
+        Element::Xe => &[0, 2, 4, 6, 8],
+        // common states missing from the main oxidation states
+        Element::Ti | Element::V => &[2, 3, 4, 5],
+        Element::Cr => &[2, 3, 6],
+        Element::Mn => &[2, 3, 4, 6, 7],
+        Element::Cu => &[1, 2],
+        Element::Ru => &[2, 3, 4],
+        Element::Re => &[4, 7],
+        Element::Os => &[4, 8],
+        Element::Au => &[1, 3],
+        Element::Sm | Element::Yb => &[2, 3],
+        Element::U => &[3, 4, 5, 6],
+        _ => &[],
+    };
+
+    if !targets.is_empty() {
+        return targets.to_vec();
+    }
+
+    let mut targets = element
+        .oxidation_states(OxidationStateCategory::Main)
+        .iter()
+        .filter_map(|&state| u8::try_from(state).ok())
+        .filter(|&state| state > 0)
+        .collect::<Vec<_>>();
+
+    targets.sort_unstable();
+    targets.dedup();
+
+    // superheavy elements without data follow the congener above them
+    match element.atomic_number() {
+        number if targets.is_empty() && number > 86 => base(Element::list()[number as usize - 33]),
+        _ => targets,
+    }
+}
+
+fn rdkit_base(element: Element) -> &'static [u8] {
+    match element {
+        Element::H | Element::Li | Element::Na | Element::K | Element::Rb | Element::Cs => &[1],
+        Element::Fr | Element::F | Element::Cl | Element::Br => &[1],
+        Element::Be | Element::Mg | Element::Ca | Element::Sr | Element::Ba | Element::Ra => &[2],
+        Element::O => &[2],
+        Element::B | Element::Al | Element::Ga | Element::In | Element::N => &[3],
+        Element::C | Element::Si | Element::Ge => &[4],
+        Element::He | Element::Ne | Element::Ar | Element::Kr | Element::Rn => &[0],
+        Element::Xe => &[0, 2, 4, 6],
+        Element::P | Element::As | Element::Sb => &[3, 5, 7],
+        Element::S | Element::Se | Element::Te | Element::Po => &[2, 4, 6],
+        Element::I | Element::At => &[1, 3, 5],
+        Element::Sn | Element::Pb => &[2, 4],
+        Element::Tl => &[1, 3],
+        Element::Bi => &[3, 5],
+        _ => &[],
+    }
+}
+
+fn group(element: Element) -> u32 {
+    element.group().map_or(0, |group| group.group_number())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn organic_subset() {
+        let table = ValenceTable::default();
+
+        assert_eq!(table.targets(Element::C, 0), &[4]);
+        assert_eq!(table.targets(Element::N, 0), &[3, 5]);
+        assert_eq!(table.targets(Element::N, -1), &[2, 4, 6]);
+        assert_eq!(table.targets(Element::O, 1), &[3, 5]);
+        assert_eq!(table.targets(Element::S, 0), &[2, 4, 6]);
+        assert_eq!(table.targets(Element::Cl, 0), &[1])
+    }
+
+    #[test]
+    fn p_block() {
+        let table = ValenceTable::default();
+
+        assert_eq!(table.targets(Element::Al, 0), &[3]);
+        assert_eq!(table.targets(Element::Al, -1), &[4]);
+        assert_eq!(table.targets(Element::I, 1), &[2, 4, 6]);
+        assert_eq!(table.targets(Element::Cl, -1), &[0]);
+        assert_eq!(table.targets(Element::O, -1), &[1]);
+        assert_eq!(table.targets(Element::Sb, 0), &[3, 5])
+    }
+
+    #[test]
+    fn metals() {
+        let table = ValenceTable::default();
+
+        assert_eq!(table.targets(Element::Na, 0), &[1]);
+        assert_eq!(table.targets(Element::Na, 1), &[0]);
+        assert_eq!(table.targets(Element::Fe, 0), &[2, 3]);
+        assert_eq!(table.targets(Element::Fe, 2), &[0, 1]);
+        assert_eq!(table.targets(Element::Cu, 0), &[1, 2]);
+        assert_eq!(table.targets(Element::Db, 0), &[5])
+    }
+
+    #[test]
+    fn every_element() {
+        let table = ValenceTable::default();
+
+        for element in Element::iter() {
+            assert!(!table.targets(element, 0).is_empty(), "{element:?}")
+        }
+    }
+
+    #[test]
+    fn hydrogen_and_noble_gases() {
+        let table = ValenceTable::default();
+
+        assert_eq!(table.targets(Element::H, 0), &[1]);
+        assert_eq!(table.targets(Element::H, 1), &[0]);
+        assert_eq!(table.targets(Element::H, -1), &[0]);
+        assert_eq!(table.targets(Element::He, 0), &[0]);
+        assert_eq!(table.targets(Element::Xe, 0), &[0, 2, 4, 6, 8])
+    }
+
+    #[test]
+    fn rdkit() {
+        let table = ValenceTable::rdkit();
+
+        assert_eq!(table.targets(Element::P, 0), &[3, 5, 7]);
+        assert_eq!(table.targets(Element::I, 0), &[1, 3, 5]);
+        assert_eq!(table.targets(Element::N, 1), &[4]);
+        assert_eq!(table.targets(Element::B, -1), &[4]);
+        assert_eq!(table.targets(Element::Na, 1), &[0]);
+        assert_eq!(table.targets(Element::Fe, 0), &[] as &[u8])
+    }
+
+    #[test]
+    fn insert() {
+        let mut table = ValenceTable::empty();
+
+        assert_eq!(table.insert(Element::C, 0, vec![4, 2, 4]), None);
+        assert_eq!(table.targets(Element::C, 0), &[2, 4]);
+        assert_eq!(table.targets(Element::N, 0), &[] as &[u8])
+    }
+}
@@ -3,6 +3,8 @@ use std::fmt;
 
 /// Represents the virtual hydrogen count on a bracket atom.
 /// See: [Hydrogen Suppression in SMILES](https://depth-first.com/articles/2020/06/08/hydrogen-suppression-in-smiles/).
+///
+/// With the `serde` feature, serializes as an integer from 0 to 9.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum VirtualHydrogen {
     H0,
@@ -81,3 +83,20 @@ impl fmt::Display for VirtualHydrogen {
         )
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for VirtualHydrogen {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.into())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for VirtualHydrogen {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let count = u8::deserialize(deserializer)?;
+
+        Self::try_from(count)
+            .map_err(|()| serde::de::Error::custom(format!("hydrogen count out of range: {count}")))
+    }
+}
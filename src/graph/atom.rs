@@ -1,8 +1,13 @@
 use super::Bond;
-use crate::feature::{AtomKind, Symbol};
+use crate::feature::{standard_valences, AtomKind, Symbol, ValenceModel, VirtualHydrogen};
 
 /// Atom used in graph-like (adjacency) SMILES representation.
-#[derive(Debug, PartialEq)]
+///
+/// With the `serde` feature, serializes as a struct with `kind` and
+/// `bonds` fields. Use the `graph::atoms` adapter to check bond symmetry
+/// when deserializing a whole graph.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Atom {
     pub kind: AtomKind,
     pub bonds: Vec<Bond>,
@@ -26,7 +31,54 @@ impl Atom {
     /// Subvalence represents the maximum number of [implicit hydrogens](https://depth-first.com/articles/2020/06/08/hydrogen-suppression-in-smiles/)
     /// that can be added to this Atom without exceeding a valence target.
     /// This value is independent of an atom's aromaticity marking.
+    /// Charged atoms take the targets for their charge, so a lone `[O-]`
+    /// has a subvalence of 1, and `[NH4+]` of 0.
     pub fn subvalence(&self) -> u8 {
+        self.subvalence_with(standard_valences())
+    }
+
+    /// Returns the subvalence of this Atom with valence targets given by
+    /// `model`.
+    pub fn subvalence_with<M: ValenceModel + ?Sized>(&self, model: &M) -> u8 {
+        next_target(self.kind.targets_with(model), self.valence())
+    }
+
+    /// Returns the number of implicit or virtual hydrogens at this Atom,
+    /// accounting for aromaticity.
+    pub fn suppressed_hydrogens(&self) -> u8 {
+        self.suppressed_hydrogens_with(standard_valences())
+    }
+
+    /// Returns the number of implicit or virtual hydrogens at this Atom,
+    /// with valence targets given by `model`.
+    pub fn suppressed_hydrogens_with<M: ValenceModel + ?Sized>(&self, model: &M) -> u8 {
+        match &self.kind {
+            AtomKind::Symbol(Symbol::Star) => 0,
+            AtomKind::Symbol(Symbol::Aromatic(_)) => self.subvalence_with(model).saturating_sub(1),
+            AtomKind::Symbol(Symbol::Aliphatic(_)) => self.subvalence_with(model),
+
+            AtomKind::Bracket { hcount, .. } => hcount.as_ref().map_or(0, std::convert::Into::into),
+        }
+    }
+
+    /// Returns true if the valence of this Atom, counting virtual
+    /// hydrogens, exceeds every valence target, as does that of `[ClH2]`
+    /// or `C(C)(C)(C)(C)C`. Atoms without targets are never hypervalent.
+    pub fn is_hypervalent(&self) -> bool {
+        self.is_hypervalent_with(standard_valences())
+    }
+
+    /// Returns true if this Atom is hypervalent with valence targets given
+    /// by `model`.
+    pub fn is_hypervalent_with<M: ValenceModel + ?Sized>(&self, model: &M) -> bool {
+        self.kind
+            .targets_with(model)
+            .last()
+            .is_some_and(|&target| self.valence() > target)
+    }
+
+    /// Returns the total bond order plus virtual hydrogens.
+    fn valence(&self) -> u8 {
         let hcount = match &self.kind {
             AtomKind::Bracket {
                 hcount: Some(h), ..
@@ -34,28 +86,48 @@ impl Atom {
             _ => 0,
         };
 
-        let valence = self
-            .bonds
-            .iter()
-            .fold(hcount, |sum, bond| sum + bond.order());
-        self.kind
-            .targets()
+        self.bonds
             .iter()
-            .find(|&&target| target >= valence)
-            .map_or(0, |&target| target - valence)
+            .fold(hcount, |sum, bond| sum + bond.order())
     }
+}
 
-    /// Returns the number of implicit or virtual hydrogens at this Atom,
-    /// accounting for aromaticity.
-    pub fn suppressed_hydrogens(&self) -> u8 {
-        let subvalence = self.subvalence();
-        match &self.kind {
-            AtomKind::Symbol(Symbol::Star) => 0,
-            AtomKind::Symbol(Symbol::Aromatic(_)) => subvalence.saturating_sub(1),
-            AtomKind::Symbol(Symbol::Aliphatic(_)) => subvalence,
+/// Returns the number of hydrogens that can be added to an atom of `kind`
+/// with `valence` without exceeding a valence target.
+pub(crate) fn subvalence(kind: &AtomKind, valence: u8) -> u8 {
+    next_target(kind.targets(), valence)
+}
 
-            AtomKind::Bracket { hcount, .. } => hcount.as_ref().map_or(0, std::convert::Into::into),
+fn next_target(targets: &[u8], valence: u8) -> u8 {
+    targets
+        .iter()
+        .find(|&&target| target >= valence)
+        .map_or(0, |&target| target - valence)
+}
+
+/// Returns the bracket atom equivalent to `kind` with bonds of total
+/// order `valence`.
+pub(crate) fn bracket(kind: &AtomKind, valence: u8) -> AtomKind {
+    match *kind {
+        AtomKind::Symbol(symbol) => {
+            let hcount = match symbol {
+                Symbol::Star => 0,
+                Symbol::Aliphatic(_) => subvalence(kind, valence),
+                Symbol::Aromatic(_) => subvalence(kind, valence).saturating_sub(1),
+            };
+
+            AtomKind::Bracket {
+                isotope: None,
+                symbol,
+                configuration: None,
+                hcount: VirtualHydrogen::try_from(hcount)
+                    .ok()
+                    .filter(|hcount| !hcount.is_zero()),
+                charge: None,
+                map: None,
+            }
         }
+        AtomKind::Bracket { .. } => *kind,
     }
 }
 
@@ -64,7 +136,7 @@ mod subvalence {
     use crate::Element;
 
     use super::*;
-    use crate::feature::{BondKind, Charge, VirtualHydrogen};
+    use crate::feature::{BondKind, Charge, ValenceTable, VirtualHydrogen};
 
     #[test]
     fn star() {
@@ -210,6 +282,62 @@ mod subvalence {
 
         assert_eq!(atom.subvalence(), 1)
     }
+
+    #[test]
+    fn bracket_aluminum() {
+        let atom = Atom {
+            kind: AtomKind::Bracket {
+                isotope: None,
+                symbol: Symbol::Aliphatic(Element::Al),
+                configuration: None,
+                hcount: None,
+                charge: None,
+                map: None,
+            },
+            bonds: vec![Bond::new(BondKind::Single, 1)],
+        };
+
+        assert_eq!(atom.subvalence(), 2)
+    }
+
+    #[test]
+    fn charged() {
+        let atom = |element: Element, charge: i8| Atom {
+            kind: AtomKind::Bracket {
+                isotope: None,
+                symbol: Symbol::Aliphatic(element),
+                configuration: None,
+                hcount: None,
+                charge: Charge::new(charge),
+                map: None,
+            },
+            bonds: vec![],
+        };
+
+        assert_eq!(atom(Element::O, -1).subvalence(), 1);
+        assert_eq!(atom(Element::N, 1).subvalence(), 4);
+        assert_eq!(atom(Element::Cl, -1).subvalence(), 0);
+        assert_eq!(atom(Element::I, 1).subvalence(), 2);
+        assert_eq!(
+            atom(Element::O, -1).subvalence_with(&ValenceTable::empty()),
+            0
+        )
+    }
+
+    #[test]
+    fn phosphorus_with_rdkit() {
+        let atom = Atom {
+            kind: AtomKind::Symbol(Symbol::Aliphatic(Element::P)),
+            bonds: vec![
+                Bond::new(BondKind::Double, 1),
+                Bond::new(BondKind::Double, 2),
+                Bond::new(BondKind::Double, 3),
+            ],
+        };
+
+        assert_eq!(atom.subvalence(), 0);
+        assert_eq!(atom.subvalence_with(&ValenceTable::rdkit()), 1)
+    }
 }
 
 #[cfg(test)]
@@ -346,3 +474,69 @@ mod suppressed_hydrogens {
         assert_eq!(atom.suppressed_hydrogens(), 1)
     }
 }
+
+#[cfg(test)]
+mod hypervalence {
+    use super::*;
+    use crate::feature::{BondKind, Charge, ValenceTable};
+    use crate::Element;
+
+    fn bracket(element: Element, charge: i8, hcount: u8, bonds: usize) -> Atom {
+        Atom {
+            kind: AtomKind::Bracket {
+                isotope: None,
+                symbol: Symbol::Aliphatic(element),
+                configuration: None,
+                hcount: VirtualHydrogen::try_from(hcount).ok(),
+                charge: Charge::new(charge).filter(|_| charge != 0),
+                map: None,
+            },
+            bonds: (0..bonds)
+                .map(|tid| Bond::new(BondKind::Single, tid + 1))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn within_targets() {
+        assert!(!bracket(Element::C, 0, 4, 0).is_hypervalent());
+        assert!(!bracket(Element::N, 1, 4, 0).is_hypervalent());
+        assert!(!bracket(Element::Al, 0, 0, 3).is_hypervalent());
+        assert!(!bracket(Element::I, 1, 0, 2).is_hypervalent())
+    }
+
+    #[test]
+    fn exceeding_targets() {
+        assert!(bracket(Element::C, 0, 5, 0).is_hypervalent());
+        assert!(bracket(Element::Cl, 0, 2, 0).is_hypervalent());
+        assert!(bracket(Element::Al, 0, 0, 4).is_hypervalent());
+        assert!(bracket(Element::Na, 1, 0, 1).is_hypervalent())
+    }
+
+    #[test]
+    fn star() {
+        let atom = Atom {
+            kind: AtomKind::Symbol(Symbol::Star),
+            bonds: (0..8).map(|tid| Bond::new(BondKind::Single, tid)).collect(),
+        };
+
+        assert!(!atom.is_hypervalent())
+    }
+
+    #[test]
+    fn with_model() {
+        let iodine = bracket(Element::I, 0, 0, 3);
+        let iron = bracket(Element::Fe, 0, 0, 6);
+        let mut table = ValenceTable::empty();
+
+        assert!(iodine.is_hypervalent());
+        assert!(!iodine.is_hypervalent_with(&ValenceTable::rdkit()));
+        assert!(iron.is_hypervalent());
+        assert!(!iron.is_hypervalent_with(&ValenceTable::rdkit()));
+
+        table.insert(Element::Fe, 0, vec![6]);
+
+        assert!(!iron.is_hypervalent_with(&table));
+        assert_eq!(iron.subvalence_with(&table), 0)
+    }
+}
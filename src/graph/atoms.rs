@@ -0,0 +1,88 @@
+//! Serde adapter for whole graphs, for use as
+//! `#[serde(with = "yowl::graph::atoms")]` on a `Vec<Atom>` field.
+//!
+//! A graph serializes as a sequence of atoms. Deserialization additionally
+//! checks that every bond targets another atom in the graph at most once,
+//! and that it's matched by a bond back of the reverse kind.
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+use super::sound_graph::check;
+use super::Atom;
+
+pub fn serialize<S: Serializer>(atoms: &[Atom], serializer: S) -> Result<S::Ok, S::Error> {
+    atoms.serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Atom>, D::Error> {
+    let atoms = Vec::<Atom>::deserialize(deserializer)?;
+
+    check(&atoms).map_err(de::Error::custom)?;
+
+    Ok(atoms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::{AtomKind, BondKind, Symbol};
+    use crate::graph::Bond;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Molecule {
+        #[serde(with = "super")]
+        atoms: Vec<Atom>,
+    }
+
+    fn star(bonds: Vec<Bond>) -> Atom {
+        Atom {
+            kind: AtomKind::Symbol(Symbol::Star),
+            bonds,
+        }
+    }
+
+    fn roundtrip(atoms: Vec<Atom>) -> Result<Molecule, serde_json::Error> {
+        let json = serde_json::to_string(&Molecule { atoms }).unwrap();
+
+        serde_json::from_str(&json)
+    }
+
+    #[test]
+    fn symmetric() {
+        let atoms = vec![
+            star(vec![Bond::new(BondKind::Up, 1)]),
+            star(vec![Bond::new(BondKind::Down, 0)]),
+        ];
+
+        assert_eq!(roundtrip(atoms).unwrap().atoms.len(), 2)
+    }
+
+    #[test]
+    fn half_bond() {
+        let atoms = vec![star(vec![Bond::new(BondKind::Single, 1)]), star(vec![])];
+
+        assert!(roundtrip(atoms).is_err())
+    }
+
+    #[test]
+    fn mismatched_kind() {
+        let atoms = vec![
+            star(vec![Bond::new(BondKind::Up, 1)]),
+            star(vec![Bond::new(BondKind::Up, 0)]),
+        ];
+
+        assert!(roundtrip(atoms).is_err())
+    }
+
+    #[test]
+    fn unknown_target() {
+        assert!(roundtrip(vec![star(vec![Bond::new(BondKind::Single, 4)])]).is_err())
+    }
+
+    #[test]
+    fn loop_bond() {
+        assert!(roundtrip(vec![star(vec![Bond::new(BondKind::Single, 0)])]).is_err())
+    }
+}
@@ -1,7 +1,11 @@
 use crate::feature::BondKind;
 
 /// A bond from a graph-like Atom to an Atom ID.
-#[derive(Debug, PartialEq, Eq)]
+///
+/// With the `serde` feature, serializes as a struct with `kind` and `tid`
+/// fields.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bond {
     pub kind: BondKind,
     pub tid: usize,
@@ -14,14 +18,18 @@ impl Bond {
     }
 
     /// Returns the order of this Bond. Elided, Single, Up, Down,
-    /// and Aromatic kinds return 1. The rest return the bond multiplicity.
+    /// and Aromatic kinds return 1. As the bond counts toward the valence
+    /// of the acceptor only, DativeLeft returns 1 and DativeRight 0. Zero
+    /// returns 0, and the rest return the bond multiplicity.
     pub const fn order(&self) -> u8 {
         match &self.kind {
+            BondKind::DativeRight | BondKind::Zero => 0,
             BondKind::Elided
             | BondKind::Single
             | BondKind::Up
             | BondKind::Down
-            | BondKind::Aromatic => 1,
+            | BondKind::Aromatic
+            | BondKind::DativeLeft => 1,
             BondKind::Double => 2,
             BondKind::Triple => 3,
             BondKind::Quadruple => 4,
@@ -52,8 +60,8 @@ mod tests {
             BondKind::Aromatic,
         ];
         for kind in kinds.iter() {
-            let bond = Bond::new(kind.clone(), 0);
-            assert_eq!(bond.order(), 1, "{:?} should have order 1", kind);
+            let bond = Bond::new(*kind, 0);
+            assert_eq!(bond.order(), 1, "{kind:?} should have order 1");
         }
     }
 
@@ -66,17 +74,22 @@ mod tests {
             (BondKind::Quadruple, 4),
         ];
         for (kind, expected) in cases.iter() {
-            let bond = Bond::new(kind.clone(), 1);
+            let bond = Bond::new(*kind, 1);
             assert_eq!(
                 bond.order(),
                 *expected,
-                "{:?} should have order {}",
-                kind,
-                expected
+                "{kind:?} should have order {expected}"
             );
         }
     }
 
+    #[test]
+    fn test_order_dative_zero() {
+        assert_eq!(Bond::new(BondKind::DativeRight, 1).order(), 0);
+        assert_eq!(Bond::new(BondKind::DativeLeft, 1).order(), 1);
+        assert_eq!(Bond::new(BondKind::Zero, 1).order(), 0);
+    }
+
     #[test]
     fn test_is_aromatic() {
         let aro = Bond::new(BondKind::Aromatic, 2);
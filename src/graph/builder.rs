@@ -43,13 +43,26 @@ pub struct Builder {
 impl Builder {
     /// Builds the representation created by using the `Follower` trait
     /// methods.
-    pub fn build(self) -> Result<Vec<Atom>, Error> {
-        if let Some(error) = self.errors.into_iter().next() {
+    pub fn build(mut self) -> Result<Vec<Atom>, Error> {
+        self.take()
+    }
+
+    /// Builds the representation created by using the `Follower` trait
+    /// methods, leaving this Builder empty but with its allocations
+    /// retained for reuse.
+    pub fn take(&mut self) -> Result<Vec<Atom>, Error> {
+        self.stack.clear();
+        self.opens.clear();
+        self.ring_idx = 0;
+
+        if let Some(error) = self.errors.drain(..).next() {
+            self.graph.clear();
+
             return Err(error);
         }
 
         self.graph
-            .into_iter()
+            .drain(..)
             .enumerate()
             .map(|(idx, node)| {
                 node.check_stereo(idx);
@@ -254,6 +267,24 @@ mod errors {
 
         assert_eq!(builder.build(), Err(Error::Rnum(2)))
     }
+
+    #[test]
+    fn take_after_error() {
+        let mut builder = Builder::default();
+
+        builder.root(AtomKind::Symbol(Symbol::Star));
+        builder.join(BondKind::Elided, Rnum::new(1));
+
+        assert_eq!(builder.take(), Err(Error::Rnum(0)));
+
+        builder.root(AtomKind::Symbol(Symbol::Star));
+
+        assert_eq!(
+            builder.take(),
+            Ok(vec![Atom::new(AtomKind::Symbol(Symbol::Star))])
+        );
+        assert_eq!(builder, Builder::default())
+    }
 }
 
 #[cfg(test)]
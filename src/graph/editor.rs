@@ -0,0 +1,566 @@
+use thiserror::Error;
+
+use super::{bracket, Atom, Bond};
+use crate::feature::{AtomKind, BondKind, Charge, Configuration, Symbol};
+use crate::Element;
+
+/// An error resulting from editing a graph.
+#[derive(Debug, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EditError {
+    #[error("The atom is unknown: {0}")]
+    UnknownAtom(usize),
+    #[error("A bond can't join an atom to itself: {0}")]
+    Loop(usize),
+    #[error("The bond already exists: ({0}, {1})")]
+    DuplicateBond(usize, usize),
+    #[error("The bond is unknown: ({0}, {1})")]
+    UnknownBond(usize, usize),
+}
+
+/// Edits a graph, keeping both halves of every bond in sync.
+///
+/// Removing a neighbor from an atom with a `TH1` or `TH2` configuration
+/// and four bonds turns the neighbor into an implicit one, as for a lone
+/// pair, keeping the configuration's meaning. Adding a fourth neighbor to
+/// such an atom undoes that. Any other change to the neighbors of an atom
+/// with a configuration clears it. Hydrogen counts of bracket atoms are
+/// never changed.
+///
+/// ```
+/// use yowl::feature::{AtomKind, BondKind, Symbol};
+/// use yowl::graph::{Builder, Editor};
+/// use yowl::read::read;
+/// use yowl::walk::walk;
+/// use yowl::write::Writer;
+/// use yowl::Element;
+///
+/// let mut builder = Builder::default();
+///
+/// read("CC(=O)Cl", &mut builder, None).expect("read");
+///
+/// let mut editor = Editor::new(builder.build().expect("atoms"));
+/// let mapping = editor.remove_atom(3).expect("remove");
+/// let nitrogen = editor.add_atom(AtomKind::Symbol(Symbol::Aliphatic(Element::N)));
+///
+/// editor.add_bond(1, nitrogen, BondKind::Elided).expect("bond");
+///
+/// let mut writer = Writer::default();
+///
+/// walk(editor.into_atoms(), &mut writer).expect("walk");
+///
+/// assert_eq!(mapping, vec![Some(0), Some(1), Some(2), None]);
+/// assert_eq!(writer.write(), "CC(=O)N")
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Editor {
+    atoms: Vec<Atom>,
+}
+
+impl Editor {
+    /// Constructs an Editor over `atoms`.
+    pub const fn new(atoms: Vec<Atom>) -> Self {
+        Self { atoms }
+    }
+
+    /// Returns the atoms.
+    pub fn atoms(&self) -> &[Atom] {
+        &self.atoms
+    }
+
+    /// Returns the atoms, consuming this Editor.
+    pub fn into_atoms(self) -> Vec<Atom> {
+        self.atoms
+    }
+
+    /// Adds an atom without bonds, returning its index.
+    pub fn add_atom(&mut self, kind: AtomKind) -> usize {
+        self.atoms.push(Atom::new(kind));
+
+        self.atoms.len() - 1
+    }
+
+    /// Adds a bond of `kind` from `sid` to `tid`, and its reverse.
+    pub fn add_bond(&mut self, sid: usize, tid: usize, kind: BondKind) -> Result<(), EditError> {
+        self.check(sid)?;
+        self.check(tid)?;
+
+        if sid == tid {
+            return Err(EditError::Loop(sid));
+        }
+
+        if self.find(sid, tid).is_some() {
+            return Err(EditError::DuplicateBond(sid, tid));
+        }
+
+        add_neighbor(&mut self.atoms[sid], Bond::new(kind, tid));
+        add_neighbor(&mut self.atoms[tid], Bond::new(kind.reverse(), sid));
+
+        Ok(())
+    }
+
+    /// Removes the bond from `sid` to `tid` and its reverse, returning the
+    /// kind of the bond from `sid`.
+    pub fn remove_bond(&mut self, sid: usize, tid: usize) -> Result<BondKind, EditError> {
+        let (forward, back) = self.bond(sid, tid)?;
+        let kind = self.atoms[sid].bonds[forward].kind;
+
+        remove_neighbor(&mut self.atoms[sid], forward);
+        remove_neighbor(&mut self.atoms[tid], back);
+
+        Ok(kind)
+    }
+
+    /// Sets the bond from `sid` to `tid` to `kind`, and its reverse to the
+    /// reverse kind.
+    pub fn set_bond_kind(
+        &mut self,
+        sid: usize,
+        tid: usize,
+        kind: BondKind,
+    ) -> Result<(), EditError> {
+        let (forward, back) = self.bond(sid, tid)?;
+
+        self.atoms[sid].bonds[forward].kind = kind;
+        self.atoms[tid].bonds[back].kind = kind.reverse();
+
+        Ok(())
+    }
+
+    /// Sets the element of atom `id`, keeping its aromaticity. An atom
+    /// written without brackets keeps its hydrogen count in brackets if
+    /// `element` isn't in the organic subset. Isotopes of other elements
+    /// are cleared.
+    pub fn set_element(&mut self, id: usize, element: Element) -> Result<(), EditError> {
+        self.check(id)?;
+
+        let atom = &mut self.atoms[id];
+        let with_element = |symbol: Symbol| match symbol {
+            Symbol::Star | Symbol::Aliphatic(_) => Symbol::Aliphatic(element),
+            Symbol::Aromatic(_) => Symbol::Aromatic(element),
+        };
+
+        match &mut atom.kind {
+            AtomKind::Symbol(symbol) => {
+                let replaced = with_element(*symbol);
+
+                if !replaced.is_organic() {
+                    atom.kind = bracket(&atom.kind, valence(atom));
+                }
+
+                set_symbol(&mut atom.kind, replaced);
+            }
+            AtomKind::Bracket {
+                isotope, symbol, ..
+            } => {
+                *symbol = with_element(*symbol);
+
+                if isotope.is_some_and(|isotope| isotope.element() != element) {
+                    *isotope = None;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the charge of atom `id`. An atom written without brackets
+    /// keeps its hydrogen count in brackets when given a charge.
+    pub fn set_charge(&mut self, id: usize, charge: Option<Charge>) -> Result<(), EditError> {
+        self.check(id)?;
+
+        let atom = &mut self.atoms[id];
+
+        if charge.is_some() {
+            atom.kind = bracket(&atom.kind, valence(atom));
+        }
+
+        if let AtomKind::Bracket { charge: target, .. } = &mut atom.kind {
+            *target = charge;
+        }
+
+        Ok(())
+    }
+
+    /// Removes atom `id` and its bonds, returning the new index of each
+    /// atom by old index.
+    pub fn remove_atom(&mut self, id: usize) -> Result<Vec<Option<usize>>, EditError> {
+        self.remove_atoms(&[id])
+    }
+
+    /// Removes atoms `ids` and their bonds, returning the new index of each
+    /// atom by old index. Remaining atoms keep their relative order, as do
+    /// their remaining bonds.
+    pub fn remove_atoms(&mut self, ids: &[usize]) -> Result<Vec<Option<usize>>, EditError> {
+        for &id in ids {
+            self.check(id)?;
+        }
+
+        let mut removed = vec![false; self.atoms.len()];
+
+        for &id in ids {
+            removed[id] = true;
+        }
+
+        let mut next = 0;
+        let mapping = removed
+            .iter()
+            .map(|&removed| {
+                if removed {
+                    None
+                } else {
+                    next += 1;
+
+                    Some(next - 1)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for (id, atom) in self.atoms.iter_mut().enumerate() {
+            if removed[id] {
+                continue;
+            }
+
+            while let Some(idx) = atom.bonds.iter().rposition(|bond| removed[bond.tid]) {
+                remove_neighbor(atom, idx);
+            }
+
+            for bond in &mut atom.bonds {
+                bond.tid = mapping[bond.tid].expect("remaining target");
+            }
+        }
+
+        let mut id = 0;
+
+        self.atoms.retain(|_| {
+            id += 1;
+
+            !removed[id - 1]
+        });
+
+        Ok(mapping)
+    }
+
+    /// Appends the atoms of `other`, returning the index of the first. The
+    /// two graphs aren't bonded.
+    pub fn merge(&mut self, other: &[Atom]) -> usize {
+        let offset = self.atoms.len();
+
+        self.atoms.extend(other.iter().map(|atom| {
+            Atom {
+                kind: atom.kind,
+                bonds: atom
+                    .bonds
+                    .iter()
+                    .map(|bond| Bond::new(bond.kind, bond.tid + offset))
+                    .collect(),
+            }
+        }));
+
+        offset
+    }
+
+    fn check(&self, id: usize) -> Result<(), EditError> {
+        if id < self.atoms.len() {
+            Ok(())
+        } else {
+            Err(EditError::UnknownAtom(id))
+        }
+    }
+
+    fn find(&self, sid: usize, tid: usize) -> Option<usize> {
+        self.atoms[sid]
+            .bonds
+            .iter()
+            .position(|bond| bond.tid == tid)
+    }
+
+    /// Returns the index of the bond from `sid` to `tid`, and of its
+    /// reverse.
+    fn bond(&self, sid: usize, tid: usize) -> Result<(usize, usize), EditError> {
+        self.check(sid)?;
+        self.check(tid)?;
+
+        match (self.find(sid, tid), self.find(tid, sid)) {
+            (Some(forward), Some(back)) => Ok((forward, back)),
+            _ => Err(EditError::UnknownBond(sid, tid)),
+        }
+    }
+}
+
+impl From<Vec<Atom>> for Editor {
+    fn from(atoms: Vec<Atom>) -> Self {
+        Self::new(atoms)
+    }
+}
+
+impl From<Editor> for Vec<Atom> {
+    fn from(editor: Editor) -> Self {
+        editor.atoms
+    }
+}
+
+fn valence(atom: &Atom) -> u8 {
+    atom.bonds.iter().map(Bond::order).sum()
+}
+
+fn set_symbol(kind: &mut AtomKind, replaced: Symbol) {
+    match kind {
+        AtomKind::Symbol(symbol) | AtomKind::Bracket { symbol, .. } => *symbol = replaced,
+    }
+}
+
+/// Returns the configuration of `atom` and whether it has an implicit
+/// hydrogen.
+fn stereo(atom: &mut Atom) -> Option<(&mut Option<Configuration>, bool)> {
+    match &mut atom.kind {
+        AtomKind::Bracket {
+            configuration: configuration @ Some(_),
+            hcount,
+            ..
+        } => Some((
+            configuration,
+            hcount.is_some_and(|hcount| !hcount.is_zero()),
+        )),
+        _ => None,
+    }
+}
+
+/// Appends `bond` to `atom`. A fourth neighbor of a tetrahedral center
+/// without hydrogen moves from the implicit first place to the last.
+fn add_neighbor(atom: &mut Atom, bond: Bond) {
+    atom.bonds.push(bond);
+
+    let count = atom.bonds.len();
+
+    if let Some((configuration, hydrogen)) = stereo(atom) {
+        match configuration {
+            Some(Configuration::TH1 | Configuration::TH2) if count == 4 && !hydrogen => {
+                invert(configuration)
+            }
+            _ => *configuration = None,
+        }
+    }
+}
+
+/// Removes the bond at `idx` from `atom`. The removed neighbor of a
+/// tetrahedral center without hydrogen moves to the implicit first place.
+fn remove_neighbor(atom: &mut Atom, idx: usize) {
+    atom.bonds.remove(idx);
+
+    let count = atom.bonds.len();
+
+    if let Some((configuration, hydrogen)) = stereo(atom) {
+        match configuration {
+            Some(Configuration::TH1 | Configuration::TH2) if count == 3 && !hydrogen => {
+                if idx % 2 == 1 {
+                    invert(configuration)
+                }
+            }
+            _ => *configuration = None,
+        }
+    }
+}
+
+fn invert(configuration: &mut Option<Configuration>) {
+    *configuration = match *configuration {
+        Some(Configuration::TH1) => Some(Configuration::TH2),
+        Some(Configuration::TH2) => Some(Configuration::TH1),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::is_isomorphic;
+    use crate::testing::atoms;
+    use crate::walk::walk;
+    use crate::write::Writer;
+    use pretty_assertions::assert_eq;
+
+    fn editor(smiles: &str) -> Editor {
+        Editor::new(atoms(smiles))
+    }
+
+    fn write(editor: Editor) -> String {
+        let mut writer = Writer::default();
+
+        walk(editor.into_atoms(), &mut writer).unwrap();
+
+        writer.write()
+    }
+
+    #[test]
+    fn add_bond() {
+        let mut editor = editor("CC.O");
+
+        assert_eq!(editor.add_bond(1, 2, BondKind::Elided), Ok(()));
+        assert_eq!(write(editor), "CCO")
+    }
+
+    #[test]
+    fn add_bond_errors() {
+        let mut editor = editor("CC");
+
+        assert_eq!(
+            editor.add_bond(0, 2, BondKind::Elided),
+            Err(EditError::UnknownAtom(2))
+        );
+        assert_eq!(
+            editor.add_bond(1, 1, BondKind::Elided),
+            Err(EditError::Loop(1))
+        );
+        assert_eq!(
+            editor.add_bond(1, 0, BondKind::Double),
+            Err(EditError::DuplicateBond(1, 0))
+        )
+    }
+
+    #[test]
+    fn remove_bond() {
+        let mut editor = editor("C1CC1");
+
+        assert_eq!(editor.remove_bond(2, 0), Ok(BondKind::Elided));
+        assert_eq!(editor.remove_bond(2, 0), Err(EditError::UnknownBond(2, 0)));
+        assert_eq!(write(editor), "CCC")
+    }
+
+    #[test]
+    fn set_bond_kind() {
+        let mut editor = editor("CC");
+
+        assert_eq!(editor.set_bond_kind(1, 0, BondKind::Triple), Ok(()));
+        assert_eq!(
+            editor.clone().into_atoms()[0].bonds[0].kind,
+            BondKind::Triple
+        );
+        assert_eq!(write(editor), "C#C")
+    }
+
+    #[test]
+    fn set_bond_kind_directional() {
+        let mut editor = editor("FC=CF");
+
+        editor.set_bond_kind(0, 1, BondKind::Up).unwrap();
+        editor.set_bond_kind(2, 3, BondKind::Up).unwrap();
+
+        assert_eq!(editor.atoms()[1].bonds[0].kind, BondKind::Down);
+        assert_eq!(write(editor), "F/C=C/F")
+    }
+
+    #[test]
+    fn set_element() {
+        let mut editor = editor("CC[13CH2]c1ccccc1");
+
+        editor.set_element(0, Element::N).unwrap();
+        editor.set_element(1, Element::Si).unwrap();
+        editor.set_element(2, Element::O).unwrap();
+        editor.set_element(3, Element::N).unwrap();
+
+        assert_eq!(write(editor), "N[SiH2][OH2]n(ccccc1)1")
+    }
+
+    #[test]
+    fn set_charge() {
+        let mut editor = editor("CN(C)C");
+
+        editor.set_charge(1, Charge::new(1)).unwrap();
+
+        assert_eq!(write(editor.clone()), "C[N+](C)C");
+
+        editor.set_charge(1, None).unwrap();
+
+        assert_eq!(write(editor), "C[N](C)C");
+    }
+
+    #[test]
+    fn unknown_atom() {
+        let mut editor = editor("C");
+
+        assert_eq!(
+            editor.set_element(1, Element::N),
+            Err(EditError::UnknownAtom(1))
+        );
+        assert_eq!(editor.set_charge(1, None), Err(EditError::UnknownAtom(1)));
+        assert_eq!(editor.remove_atoms(&[0, 1]), Err(EditError::UnknownAtom(1)));
+        assert_eq!(editor.atoms().len(), 1)
+    }
+
+    #[test]
+    fn remove_atoms() {
+        let mut editor = editor("OC1CCC(N)CC1");
+
+        assert_eq!(
+            editor.remove_atoms(&[0, 5]),
+            Ok(vec![
+                None,
+                Some(0),
+                Some(1),
+                Some(2),
+                Some(3),
+                None,
+                Some(4),
+                Some(5)
+            ])
+        );
+        assert_eq!(write(editor), "C(CCCCC1)1")
+    }
+
+    #[test]
+    fn remove_atom_keeps_stereo() {
+        let smiles = "[F:1][C@:2]([Cl:3])([Br:4])[I:5]";
+
+        for (id, expected) in [
+            (0, "[C@:2]([Cl:3])([Br:4])[I:5]"),
+            (2, "[F:1][C@@:2]([Br:4])[I:5]"),
+            (3, "[F:1][C@:2]([Cl:3])[I:5]"),
+            (4, "[F:1][C@@:2]([Cl:3])[Br:4]"),
+        ] {
+            let mut editor = editor(smiles);
+
+            editor.remove_atom(id).unwrap();
+
+            assert!(
+                is_isomorphic(editor.atoms(), &atoms(expected), true),
+                "{id}"
+            )
+        }
+    }
+
+    #[test]
+    fn remove_and_add_restores_stereo() {
+        let mut editor = editor("[F:1][C@:2]([Cl:3])([Br:4])[I:5]");
+        let expected = editor.atoms().to_vec();
+        let kind = editor.atoms()[2].kind;
+
+        editor.remove_atom(2).unwrap();
+
+        let chlorine = editor.add_atom(kind);
+
+        editor.add_bond(1, chlorine, BondKind::Elided).unwrap();
+
+        assert!(is_isomorphic(editor.atoms(), &expected, true))
+    }
+
+    #[test]
+    fn remove_atom_clears_stereo() {
+        let mut editor = editor("F[C@H](Cl)Br");
+
+        editor.remove_atom(3).unwrap();
+
+        assert_eq!(write(editor), "F[CH]Cl")
+    }
+
+    #[test]
+    fn merge() {
+        let mut left = editor("CC");
+        let right = editor("O=C").into_atoms();
+
+        assert_eq!(left.merge(&right), 2);
+
+        left.add_bond(1, 3, BondKind::Elided).unwrap();
+
+        assert_eq!(write(left), "CCC=O")
+    }
+}
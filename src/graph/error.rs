@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 #[derive(Debug, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Error {
     #[error("join error")]
     Join(usize, usize),
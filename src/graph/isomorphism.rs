@@ -0,0 +1,610 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::Atom;
+use crate::feature::{standard_valences, AtomKind, BondKind, Configuration, Symbol, ValenceModel};
+use crate::walk::is_odd;
+use crate::{Element, Isotope};
+
+/// Returns true if `left` and `right` are the same molecule up to atom
+/// order. See `isomorphism`.
+///
+/// ```
+/// use yowl::graph::{is_isomorphic, Builder};
+/// use yowl::read::read;
+///
+/// let mut left = Builder::default();
+/// let mut right = Builder::default();
+///
+/// read("CCO", &mut left, None).expect("left");
+/// read("OCC", &mut right, None).expect("right");
+///
+/// let left = left.build().expect("left atoms");
+/// let right = right.build().expect("right atoms");
+///
+/// assert_ne!(left, right);
+/// assert!(is_isomorphic(&left, &right, false));
+/// ```
+pub fn is_isomorphic(left: &[Atom], right: &[Atom], stereo: bool) -> bool {
+    isomorphism(left, right, stereo).is_some()
+}
+
+/// Performs `is_isomorphic`, counting implicit hydrogens by the targets
+/// of `model`.
+pub fn is_isomorphic_with<M: ValenceModel + ?Sized>(
+    left: &[Atom],
+    right: &[Atom],
+    stereo: bool,
+    model: &M,
+) -> bool {
+    isomorphism_with(left, right, stereo, model).is_some()
+}
+
+/// Returns the index in `right` of each atom of `left`, under a mapping
+/// that keeps elements, aromaticity, charges, isotopes, hydrogen counts
+/// and bond orders, or `None` if there's no such mapping. Atom maps are
+/// ignored.
+///
+/// With `stereo`, tetrahedral configurations and the geometry of double
+/// bonds with `Up` and `Down` bonds at both ends must also match. Other
+/// configurations are compared as written.
+///
+/// # Panics
+///
+/// Panics if a bond targets an atom out of range.
+///
+/// ```
+/// use yowl::graph::{isomorphism, Builder};
+/// use yowl::read::read;
+///
+/// let mut left = Builder::default();
+/// let mut right = Builder::default();
+///
+/// read("F[C@H](Cl)Br", &mut left, None).expect("left");
+/// read("Br[C@@H](Cl)F", &mut right, None).expect("right");
+///
+/// let left = left.build().expect("left atoms");
+/// let right = right.build().expect("right atoms");
+///
+/// assert_eq!(isomorphism(&left, &right, true), Some(vec![3, 1, 2, 0]));
+/// ```
+pub fn isomorphism(left: &[Atom], right: &[Atom], stereo: bool) -> Option<Vec<usize>> {
+    isomorphism_with(left, right, stereo, standard_valences())
+}
+
+/// Performs `isomorphism`, counting implicit hydrogens by the targets of
+/// `model`.
+pub fn isomorphism_with<M: ValenceModel + ?Sized>(
+    left: &[Atom],
+    right: &[Atom],
+    stereo: bool,
+    model: &M,
+) -> Option<Vec<usize>> {
+    if left.len() != right.len() {
+        return None;
+    }
+
+    let (left_classes, right_classes) = classes(left, right, model);
+    let mut counts = HashMap::new();
+
+    for &class in &left_classes {
+        *counts.entry(class).or_insert(0isize) += 1;
+    }
+
+    for &class in &right_classes {
+        *counts.entry(class).or_insert(0) -= 1;
+    }
+
+    if counts.values().any(|&count| count != 0) {
+        return None;
+    }
+
+    let mut search = Search {
+        left,
+        right,
+        left_classes: &left_classes,
+        right_classes: &right_classes,
+        order: search_order(left, &left_classes, &counts_by_class(&left_classes)),
+        mapping: vec![None; left.len()],
+        used: vec![false; right.len()],
+        stereo,
+    };
+
+    if search.extend(0) {
+        Some(search.mapping.into_iter().map(Option::unwrap).collect())
+    } else {
+        None
+    }
+}
+
+/// The properties of an atom that a mapping keeps.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct Label {
+    element: Option<Element>,
+    aromatic: bool,
+    isotope: Option<Isotope>,
+    charge: i8,
+    hydrogens: u8,
+    degree: usize,
+}
+
+impl Label {
+    fn new<M: ValenceModel + ?Sized>(atom: &Atom, model: &M) -> Self {
+        let (symbol, isotope, charge) = match atom.kind {
+            AtomKind::Symbol(symbol) => (symbol, None, 0),
+            AtomKind::Bracket {
+                symbol,
+                isotope,
+                charge,
+                ..
+            } => (symbol, isotope, charge.map_or(0, |charge| charge.value())),
+        };
+        let element = match symbol {
+            Symbol::Star => None,
+            Symbol::Aliphatic(element) | Symbol::Aromatic(element) => Some(element),
+        };
+
+        Self {
+            element,
+            aromatic: atom.is_aromatic(),
+            isotope,
+            charge,
+            hydrogens: atom.suppressed_hydrogens_with(model),
+            degree: atom.bonds.len(),
+        }
+    }
+}
+
+/// Partitions the atoms of both graphs into classes by label, refined by
+/// the classes and bond orders of neighbors until stable. Atoms in
+/// different classes can't be mapped to each other.
+pub(crate) fn classes<M: ValenceModel + ?Sized>(
+    left: &[Atom],
+    right: &[Atom],
+    model: &M,
+) -> (Vec<usize>, Vec<usize>) {
+    let atoms = left.iter().chain(right).collect::<Vec<_>>();
+    let mut labels = HashMap::new();
+    let mut classes = atoms
+        .iter()
+        .map(|atom| {
+            let next = labels.len();
+
+            *labels.entry(Label::new(atom, model)).or_insert(next)
+        })
+        .collect::<Vec<_>>();
+    let mut count = labels.len();
+
+    loop {
+        let mut refined = HashMap::new();
+        let next_classes = atoms
+            .iter()
+            .enumerate()
+            .map(|(idx, atom)| {
+                // bond targets are offset for atoms of right
+                let offset = if idx < left.len() { 0 } else { left.len() };
+                let mut neighbors = atom
+                    .bonds
+                    .iter()
+                    .map(|bond| (bond.order(), classes[bond.tid + offset]))
+                    .collect::<Vec<_>>();
+
+                neighbors.sort_unstable();
+
+                let next = refined.len();
+
+                *refined.entry((classes[idx], neighbors)).or_insert(next)
+            })
+            .collect::<Vec<_>>();
+
+        classes = next_classes;
+
+        if refined.len() == count {
+            break;
+        }
+
+        count = refined.len();
+    }
+
+    let right_classes = classes.split_off(left.len());
+
+    (classes, right_classes)
+}
+
+fn counts_by_class(classes: &[usize]) -> HashMap<usize, usize> {
+    let mut counts = HashMap::new();
+
+    for &class in classes {
+        *counts.entry(class).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+/// Returns the atoms of `atoms` in breadth-first order, starting each
+/// component from an atom of its rarest class.
+fn search_order(atoms: &[Atom], classes: &[usize], counts: &HashMap<usize, usize>) -> Vec<usize> {
+    let mut starts = (0..atoms.len()).collect::<Vec<_>>();
+    let mut seen = vec![false; atoms.len()];
+    let mut order = Vec::with_capacity(atoms.len());
+    let mut queue = VecDeque::new();
+
+    starts.sort_by_key(|&id| counts[&classes[id]]);
+
+    for start in starts {
+        if seen[start] {
+            continue;
+        }
+
+        seen[start] = true;
+        queue.push_back(start);
+
+        while let Some(sid) = queue.pop_front() {
+            order.push(sid);
+
+            for bond in &atoms[sid].bonds {
+                if !seen[bond.tid] {
+                    seen[bond.tid] = true;
+                    queue.push_back(bond.tid);
+                }
+            }
+        }
+    }
+
+    order
+}
+
+struct Search<'a> {
+    left: &'a [Atom],
+    right: &'a [Atom],
+    left_classes: &'a [usize],
+    right_classes: &'a [usize],
+    order: Vec<usize>,
+    mapping: Vec<Option<usize>>,
+    used: Vec<bool>,
+    stereo: bool,
+}
+
+impl Search<'_> {
+    /// Maps atoms from `depth` in search order onward, returning true on
+    /// success.
+    fn extend(&mut self, depth: usize) -> bool {
+        let Some(&lid) = self.order.get(depth) else {
+            return !self.stereo || self.stereo_matches();
+        };
+        let parent = self.left[lid]
+            .bonds
+            .iter()
+            .find_map(|bond| self.mapping[bond.tid]);
+        let candidates = match parent {
+            Some(parent) => self.right[parent]
+                .bonds
+                .iter()
+                .map(|bond| bond.tid)
+                .collect(),
+            None => (0..self.right.len()).collect::<Vec<_>>(),
+        };
+
+        for rid in candidates {
+            if !self.feasible(lid, rid) {
+                continue;
+            }
+
+            self.mapping[lid] = Some(rid);
+            self.used[rid] = true;
+
+            if self.extend(depth + 1) {
+                return true;
+            }
+
+            self.mapping[lid] = None;
+            self.used[rid] = false;
+        }
+
+        false
+    }
+
+    /// Returns true if mapping `lid` to `rid` keeps every bond between
+    /// mapped atoms, and adds none.
+    fn feasible(&self, lid: usize, rid: usize) -> bool {
+        if self.used[rid] || self.left_classes[lid] != self.right_classes[rid] {
+            return false;
+        }
+
+        let mut mapped = 0;
+
+        for bond in &self.left[lid].bonds {
+            if let Some(target) = self.mapping[bond.tid] {
+                mapped += 1;
+
+                let order = self.right[rid]
+                    .bonds
+                    .iter()
+                    .find(|other| other.tid == target)
+                    .map(|other| other.order());
+
+                if order != Some(bond.order()) {
+                    return false;
+                }
+            }
+        }
+
+        self.right[rid]
+            .bonds
+            .iter()
+            .filter(|bond| self.used[bond.tid])
+            .count()
+            == mapped
+    }
+
+    fn stereo_matches(&self) -> bool {
+        let mapping = self
+            .mapping
+            .iter()
+            .map(|rid| rid.expect("complete mapping"))
+            .collect::<Vec<_>>();
+
+        for (lid, &rid) in mapping.iter().enumerate() {
+            if !configurations_match(&self.left[lid], &self.right[rid], &mapping) {
+                return false;
+            }
+        }
+
+        let mut count = 0;
+
+        for (sid, atom) in self.left.iter().enumerate() {
+            for bond in &atom.bonds {
+                if sid > bond.tid || bond.kind != BondKind::Double {
+                    continue;
+                }
+
+                let Some((x, y, cis)) = geometry(self.left, sid, bond.tid) else {
+                    continue;
+                };
+                let mapped = cis_trans(
+                    self.right,
+                    mapping[sid],
+                    mapping[bond.tid],
+                    mapping[x],
+                    mapping[y],
+                );
+
+                if mapped != Some(cis) {
+                    return false;
+                }
+
+                count += 1;
+            }
+        }
+
+        count == stereo_bond_count(self.right)
+    }
+}
+
+/// Returns true if the configurations of `left` and `right` agree, given
+/// the mapping of their neighbors.
+fn configurations_match(left: &Atom, right: &Atom, mapping: &[usize]) -> bool {
+    let configuration = |atom: &Atom| match atom.kind {
+        AtomKind::Bracket { configuration, .. } => configuration,
+        AtomKind::Symbol(_) => None,
+    };
+
+    match (configuration(left), configuration(right)) {
+        (
+            Some(left_configuration @ (Configuration::TH1 | Configuration::TH2)),
+            Some(right_configuration @ (Configuration::TH1 | Configuration::TH2)),
+        ) => {
+            let order = neighbors(right, |id| id);
+            let mapped = neighbors(left, |id| mapping[id]);
+            let positions = mapped
+                .iter()
+                .map(|id| order.iter().position(|other| other == id))
+                .collect::<Option<Vec<_>>>();
+
+            positions.is_some_and(|positions| {
+                (left_configuration == right_configuration) != is_odd(&positions)
+            })
+        }
+        (left, right) => left == right,
+    }
+}
+
+/// Returns the neighbors of `atom` through `map`, with a virtual hydrogen
+/// first as `usize::MAX`.
+fn neighbors(atom: &Atom, map: impl Fn(usize) -> usize) -> Vec<usize> {
+    let hydrogen = matches!(
+        atom.kind,
+        AtomKind::Bracket {
+            hcount: Some(hcount),
+            ..
+        } if !hcount.is_zero()
+    );
+
+    hydrogen
+        .then_some(usize::MAX)
+        .into_iter()
+        .chain(atom.bonds.iter().map(|bond| map(bond.tid)))
+        .collect()
+}
+
+/// Returns the side of neighbor `nid` of atom `sid`, away from `tid`:
+/// true for `Up`, either from the bond to it or opposite another
+/// neighbor's `Down`.
+fn side(atoms: &[Atom], sid: usize, tid: usize, nid: usize) -> Option<bool> {
+    let bonds = &atoms[sid].bonds;
+
+    if let Some(bond) = bonds
+        .iter()
+        .find(|bond| bond.tid == nid && bond.is_directional())
+    {
+        return Some(bond.kind == BondKind::Up);
+    }
+
+    bonds
+        .iter()
+        .find(|bond| bond.tid != nid && bond.tid != tid && bond.is_directional())
+        .map(|bond| bond.kind == BondKind::Down)
+}
+
+/// Returns true if `x` on `sid` and `y` on `tid` are on the same side of
+/// the double bond between `sid` and `tid`, if specified.
+pub(crate) fn cis_trans(
+    atoms: &[Atom],
+    sid: usize,
+    tid: usize,
+    x: usize,
+    y: usize,
+) -> Option<bool> {
+    Some(side(atoms, sid, tid, x)? == side(atoms, tid, sid, y)?)
+}
+
+/// Returns a neighbor of each end of the double bond between `sid` and
+/// `tid`, and whether they're cis, if the geometry is specified.
+pub(crate) fn geometry(atoms: &[Atom], sid: usize, tid: usize) -> Option<(usize, usize, bool)> {
+    let x = atoms[sid].bonds.iter().find(|bond| bond.tid != tid)?.tid;
+    let y = atoms[tid].bonds.iter().find(|bond| bond.tid != sid)?.tid;
+
+    Some((x, y, cis_trans(atoms, sid, tid, x, y)?))
+}
+
+fn stereo_bond_count(atoms: &[Atom]) -> usize {
+    atoms
+        .iter()
+        .enumerate()
+        .flat_map(|(sid, atom)| atom.bonds.iter().map(move |bond| (sid, bond)))
+        .filter(|(sid, bond)| {
+            *sid < bond.tid
+                && bond.kind == BondKind::Double
+                && geometry(atoms, *sid, bond.tid).is_some()
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::ValenceTable;
+    use crate::testing::atoms;
+    use crate::write::Enumerator;
+    use pretty_assertions::assert_eq;
+
+    fn check(left: &str, right: &str, stereo: bool) -> bool {
+        let left = atoms(left);
+        let right = atoms(right);
+        let mapping = isomorphism(&left, &right, stereo);
+
+        if let Some(mapping) = &mapping {
+            for (lid, atom) in left.iter().enumerate() {
+                assert_eq!(
+                    Label::new(atom, standard_valences()),
+                    Label::new(&right[mapping[lid]], standard_valences())
+                );
+
+                for bond in &atom.bonds {
+                    assert!(right[mapping[lid]].bonds.iter().any(|other| other.tid
+                        == mapping[bond.tid]
+                        && other.order() == bond.order()));
+                }
+            }
+        }
+
+        assert_eq!(
+            isomorphism(&right, &left, stereo).is_some(),
+            mapping.is_some()
+        );
+
+        mapping.is_some()
+    }
+
+    #[test]
+    fn atom_order() {
+        assert_eq!(
+            isomorphism(&atoms("CCO"), &atoms("OCC"), false),
+            Some(vec![2, 1, 0])
+        );
+        assert!(check("c1ccccc1O", "Oc1ccccc1", false));
+        assert!(check("C.O", "O.C", false))
+    }
+
+    #[test]
+    fn atom_properties() {
+        assert!(!check("CCO", "CCN", false));
+        assert!(!check("CC[O-]", "CCO", false));
+        assert!(!check("CC[18OH]", "CCO", false));
+        assert!(!check("C[CH]O", "CCO", false));
+        assert!(check("[CH3][CH2][OH]", "OCC", false));
+        assert!(check("[CH3:1]CO", "OCC", false));
+        assert!(!check("c1ccccc1", "C1=CC=CC=C1", false))
+    }
+
+    #[test]
+    fn bond_orders() {
+        assert!(!check("C=CC=O", "CC=CO", false));
+        assert!(check("C=CC=O", "O=CC=C", false))
+    }
+
+    #[test]
+    fn topology() {
+        // same classes at every refinement, different graphs
+        assert!(!check("C1CCCCC1", "C1CC1.C1CC1", false));
+        assert!(!check("C1CCC2CCCC2C1", "C1CCC(C1)C1CCCC1", false));
+        assert!(!check("CC(C)CC", "CCCCC", false))
+    }
+
+    #[test]
+    fn tetrahedral() {
+        assert!(check("F[C@H](Cl)Br", "Br[C@@H](Cl)F", true));
+        assert!(check("F[C@H](Cl)Br", "F[C@@H](Br)Cl", true));
+        assert!(!check("F[C@H](Cl)Br", "F[C@@H](Cl)Br", true));
+        assert!(check("F[C@H](Cl)Br", "F[C@@H](Cl)Br", false));
+        assert!(!check("F[C@H](Cl)Br", "FC(Cl)Br", true));
+        assert!(check("N[C@@H](C)C(=O)O", "OC(=O)[C@H](C)N", true));
+        assert!(check(
+            "C[C@@H]1CC[C@H](C)CC1",
+            "C[C@H]1CC[C@@H](C)CC1",
+            true
+        ))
+    }
+
+    #[test]
+    fn double_bonds() {
+        assert!(check("F/C=C/F", "F\\C=C\\F", true));
+        assert!(!check("F/C=C/F", "F/C=C\\F", true));
+        assert!(check("F/C=C/F", "F/C=C\\F", false));
+        assert!(!check("F/C=C/F", "FC=CF", true));
+        assert!(check("C/C(F)=C/F", "F/C=C(/C)F", true));
+        assert!(check("C/C(F)=C/F", "F\\C(C)=C/F", true))
+    }
+
+    #[test]
+    fn all_strings() {
+        let smiles = "C[C@@H]1C[C@H](O)/C=C/CC1";
+        let left = atoms(smiles);
+
+        for string in Enumerator::new(&left).take(200) {
+            let string = string.unwrap();
+
+            assert!(check(smiles, &string, true), "{string}")
+        }
+    }
+
+    #[test]
+    fn with_model() {
+        let mut table = ValenceTable::default();
+
+        table.insert(Element::P, 0, vec![5]);
+
+        assert!(is_isomorphic(&atoms("P"), &atoms("[PH3]"), false));
+        assert!(!is_isomorphic_with(
+            &atoms("P"),
+            &atoms("[PH3]"),
+            false,
+            &table
+        ));
+        assert!(is_isomorphic_with(
+            &atoms("P"),
+            &atoms("[PH5]"),
+            false,
+            &table
+        ))
+    }
+}
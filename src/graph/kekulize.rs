@@ -0,0 +1,419 @@
+use std::collections::VecDeque;
+
+use thiserror::Error;
+
+use super::Atom;
+use crate::feature::{standard_valences, AtomKind, BondKind, Symbol, ValenceModel};
+
+/// An error resulting from kekulization.
+#[derive(Debug, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KekulizeError {
+    #[error("An aromatic atom can't be given a double bond: {0}")]
+    Unmatched(usize),
+}
+
+/// Replaces aromatic atoms with aliphatic ones, and bonds between them with
+/// alternating single and double bonds, keeping implicit hydrogen counts.
+///
+/// Every aromatic atom with a free valence must take part in exactly one
+/// double bond. If no such assignment exists, returns an error
+/// with an atom that couldn't be given a double bond, and leaves `atoms`
+/// unchanged.
+///
+/// ```
+/// use yowl::graph::{kekulize, Builder};
+/// use yowl::read::read;
+/// use yowl::walk::walk;
+/// use yowl::write::Writer;
+///
+/// let mut builder = Builder::default();
+///
+/// read("c1ccncc1O", &mut builder, None).expect("read");
+///
+/// let mut atoms = builder.build().expect("atoms");
+/// let mut writer = Writer::default();
+///
+/// kekulize(&mut atoms).expect("kekulize");
+/// walk(atoms, &mut writer).expect("walk");
+///
+/// assert_eq!(writer.write(), "C(=C(C=NC=C1)O)1")
+/// ```
+pub fn kekulize(atoms: &mut [Atom]) -> Result<(), KekulizeError> {
+    kekulize_with(atoms, standard_valences())
+}
+
+/// Performs `kekulize`, finding the aromatic atoms with a free valence by
+/// the targets of `model`.
+pub fn kekulize_with<M: ValenceModel + ?Sized>(
+    atoms: &mut [Atom],
+    model: &M,
+) -> Result<(), KekulizeError> {
+    let pi = atoms
+        .iter()
+        .map(|atom| atom.is_aromatic() && atom.subvalence_with(model) > 0)
+        .collect::<Vec<_>>();
+    let neighbors = atoms
+        .iter()
+        .enumerate()
+        .map(|(sid, atom)| {
+            if !pi[sid] {
+                return Vec::new();
+            }
+
+            atom.bonds
+                .iter()
+                .filter(|bond| {
+                    bond.tid < atoms.len()
+                        && pi[bond.tid]
+                        && matches!(bond.kind, BondKind::Elided | BondKind::Aromatic)
+                })
+                .map(|bond| bond.tid)
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    let mates = maximum_matching(&neighbors);
+
+    if let Some(id) = (0..atoms.len()).find(|&id| pi[id] && mates[id].is_none()) {
+        return Err(KekulizeError::Unmatched(id));
+    }
+
+    for (sid, atom) in atoms.iter_mut().enumerate() {
+        if !atom.is_aromatic() {
+            continue;
+        }
+
+        dearomatize(&mut atom.kind);
+
+        for bond in &mut atom.bonds {
+            if mates[sid] == Some(bond.tid) {
+                bond.kind = BondKind::Double;
+            } else if bond.kind == BondKind::Aromatic {
+                bond.kind = BondKind::Elided;
+            }
+        }
+    }
+
+    // bonds from aliphatic atoms to aromatic ones may also be marked aromatic
+    for atom in atoms.iter_mut() {
+        for bond in &mut atom.bonds {
+            if bond.kind == BondKind::Aromatic {
+                bond.kind = BondKind::Elided;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn dearomatize(kind: &mut AtomKind) {
+    let symbol = match kind {
+        AtomKind::Symbol(symbol) | AtomKind::Bracket { symbol, .. } => symbol,
+    };
+
+    if let Symbol::Aromatic(element) = *symbol {
+        *symbol = Symbol::Aliphatic(element);
+    }
+}
+
+/// Returns the mate of each vertex in a maximum matching of the general
+/// graph given by `neighbors`, using Edmonds' blossom algorithm.
+fn maximum_matching(neighbors: &[Vec<usize>]) -> Vec<Option<usize>> {
+    let size = neighbors.len();
+    let mut mates = vec![None; size];
+
+    // a greedy start leaves few vertices for the blossom search
+    for sid in 0..size {
+        if mates[sid].is_none() {
+            if let Some(&tid) = neighbors[sid].iter().find(|&&tid| mates[tid].is_none()) {
+                mates[sid] = Some(tid);
+                mates[tid] = Some(sid);
+            }
+        }
+    }
+
+    let mut search = Search::new(size);
+
+    for root in 0..size {
+        if mates[root].is_none() && !neighbors[root].is_empty() {
+            search.augment(root, neighbors, &mut mates);
+        }
+    }
+
+    mates
+}
+
+/// Working state of one augmenting path search.
+struct Search {
+    parents: Vec<Option<usize>>,
+    bases: Vec<usize>,
+    used: Vec<bool>,
+    blossom: Vec<bool>,
+    queue: VecDeque<usize>,
+}
+
+impl Search {
+    fn new(size: usize) -> Self {
+        Self {
+            parents: vec![None; size],
+            bases: (0..size).collect(),
+            used: vec![false; size],
+            blossom: vec![false; size],
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Extends the matching along an augmenting path from `root`, if any.
+    fn augment(&mut self, root: usize, neighbors: &[Vec<usize>], mates: &mut [Option<usize>]) {
+        self.parents.fill(None);
+        self.used.fill(false);
+        self.queue.clear();
+
+        for (idx, base) in self.bases.iter_mut().enumerate() {
+            *base = idx;
+        }
+
+        self.used[root] = true;
+        self.queue.push_back(root);
+
+        while let Some(sid) = self.queue.pop_front() {
+            for &tid in &neighbors[sid] {
+                if self.bases[sid] == self.bases[tid] || mates[sid] == Some(tid) {
+                    continue;
+                }
+
+                if tid == root || mates[tid].is_some_and(|mate| self.parents[mate].is_some()) {
+                    self.contract(sid, tid, mates);
+                } else if self.parents[tid].is_none() {
+                    self.parents[tid] = Some(sid);
+
+                    match mates[tid] {
+                        Some(mate) => {
+                            self.used[mate] = true;
+                            self.queue.push_back(mate);
+                        }
+                        None => {
+                            self.flip(tid, mates);
+
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn contract(&mut self, sid: usize, tid: usize, mates: &[Option<usize>]) {
+        let base = self.common_base(sid, tid, mates);
+
+        self.blossom.fill(false);
+        self.mark_path(sid, base, tid, mates);
+        self.mark_path(tid, base, sid, mates);
+
+        for id in 0..self.bases.len() {
+            if self.blossom[self.bases[id]] {
+                self.bases[id] = base;
+
+                if !self.used[id] {
+                    self.used[id] = true;
+                    self.queue.push_back(id);
+                }
+            }
+        }
+    }
+
+    fn common_base(&self, mut left: usize, mut right: usize, mates: &[Option<usize>]) -> usize {
+        let mut seen = vec![false; self.bases.len()];
+
+        loop {
+            left = self.bases[left];
+            seen[left] = true;
+
+            match mates[left] {
+                Some(mate) => left = self.parents[mate].expect("parent"),
+                None => break,
+            }
+        }
+
+        loop {
+            right = self.bases[right];
+
+            if seen[right] {
+                return right;
+            }
+
+            right = self.parents[mates[right].expect("mate")].expect("parent");
+        }
+    }
+
+    fn mark_path(&mut self, mut id: usize, base: usize, mut child: usize, mates: &[Option<usize>]) {
+        while self.bases[id] != base {
+            let mate = mates[id].expect("mate");
+
+            self.blossom[self.bases[id]] = true;
+            self.blossom[self.bases[mate]] = true;
+            self.parents[id] = Some(child);
+            child = mate;
+            id = self.parents[mate].expect("parent");
+        }
+    }
+
+    fn flip(&self, mut id: usize, mates: &mut [Option<usize>]) {
+        loop {
+            let parent = self.parents[id].expect("parent");
+            let next = mates[parent];
+
+            mates[id] = Some(parent);
+            mates[parent] = Some(id);
+
+            match next {
+                Some(next) => id = next,
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::ValenceTable;
+    use crate::graph::Builder;
+    use crate::read::read;
+    use crate::walk::walk;
+    use crate::write::Writer;
+    use crate::Element;
+    use pretty_assertions::assert_eq;
+
+    fn kekule(smiles: &str) -> Result<String, KekulizeError> {
+        let mut builder = Builder::default();
+
+        read(smiles, &mut builder, None).unwrap();
+
+        let mut atoms = builder.build().unwrap();
+        let hydrogens = atoms
+            .iter()
+            .map(Atom::suppressed_hydrogens)
+            .collect::<Vec<_>>();
+
+        kekulize(&mut atoms)?;
+
+        assert!(atoms.iter().all(|atom| !atom.is_aromatic()));
+        assert_eq!(
+            atoms
+                .iter()
+                .map(Atom::suppressed_hydrogens)
+                .collect::<Vec<_>>(),
+            hydrogens,
+            "{smiles}"
+        );
+
+        let mut writer = Writer::default();
+
+        walk(atoms, &mut writer).unwrap();
+
+        Ok(writer.write())
+    }
+
+    #[test]
+    fn benzene() {
+        assert_eq!(kekule("c1ccccc1"), Ok("C(=CC=CC=C1)1".to_string()))
+    }
+
+    #[test]
+    fn heteroaromatics() {
+        assert!(kekule("c1cc[nH]c1").is_ok());
+        assert!(kekule("c1ccoc1").is_ok());
+        assert!(kekule("Cn1cccc1").is_ok());
+        assert!(kekule("c1ccncc1").is_ok());
+        assert!(kekule("O=c1cc[nH]cc1").is_ok())
+    }
+
+    #[test]
+    fn fused_and_odd_rings() {
+        // naphthalene, azulene and a biphenyl
+        assert!(kekule("c1ccc2ccccc2c1").is_ok());
+        assert!(kekule("c1cc2cccccc2c1").is_ok());
+        assert!(kekule("c1ccccc1-c1ccccc1").is_ok());
+        assert!(kekule("c1ccc2c(c1)c1ccccc1c1ccccc21").is_ok())
+    }
+
+    #[test]
+    fn unchanged_aliphatic() {
+        assert_eq!(kekule("CC(=O)O"), Ok("CC(=O)O".to_string()))
+    }
+
+    #[test]
+    fn impossible() {
+        assert_eq!(kekule("c1cccc1"), Err(KekulizeError::Unmatched(3)))
+    }
+
+    #[test]
+    fn with_model() {
+        let mut builder = Builder::default();
+        let mut table = ValenceTable::empty();
+
+        read("c1cccc1", &mut builder, None).unwrap();
+        table.insert(Element::C, 0, vec![2, 4]);
+
+        let mut atoms = builder.build().unwrap();
+
+        assert_eq!(kekulize_with(&mut atoms, &table), Ok(()));
+        assert!(atoms
+            .iter()
+            .flat_map(|atom| &atom.bonds)
+            .all(|bond| bond.kind == BondKind::Elided))
+    }
+
+    fn brute_force(edges: &[(usize, usize)], used: u32) -> usize {
+        match edges.split_first() {
+            None => 0,
+            Some((&(sid, tid), rest)) => {
+                let without = brute_force(rest, used);
+
+                if used & (1 << sid | 1 << tid) == 0 {
+                    without.max(1 + brute_force(rest, used | 1 << sid | 1 << tid))
+                } else {
+                    without
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn maximum_on_all_small_graphs() {
+        let pairs = (0..6)
+            .flat_map(|sid| (sid + 1..6).map(move |tid| (sid, tid)))
+            .collect::<Vec<_>>();
+
+        for mask in 0..1u32 << pairs.len() {
+            let edges = pairs
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| mask & 1 << idx != 0)
+                .map(|(_, &pair)| pair)
+                .collect::<Vec<_>>();
+            let mut neighbors = vec![Vec::new(); 6];
+
+            for &(sid, tid) in &edges {
+                neighbors[sid].push(tid);
+                neighbors[tid].push(sid);
+            }
+
+            let mates = maximum_matching(&neighbors);
+
+            for (id, mate) in mates.iter().enumerate() {
+                if let Some(mate) = mate {
+                    assert_eq!(mates[*mate], Some(id));
+                    assert!(neighbors[id].contains(mate));
+                }
+            }
+
+            assert_eq!(
+                mates.iter().filter(|mate| mate.is_some()).count() / 2,
+                brute_force(&edges, 0),
+                "{edges:?}"
+            )
+        }
+    }
+}
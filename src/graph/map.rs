@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use super::Atom;
+use crate::feature::AtomKind;
+
+/// Clears every atom map. Atoms that needed brackets only for their map
+/// are rewritten as organic subset symbols.
+///
+/// ```
+/// use yowl::graph::{strip_maps, Builder};
+/// use yowl::read::read;
+/// use yowl::walk::walk;
+/// use yowl::write::Writer;
+///
+/// let mut builder = Builder::default();
+///
+/// read("[CH3:1][CH2:2][OH:3]", &mut builder, None).expect("read");
+///
+/// let mut atoms = builder.build().expect("atoms");
+/// let mut writer = Writer::default();
+///
+/// strip_maps(&mut atoms);
+/// walk(atoms, &mut writer).expect("walk");
+///
+/// assert_eq!(writer.write(), "CCO")
+/// ```
+pub fn strip_maps(atoms: &mut [Atom]) {
+    for atom in atoms {
+        let AtomKind::Bracket {
+            isotope,
+            symbol,
+            configuration,
+            hcount,
+            charge,
+            map,
+        } = &mut atom.kind
+        else {
+            continue;
+        };
+
+        *map = None;
+
+        if isotope.is_some()
+            || configuration.is_some()
+            || charge.is_some_and(|charge| charge.value() != 0)
+            || !symbol.is_organic()
+        {
+            continue;
+        }
+
+        let hcount = hcount.as_ref().map_or(0, u8::from);
+        let organic = Atom {
+            kind: AtomKind::Symbol(*symbol),
+            bonds: std::mem::take(&mut atom.bonds),
+        };
+
+        if organic.suppressed_hydrogens() == hcount {
+            *atom = organic;
+        } else {
+            atom.bonds = organic.bonds;
+        }
+    }
+}
+
+/// Renumbers atom maps sequentially from 1, in order of atom index.
+/// Atoms sharing a map number keep sharing it. Unmapped atoms and atoms
+/// with map 0 are left unchanged.
+pub fn renumber_maps(atoms: &mut [Atom]) {
+    renumber(atoms, &mut HashMap::new());
+}
+
+/// Renumbers maps using and extending the shared `numbers` assignment.
+pub(crate) fn renumber(atoms: &mut [Atom], numbers: &mut HashMap<u16, u16>) {
+    for atom in atoms {
+        if let AtomKind::Bracket {
+            map: Some(map @ 1..),
+            ..
+        } = &mut atom.kind
+        {
+            let next = u16::try_from(numbers.len() + 1).expect("map number");
+
+            *map = *numbers.entry(*map).or_insert(next);
+        }
+    }
+}
+
+/// Returns the atom map number of `atom`, ignoring map 0.
+pub(crate) const fn map_number(atom: &Atom) -> Option<u16> {
+    match atom.kind {
+        AtomKind::Bracket {
+            map: Some(map @ 1..),
+            ..
+        } => Some(map),
+        _ => None,
+    }
+}
+
+/// Returns pairs of indices into `left` and `right` for atoms sharing a
+/// map number, in order of `left` index. Map 0 counts as unmapped. When a
+/// map number repeats within a graph, its first atom is used.
+///
+/// ```
+/// use yowl::graph::{correspondence, Builder};
+/// use yowl::read::read;
+///
+/// let mut left = Builder::default();
+/// let mut right = Builder::default();
+///
+/// read("[CH3:1][OH:2]", &mut left, None).expect("left");
+/// read("[OH2:2].[CH4:1]", &mut right, None).expect("right");
+///
+/// let left = left.build().expect("left atoms");
+/// let right = right.build().expect("right atoms");
+///
+/// assert_eq!(correspondence(&left, &right), vec![(0, 1), (1, 0)])
+/// ```
+pub fn correspondence(left: &[Atom], right: &[Atom]) -> Vec<(usize, usize)> {
+    let mut targets = HashMap::new();
+
+    for (idx, atom) in right.iter().enumerate() {
+        if let Some(map) = map_number(atom) {
+            targets.entry(map).or_insert(idx);
+        }
+    }
+
+    let mut seen = HashMap::new();
+
+    left.iter()
+        .enumerate()
+        .filter_map(|(idx, atom)| {
+            let map = map_number(atom)?;
+
+            if seen.insert(map, idx).is_some() {
+                return None;
+            }
+
+            targets.get(&map).map(|&tid| (idx, tid))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::atoms;
+    use crate::walk::walk;
+    use crate::write::Writer;
+    use pretty_assertions::assert_eq;
+
+    fn write(atoms: Vec<Atom>) -> String {
+        let mut writer = Writer::default();
+
+        walk(atoms, &mut writer).unwrap();
+
+        writer.write()
+    }
+
+    fn stripped(smiles: &str) -> String {
+        let mut atoms = atoms(smiles);
+
+        strip_maps(&mut atoms);
+
+        write(atoms)
+    }
+
+    #[test]
+    fn strip_keeps_needed_brackets() {
+        assert_eq!(
+            stripped("[13CH3:1][NH3+:2].[C@@H:3](F)(Cl)Br.[Na:4]"),
+            "[13CH3][NH3+].[C@@H](F)(Cl)Br.[Na]"
+        )
+    }
+
+    #[test]
+    fn strip_keeps_unusual_hydrogens() {
+        assert_eq!(stripped("[CH2:1]=[CH:2]"), "C=[CH]")
+    }
+
+    #[test]
+    fn strip_aromatic() {
+        assert_eq!(stripped("[cH:1]1[cH:2][cH:3][nH:4][cH:5]1"), "c(c[nH]cc1)1")
+    }
+
+    #[test]
+    fn strip_star() {
+        assert_eq!(stripped("[*:1][CH3:2]"), "*C")
+    }
+
+    #[test]
+    fn renumber_in_order() {
+        let mut atoms = atoms("[CH3:7][CH2:3][OH:7].[Na:0]");
+
+        renumber_maps(&mut atoms);
+
+        assert_eq!(write(atoms), "[CH3:1][CH2:2][OH:1].[Na:0]")
+    }
+
+    #[test]
+    fn correspondence_skips_unmapped_and_repeats() {
+        let left = atoms("[CH3:1][CH2:1]C[OH:0][NH2:2]");
+        let right = atoms("[NH3:2].[CH4:1].[CH4:1]");
+
+        assert_eq!(correspondence(&left, &right), vec![(0, 1), (4, 0)])
+    }
+}
@@ -1,13 +1,28 @@
 mod atom;
+#[cfg(feature = "serde")]
+pub mod atoms;
 mod bond;
 mod builder;
+mod editor;
 mod error;
+mod isomorphism;
 mod join_pool;
+mod kekulize;
+mod map;
 mod reconcile;
+mod sound_graph;
 
+pub(crate) use atom::bracket;
 pub use atom::Atom;
 pub use bond::Bond;
 pub use builder::Builder;
+pub use editor::{EditError, Editor};
 pub use error::Error;
+pub(crate) use isomorphism::{cis_trans, classes, geometry};
+pub use isomorphism::{is_isomorphic, is_isomorphic_with, isomorphism, isomorphism_with};
 pub(crate) use join_pool::JoinPool;
+pub use kekulize::{kekulize, kekulize_with, KekulizeError};
+pub use map::{correspondence, renumber_maps, strip_maps};
+pub(crate) use map::{map_number, renumber};
 pub(crate) use reconcile::reconcile;
+pub use sound_graph::SoundGraph;
@@ -1,13 +1,15 @@
 use crate::feature::BondKind;
 
 pub fn reconcile(left: BondKind, right: BondKind) -> Option<(BondKind, BondKind)> {
-    use BondKind::{Down, Elided, Up};
+    use BondKind::{DativeLeft, DativeRight, Down, Elided, Up};
     match (left, right) {
-        (Up, Up) | (Down, Down) => None,
-        (Up, Down) | (Down, Up) => Some((left, right)),
+        (Up, Up) | (Down, Down) | (DativeRight, DativeRight) | (DativeLeft, DativeLeft) => None,
+        (Up, Down) | (Down, Up) | (DativeRight, DativeLeft) | (DativeLeft, DativeRight) => {
+            Some((left, right))
+        }
         (Elided, Elided) => Some((Elided, Elided)),
-        (Elided, Up) | (Down, Elided) => Some((Down, Up)),
-        (Elided, Down) | (Up, Elided) => Some((Up, Down)),
+        (Elided, Up | Down | DativeRight | DativeLeft) => Some((right.reverse(), right)),
+        (Up | Down | DativeRight | DativeLeft, Elided) => Some((left, left.reverse())),
         (other, Elided) | (Elided, other) => Some((other, other)),
         (a, b) if a == b => Some((a, b)),
         _ => None,
@@ -113,4 +115,37 @@ mod tests {
             Some((BondKind::Triple, BondKind::Triple))
         )
     }
+
+    #[test]
+    fn dative_elided() {
+        assert_eq!(
+            reconcile(BondKind::DativeRight, BondKind::Elided),
+            Some((BondKind::DativeRight, BondKind::DativeLeft))
+        )
+    }
+
+    #[test]
+    fn elided_dative() {
+        assert_eq!(
+            reconcile(BondKind::Elided, BondKind::DativeRight),
+            Some((BondKind::DativeLeft, BondKind::DativeRight))
+        )
+    }
+
+    #[test]
+    fn dative_dative() {
+        assert_eq!(
+            reconcile(BondKind::DativeRight, BondKind::DativeLeft),
+            Some((BondKind::DativeRight, BondKind::DativeLeft))
+        );
+        assert_eq!(reconcile(BondKind::DativeLeft, BondKind::DativeLeft), None)
+    }
+
+    #[test]
+    fn zero_elided() {
+        assert_eq!(
+            reconcile(BondKind::Zero, BondKind::Elided),
+            Some((BondKind::Zero, BondKind::Zero))
+        )
+    }
 }
@@ -0,0 +1,315 @@
+use std::ops::Deref;
+
+use super::{Atom, Bond};
+use crate::feature::AtomKind;
+use crate::walk::{walk, Error, Follower, Graph};
+
+/// A graph checked to be walkable: every bond targets another atom, at
+/// most once, and is matched by a bond back of the reverse kind.
+///
+/// It derefs to `[Atom]`, so it can be given to the functions that take
+/// `&[Atom]` and follow bonds by index, such as `is_isomorphic`,
+/// `cip::centers`, `Stereoisomers::new`, `library::join` and
+/// `Enumerator::new`.
+///
+/// With the `serde` feature, serializes as a sequence of atoms, and is
+/// checked when deserialized.
+///
+/// ```
+/// use yowl::graph::{Atom, Bond, Builder, SoundGraph};
+/// use yowl::feature::{AtomKind, BondKind, Symbol};
+/// use yowl::read::read;
+/// use yowl::walk::Error;
+/// use yowl::write::Writer;
+///
+/// let mut builder = Builder::default();
+///
+/// read("C1CC1O", &mut builder, None).expect("read");
+///
+/// let graph = SoundGraph::new(builder.build().expect("atoms")).expect("graph");
+/// let mut writer = Writer::default();
+///
+/// graph.walk(&mut writer);
+///
+/// assert_eq!(writer.write(), "C(C(C1)O)1");
+///
+/// let half = vec![
+///     Atom {
+///         kind: AtomKind::Symbol(Symbol::Star),
+///         bonds: vec![Bond::new(BondKind::Elided, 1)],
+///     },
+///     Atom::new(AtomKind::Symbol(Symbol::Star)),
+/// ];
+///
+/// assert_eq!(SoundGraph::new(half), Err(Error::HalfBond(0, 1)));
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<Atom>", into = "Vec<Atom>")
+)]
+pub struct SoundGraph {
+    atoms: Vec<Atom>,
+}
+
+impl SoundGraph {
+    /// Constructs a SoundGraph, returning the first problem found in atom
+    /// and bond order, if any.
+    pub fn new(atoms: Vec<Atom>) -> Result<Self, Error> {
+        check(&atoms)?;
+
+        Ok(Self { atoms })
+    }
+
+    /// Returns the atoms.
+    pub fn atoms(&self) -> &[Atom] {
+        &self.atoms
+    }
+
+    /// Returns the atoms, consuming this SoundGraph.
+    pub fn into_atoms(self) -> Vec<Atom> {
+        self.atoms
+    }
+
+    /// Performs `walk`, which can't fail on a checked graph.
+    pub fn walk<F: Follower>(&self, follower: &mut F) {
+        walk(self, follower).expect("checked graph")
+    }
+}
+
+impl Deref for SoundGraph {
+    type Target = [Atom];
+
+    fn deref(&self) -> &Self::Target {
+        &self.atoms
+    }
+}
+
+impl AsRef<[Atom]> for SoundGraph {
+    fn as_ref(&self) -> &[Atom] {
+        &self.atoms
+    }
+}
+
+impl TryFrom<Vec<Atom>> for SoundGraph {
+    type Error = Error;
+
+    fn try_from(atoms: Vec<Atom>) -> Result<Self, Self::Error> {
+        Self::new(atoms)
+    }
+}
+
+impl From<SoundGraph> for Vec<Atom> {
+    fn from(graph: SoundGraph) -> Self {
+        graph.atoms
+    }
+}
+
+impl Graph for SoundGraph {
+    fn atom_count(&self) -> usize {
+        self.atoms.len()
+    }
+
+    fn kind(&self, id: usize) -> AtomKind {
+        self.atoms[id].kind
+    }
+
+    fn bonds(&self, id: usize) -> &[Bond] {
+        &self.atoms[id].bonds
+    }
+}
+
+/// Checks that every bond targets another atom at most once, and is
+/// matched by a bond back of the reverse kind.
+pub(crate) fn check(atoms: &[Atom]) -> Result<(), Error> {
+    for (sid, atom) in atoms.iter().enumerate() {
+        for (idx, bond) in atom.bonds.iter().enumerate() {
+            let tid = bond.tid;
+            let target = atoms.get(tid).ok_or(Error::UnknownTarget(sid, tid))?;
+
+            if tid == sid {
+                return Err(Error::Loop(sid));
+            }
+
+            if atom.bonds[..idx].iter().any(|other| other.tid == tid) {
+                return Err(Error::DuplicateBond(sid, tid));
+            }
+
+            let back = target
+                .bonds
+                .iter()
+                .find(|back| back.tid == sid)
+                .ok_or(Error::HalfBond(sid, tid))?;
+
+            if back.kind != bond.kind.reverse() {
+                return Err(Error::IncompatibleBond(sid, tid));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cip::centers;
+    use crate::feature::{BondKind, Symbol};
+    use crate::graph::{is_isomorphic, Builder};
+    use crate::library::{self, join};
+    use crate::read::read;
+    use crate::write::Writer;
+    use pretty_assertions::assert_eq;
+
+    fn star(bonds: Vec<Bond>) -> Atom {
+        Atom {
+            kind: AtomKind::Symbol(Symbol::Star),
+            bonds,
+        }
+    }
+
+    #[test]
+    fn read_graphs() {
+        for smiles in ["C", "C.O", "C1CC1O", "F/C=C/F", "C/1=C/CCCCCC\\1"] {
+            let mut builder = Builder::default();
+
+            read(smiles, &mut builder, None).unwrap();
+
+            assert!(
+                SoundGraph::new(builder.build().unwrap()).is_ok(),
+                "{smiles}"
+            )
+        }
+    }
+
+    #[test]
+    fn directional() {
+        let atoms = vec![
+            star(vec![Bond::new(BondKind::Up, 1)]),
+            star(vec![Bond::new(BondKind::Down, 0)]),
+        ];
+        let graph = SoundGraph::try_from(atoms).unwrap();
+        let mut writer = Writer::default();
+
+        graph.walk(&mut writer);
+
+        assert_eq!(writer.write(), "*/*")
+    }
+
+    #[test]
+    fn unknown_target() {
+        assert_eq!(
+            SoundGraph::new(vec![star(vec![Bond::new(BondKind::Single, 4)])]),
+            Err(Error::UnknownTarget(0, 4))
+        )
+    }
+
+    #[test]
+    fn loop_bond() {
+        assert_eq!(
+            SoundGraph::new(vec![star(vec![Bond::new(BondKind::Single, 0)])]),
+            Err(Error::Loop(0))
+        )
+    }
+
+    #[test]
+    fn duplicate_bond() {
+        let atoms = vec![
+            star(vec![
+                Bond::new(BondKind::Single, 1),
+                Bond::new(BondKind::Double, 1),
+            ]),
+            star(vec![
+                Bond::new(BondKind::Single, 0),
+                Bond::new(BondKind::Double, 0),
+            ]),
+        ];
+
+        assert_eq!(SoundGraph::new(atoms), Err(Error::DuplicateBond(0, 1)))
+    }
+
+    #[test]
+    fn half_bond() {
+        // a half bond closing a ring isn't found by walk
+        let atoms = vec![
+            star(vec![
+                Bond::new(BondKind::Single, 1),
+                Bond::new(BondKind::Single, 2),
+            ]),
+            star(vec![
+                Bond::new(BondKind::Single, 0),
+                Bond::new(BondKind::Single, 2),
+            ]),
+            star(vec![Bond::new(BondKind::Single, 1)]),
+        ];
+
+        assert_eq!(SoundGraph::new(atoms), Err(Error::HalfBond(0, 2)))
+    }
+
+    #[test]
+    fn incompatible_bond() {
+        let atoms = vec![
+            star(vec![Bond::new(BondKind::Up, 1)]),
+            star(vec![Bond::new(BondKind::Up, 0)]),
+        ];
+
+        assert_eq!(SoundGraph::new(atoms), Err(Error::IncompatibleBond(0, 1)))
+    }
+
+    #[test]
+    fn as_atoms() {
+        let mut builder = Builder::default();
+
+        read("[*:1]C[C@H](F)Cl", &mut builder, None).unwrap();
+
+        let graph = SoundGraph::new(builder.build().unwrap()).unwrap();
+        let substituent = SoundGraph::new(vec![star(vec![])]).unwrap();
+
+        assert!(is_isomorphic(&graph, &graph, true));
+        assert_eq!(centers(&graph).len(), 1);
+        assert_eq!(
+            join(&graph, &substituent, 1),
+            Err(library::Error::Substituent(1))
+        )
+    }
+
+    #[test]
+    fn into_atoms() {
+        let atoms = vec![star(vec![])];
+
+        assert_eq!(Vec::from(SoundGraph::new(atoms.clone()).unwrap()), atoms)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serialization {
+    use super::*;
+    use crate::feature::{BondKind, Symbol};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn roundtrip() {
+        let graph = SoundGraph::new(vec![
+            Atom {
+                kind: AtomKind::Symbol(Symbol::Star),
+                bonds: vec![Bond::new(BondKind::Up, 1)],
+            },
+            Atom {
+                kind: AtomKind::Symbol(Symbol::Star),
+                bonds: vec![Bond::new(BondKind::Down, 0)],
+            },
+        ])
+        .unwrap();
+        let json = serde_json::to_string(&graph).unwrap();
+
+        assert_eq!(serde_json::from_str::<SoundGraph>(&json).unwrap(), graph)
+    }
+
+    #[test]
+    fn checked() {
+        let json = r#"[{"kind":{"Symbol":"*"},"bonds":[{"kind":"Single","tid":0}]}]"#;
+
+        assert!(serde_json::from_str::<SoundGraph>(json).is_err())
+    }
+}
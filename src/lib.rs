@@ -6,17 +6,41 @@
 //! - [Let's Build a SMILES Parser in Rust](https://depth-first.com/articles/2020/12/14/an-abstract-syntatx-tree-for-smiles/)
 //! - [Abstract Syntax Trees for SMILES](https://depth-first.com/articles/2020/12/21/smiles-formal-grammar-revisited/)
 
+/// Parallel reading and writing of many SMILES strings.
+#[cfg(feature = "rayon")]
+pub mod batch;
+/// Reading BigSMILES polymer notation.
+pub mod bigsmiles;
+/// CIP stereodescriptors.
+pub mod cip;
+/// Compact adjacency representation in compressed sparse rows.
+pub mod compact;
+/// Reading and writing CXSMILES extension blocks.
+pub mod cxsmiles;
 /// Common components used in `graph` and `tree` representations.
 pub mod feature;
 /// SMILES adjacency list representation.
 pub mod graph;
+/// Combinatorial library enumeration from R-group cores.
+pub mod library;
+/// Reading and writing reaction SMILES.
+pub mod reaction;
 /// Reading SMILES representations from strings.
 pub mod read;
+/// Reading and writing SD files.
+pub mod sdf;
+/// Reading SMILES files line by line.
+pub mod smi;
+/// Stereoisomers and stereo perception.
+pub mod stereo;
 /// Traversal of an adjacency representation.
 pub mod walk;
 /// Writing SMILES string representations.
 pub mod write;
 
+#[cfg(test)]
+mod testing;
+
 pub use mendeleev::Element;
 pub use mendeleev::Isotope;
 
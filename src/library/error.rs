@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+use crate::walk;
+
+/// An error resulting from joining fragments at attachment points.
+#[derive(Debug, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Error {
+    #[error("An attachment point doesn't have exactly one bond: {0}")]
+    Attachment(usize),
+    #[error("The core has no attachment point {0}")]
+    UnknownMap(u16),
+    #[error("The attachment point {0} was added already")]
+    DuplicateMap(u16),
+    #[error("A substituent doesn't have exactly one attachment point {0}")]
+    Substituent(u16),
+    #[error("The bonds at attachment point {0} are incompatible")]
+    Join(u16),
+    #[error("A product can't be written: {0}")]
+    Walk(walk::Error),
+}
@@ -0,0 +1,276 @@
+use super::Error;
+use crate::feature::{AtomKind, BondKind, Symbol, VirtualHydrogen};
+use crate::graph::{map_number, reconcile, Atom, Bond, Editor};
+
+/// Joins a copy of `substituent` to `core` at each star atom of `core`
+/// with map `map`, through the star atom of `substituent` with that map.
+/// Star atoms at the join are removed, and the atoms they were bonded to
+/// are bonded in their place.
+///
+/// The two bonds to the star atoms combine as the two halves of a ring
+/// closure, so `[*:1]=C` joins `C=[*:1]` by a double bond, and an `Up` or
+/// `Down` bond on either side is kept. A bond left elided between aromatic
+/// atoms is made `Single`. The neighbor order of both atoms is kept, and
+/// with it any configuration, and hydrogen counts of bracket atoms change
+/// with the order of the new bond.
+///
+/// ```
+/// use yowl::graph::Builder;
+/// use yowl::library::join;
+/// use yowl::read::read;
+/// use yowl::walk::walk;
+/// use yowl::write::Writer;
+///
+/// let mut core = Builder::default();
+/// let mut substituent = Builder::default();
+///
+/// read("[*:1]c1ccccn1", &mut core, None).expect("core");
+/// read("O[C@H]([*:1])F", &mut substituent, None).expect("substituent");
+///
+/// let atoms = join(
+///     &core.build().expect("core"),
+///     &substituent.build().expect("substituent"),
+///     1,
+/// )
+/// .expect("join");
+/// let mut writer = Writer::default();
+///
+/// walk(atoms, &mut writer).expect("walk");
+///
+/// assert_eq!(writer.write(), "c([C@@H](O)F)(ncccc1)1");
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if `core` has no star atom with map `map`,
+/// `substituent` doesn't have exactly one, such a star atom doesn't have
+/// exactly one bond, or the bonds to be joined are incompatible.
+pub fn join(core: &[Atom], substituent: &[Atom], map: u16) -> Result<Vec<Atom>, Error> {
+    let stars = attachments(core, map)?;
+    let star = match attachments(substituent, map)?[..] {
+        [star] => star,
+        _ => return Err(Error::Substituent(map)),
+    };
+
+    if stars.is_empty() {
+        return Err(Error::UnknownMap(map));
+    }
+
+    let mut editor = Editor::new(core.to_vec());
+    let offsets = stars
+        .iter()
+        .map(|_| editor.merge(substituent))
+        .collect::<Vec<_>>();
+    let mut atoms = editor.into_atoms();
+    let mut removed = stars.clone();
+
+    for (&sid, offset) in stars.iter().zip(offsets) {
+        connect(&mut atoms, sid, offset + star).ok_or(Error::Join(map))?;
+        removed.push(offset + star);
+    }
+
+    let mut editor = Editor::new(atoms);
+
+    editor.remove_atoms(&removed).expect("attachment points");
+
+    Ok(editor.into_atoms())
+}
+
+/// Returns the star atoms of `atoms` with map `map`.
+pub(super) fn attachments(atoms: &[Atom], map: u16) -> Result<Vec<usize>, Error> {
+    let mut result = Vec::new();
+
+    for (id, atom) in atoms.iter().enumerate() {
+        let AtomKind::Bracket {
+            symbol: Symbol::Star,
+            ..
+        } = atom.kind
+        else {
+            continue;
+        };
+
+        if map_number(atom) != Some(map) {
+            continue;
+        }
+
+        if atom.bonds.len() != 1 {
+            return Err(Error::Attachment(id));
+        }
+
+        result.push(id);
+    }
+
+    Ok(result)
+}
+
+/// Bonds the neighbors of star atoms `sid` and `tid` in place of their
+/// bonds to them, or returns `None` if the bonds are incompatible.
+fn connect(atoms: &mut [Atom], sid: usize, tid: usize) -> Option<()> {
+    let left = atoms[sid].bonds[0].tid;
+    let right = atoms[tid].bonds[0].tid;
+    let forward = atoms[left].bonds.iter().position(|bond| bond.tid == sid)?;
+    let back = atoms[right].bonds.iter().position(|bond| bond.tid == tid)?;
+    let (left_kind, right_kind) = match reconcile(
+        atoms[left].bonds[forward].kind,
+        atoms[right].bonds[back].kind,
+    )? {
+        // an elided bond between aromatic atoms would read as aromatic
+        (BondKind::Elided, _) if atoms[left].is_aromatic() && atoms[right].is_aromatic() => {
+            (BondKind::Single, BondKind::Single)
+        }
+        kinds => kinds,
+    };
+
+    set_bond(&mut atoms[left], forward, Bond::new(left_kind, right))?;
+    set_bond(&mut atoms[right], back, Bond::new(right_kind, left))
+}
+
+/// Replaces the bond at `index` of `atom`, changing the hydrogen count of
+/// a bracket atom by the change in order.
+fn set_bond(atom: &mut Atom, index: usize, bond: Bond) -> Option<()> {
+    let old = atom.bonds[index].order();
+    let new = bond.order();
+
+    if let AtomKind::Bracket { hcount, .. } = &mut atom.kind {
+        if old != new {
+            let count = (hcount.as_ref().map_or(0, u8::from) + old).checked_sub(new)?;
+            let virtual_hydrogen = VirtualHydrogen::try_from(count).ok()?;
+
+            *hcount = (!virtual_hydrogen.is_zero()).then_some(virtual_hydrogen);
+        }
+    }
+
+    atom.bonds[index] = bond;
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::is_isomorphic;
+    use crate::testing::atoms;
+    use crate::walk::walk;
+    use crate::write::Writer;
+    use pretty_assertions::assert_eq;
+
+    fn write(atoms: Vec<Atom>) -> String {
+        let mut writer = Writer::default();
+
+        walk(atoms, &mut writer).unwrap();
+
+        writer.write()
+    }
+
+    fn joined(core: &str, substituent: &str) -> Result<Vec<Atom>, Error> {
+        join(&atoms(core), &atoms(substituent), 1)
+    }
+
+    #[test]
+    fn methyl() {
+        let result = joined("[*:1]c1ccccc1", "C[*:1]").unwrap();
+
+        assert!(is_isomorphic(&result, &atoms("Cc1ccccc1"), true));
+        assert_eq!(write(result), "c(C)(ccccc1)1")
+    }
+
+    #[test]
+    fn aromatic_atoms() {
+        let result = joined("[*:1]c1ccccc1", "c1ccccc1[*:1]").unwrap();
+
+        assert_eq!(result[0].bonds[0], Bond::new(BondKind::Single, 11));
+        assert!(is_isomorphic(&result, &atoms("c1ccccc1-c1ccccc1"), true))
+    }
+
+    #[test]
+    fn each_attachment_point() {
+        let result = joined("[*:1]CC[*:1]", "O[*:1]").unwrap();
+
+        assert_eq!(write(result), "C(O)CO")
+    }
+
+    #[test]
+    fn bond_kind_from_substituent() {
+        let result = joined("[*:1]C", "O=[*:1]").unwrap();
+
+        assert_eq!(write(result), "C=O")
+    }
+
+    #[test]
+    fn bond_kind_from_core() {
+        let result = joined("C#[*:1]", "[*:1]N").unwrap();
+
+        assert_eq!(write(result), "C#N")
+    }
+
+    #[test]
+    fn incompatible_bond_kinds() {
+        assert_eq!(joined("C=[*:1]", "C#[*:1]"), Err(Error::Join(1)))
+    }
+
+    #[test]
+    fn directional_bond() {
+        let result = joined("F/C=C/[*:1]", "[*:1]C").unwrap();
+
+        assert!(is_isomorphic(&result, &atoms("F/C=C/C"), true));
+        assert!(!is_isomorphic(&result, &atoms("F/C=C\\C"), true))
+    }
+
+    #[test]
+    fn directional_bond_from_substituent() {
+        let result = joined("F/C=C[*:1]", "[*:1]/C").unwrap();
+
+        assert!(is_isomorphic(&result, &atoms("F/C=C/C"), true))
+    }
+
+    #[test]
+    fn stereocenter_in_core() {
+        let result = joined("[*:1][C@H](F)Cl", "C[*:1]").unwrap();
+
+        assert!(is_isomorphic(&result, &atoms("C[C@H](F)Cl"), true));
+        assert!(!is_isomorphic(&result, &atoms("C[C@@H](F)Cl"), true))
+    }
+
+    #[test]
+    fn stereocenter_in_substituent() {
+        let result = joined("[*:1]O", "F[C@@H](Cl)[*:1]").unwrap();
+
+        assert!(is_isomorphic(&result, &atoms("F[C@@H](Cl)O"), true));
+        assert!(!is_isomorphic(&result, &atoms("F[C@H](Cl)O"), true))
+    }
+
+    #[test]
+    fn bracket_hydrogens() {
+        let result = joined("[*:1][CH3]", "[*:1]=[NH]").unwrap();
+
+        assert_eq!(write(result), "[CH2]=[NH]")
+    }
+
+    #[test]
+    fn bracket_hydrogens_underflow() {
+        assert_eq!(joined("[*:1][C](F)(F)F", "[*:1]=O"), Err(Error::Join(1)))
+    }
+
+    #[test]
+    fn unknown_map() {
+        assert_eq!(
+            join(&atoms("[*:2]C"), &atoms("C[*:1]"), 1),
+            Err(Error::UnknownMap(1))
+        )
+    }
+
+    #[test]
+    fn substituent_without_attachment_point() {
+        assert_eq!(joined("[*:1]C", "CO"), Err(Error::Substituent(1)))
+    }
+
+    #[test]
+    fn substituent_with_two_attachment_points() {
+        assert_eq!(joined("[*:1]C", "[*:1]O[*:1]"), Err(Error::Substituent(1)))
+    }
+
+    #[test]
+    fn attachment_point_with_two_bonds() {
+        assert_eq!(joined("C[*:1]C", "C[*:1]"), Err(Error::Attachment(1)))
+    }
+}
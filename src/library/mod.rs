@@ -0,0 +1,7 @@
+mod error;
+mod join;
+mod rgroups;
+
+pub use error::Error;
+pub use join::join;
+pub use rgroups::{Library, Products};
@@ -0,0 +1,285 @@
+use super::join::attachments;
+use super::{join, Error};
+use crate::graph::Atom;
+use crate::walk::walk;
+use crate::write::Writer;
+
+/// A combinatorial library: a core with star atoms such as `[*:1]`, and
+/// lists of substituents to join at each of them.
+///
+/// Products are every combination of one substituent per R-group, joined
+/// with `join` in the order the R-groups were added, with the last
+/// R-group varying fastest.
+///
+/// ```
+/// use yowl::graph::{Atom, Builder};
+/// use yowl::library::Library;
+/// use yowl::read::read;
+///
+/// fn atoms(smiles: &str) -> Vec<Atom> {
+///     let mut builder = Builder::default();
+///
+///     read(smiles, &mut builder, None).expect("read");
+///
+///     builder.build().expect("atoms")
+/// }
+///
+/// let mut library = Library::new(atoms("[*:1]c1ccc([*:2])cc1"));
+///
+/// library
+///     .add(1, vec![atoms("C[*:1]"), atoms("Cl[*:1]")])
+///     .expect("R1");
+/// library
+///     .add(2, vec![atoms("[*:2]O"), atoms("[*:2]N"), atoms("[*:2]F")])
+///     .expect("R2");
+///
+/// let products = library
+///     .products()
+///     .smiles()
+///     .collect::<Result<Vec<_>, _>>()
+///     .expect("products");
+///
+/// assert_eq!(library.len(), 6);
+/// assert_eq!(products[0], "c(C)(ccc(cc1)O)1");
+/// assert_eq!(products[5], "c(Cl)(ccc(cc1)F)1");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Library {
+    core: Vec<Atom>,
+    rgroups: Vec<(u16, Vec<Vec<Atom>>)>,
+}
+
+impl Library {
+    /// Constructs a library over `core`, without R-groups.
+    pub fn new(core: Vec<Atom>) -> Self {
+        Self {
+            core,
+            rgroups: Vec::new(),
+        }
+    }
+
+    /// Adds an R-group joining each of `substituents` at the star atoms
+    /// with map `map`, of the core or of substituents added before.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `map` was added already, or if a substituent
+    /// can't be joined where the map appears.
+    pub fn add(&mut self, map: u16, substituents: Vec<Vec<Atom>>) -> Result<(), Error> {
+        if self.rgroups.iter().any(|(other, _)| *other == map) {
+            return Err(Error::DuplicateMap(map));
+        }
+
+        let mut targets = Vec::new();
+        let fragments = self
+            .rgroups
+            .iter()
+            .flat_map(|(_, substituents)| substituents);
+
+        for atoms in std::iter::once(&self.core).chain(fragments) {
+            if !attachments(atoms, map)?.is_empty() {
+                targets.push(atoms)
+            }
+        }
+
+        if targets.is_empty() {
+            return Err(Error::UnknownMap(map));
+        }
+
+        for substituent in &substituents {
+            for target in &targets {
+                join(target, substituent, map)?;
+            }
+        }
+
+        self.rgroups.push((map, substituents));
+
+        Ok(())
+    }
+
+    /// Returns the number of products.
+    pub fn len(&self) -> usize {
+        self.rgroups
+            .iter()
+            .fold(1, |result: usize, (_, substituents)| {
+                result.saturating_mul(substituents.len())
+            })
+    }
+
+    /// Returns true if there are no products.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the products.
+    pub fn products(&self) -> Products<'_> {
+        Products {
+            library: self,
+            indices: (!self.is_empty()).then(|| vec![0; self.rgroups.len()]),
+        }
+    }
+}
+
+/// Enumerates the products of a `Library`, lazily. A substituent that
+/// itself carries star atoms mapped to a later R-group, such as a linker,
+/// is joined there too, and a later R-group is skipped in products
+/// without its star atoms.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Products<'a> {
+    library: &'a Library,
+    indices: Option<Vec<usize>>,
+}
+
+impl<'a> Products<'a> {
+    /// Returns an iterator over the products written as SMILES.
+    pub fn smiles(self) -> impl Iterator<Item = Result<String, Error>> + 'a {
+        self.map(|product| {
+            let mut writer = Writer::default();
+
+            walk(product?, &mut writer).map_err(Error::Walk)?;
+
+            Ok(writer.write())
+        })
+    }
+
+    fn build(&self, indices: &[usize]) -> Result<Vec<Atom>, Error> {
+        let mut result = self.library.core.clone();
+
+        for ((map, substituents), &index) in self.library.rgroups.iter().zip(indices) {
+            if !attachments(&result, *map)?.is_empty() {
+                result = join(&result, &substituents[index], *map)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Returns the indices after `indices`, with the last R-group fastest,
+    /// or `None` after the last.
+    fn successor(&self, indices: &[usize]) -> Option<Vec<usize>> {
+        let mut result = indices.to_vec();
+
+        for (index, (_, substituents)) in result.iter_mut().zip(&self.library.rgroups).rev() {
+            *index += 1;
+
+            if *index < substituents.len() {
+                return Some(result);
+            }
+
+            *index = 0;
+        }
+
+        None
+    }
+}
+
+impl Iterator for Products<'_> {
+    type Item = Result<Vec<Atom>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let indices = self.indices.take()?;
+
+        self.indices = self.successor(&indices);
+
+        Some(self.build(&indices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::atoms;
+    use pretty_assertions::assert_eq;
+
+    fn smiles(library: &Library) -> Vec<String> {
+        library.products().smiles().map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn no_rgroups() {
+        let library = Library::new(atoms("[*:1]CO"));
+
+        assert_eq!(library.len(), 1);
+        assert_eq!(smiles(&library), ["[*:1]CO"])
+    }
+
+    #[test]
+    fn empty_rgroup() {
+        let mut library = Library::new(atoms("[*:1]CO"));
+
+        library.add(1, vec![]).unwrap();
+
+        assert!(library.is_empty());
+        assert_eq!(library.products().count(), 0)
+    }
+
+    #[test]
+    fn last_rgroup_fastest() {
+        let mut library = Library::new(atoms("[*:1]C[*:2]"));
+
+        library
+            .add(1, vec![atoms("F[*:1]"), atoms("Cl[*:1]")])
+            .unwrap();
+        library
+            .add(2, vec![atoms("[*:2]O"), atoms("[*:2]N")])
+            .unwrap();
+
+        assert_eq!(library.len(), 4);
+        assert_eq!(smiles(&library), ["C(F)O", "C(F)N", "C(Cl)O", "C(Cl)N"])
+    }
+
+    #[test]
+    fn graphs() {
+        let mut library = Library::new(atoms("[*:1]O"));
+
+        library.add(1, vec![atoms("C[*:1]")]).unwrap();
+
+        assert_eq!(library.products().collect::<Vec<_>>(), [Ok(atoms("OC"))])
+    }
+
+    #[test]
+    fn linker() {
+        let mut library = Library::new(atoms("[*:1]O"));
+
+        library.add(1, vec![atoms("[*:2]CC[*:1]")]).unwrap();
+        library.add(2, vec![atoms("[*:2]N")]).unwrap();
+
+        assert_eq!(smiles(&library), ["OCCN"])
+    }
+
+    #[test]
+    fn linker_in_some_substituents() {
+        let mut library = Library::new(atoms("[*:1]O"));
+
+        library
+            .add(1, vec![atoms("[*:2]CC[*:1]"), atoms("C[*:1]")])
+            .unwrap();
+        library.add(2, vec![atoms("[*:2]N")]).unwrap();
+
+        assert_eq!(library.len(), 2);
+        assert_eq!(smiles(&library), ["OCCN", "OC"])
+    }
+
+    #[test]
+    fn duplicate_map() {
+        let mut library = Library::new(atoms("[*:1]O"));
+
+        library.add(1, vec![atoms("C[*:1]")]).unwrap();
+
+        assert_eq!(
+            library.add(1, vec![atoms("N[*:1]")]),
+            Err(Error::DuplicateMap(1))
+        )
+    }
+
+    #[test]
+    fn invalid_substituent() {
+        let mut library = Library::new(atoms("[*:1]O"));
+
+        assert_eq!(
+            library.add(1, vec![atoms("C[*:1]"), atoms("CN")]),
+            Err(Error::Substituent(1))
+        );
+        assert_eq!(library.len(), 1)
+    }
+}
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+/// An error that occurs when reading a reaction SMILES string. Cursors
+/// refer to the whole reaction string.
+#[derive(Debug, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Error {
+    #[error("Unexpected end of input")]
+    EndOfLine,
+    #[error("Unexpected character: {0}")]
+    Character(usize),
+    #[error("Unmatched ring closure: {0}")]
+    Rnum(usize),
+    #[error("Incompatible ring closure bond: {0}")]
+    Join(usize),
+}
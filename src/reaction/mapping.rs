@@ -0,0 +1,188 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::Reaction;
+use crate::feature::BondKind;
+use crate::graph::{self, map_number, Atom};
+
+/// Bonds between mapped atoms that differ between reactants and products,
+/// each given as an ascending pair of map numbers.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct BondChanges {
+    pub formed: Vec<(u16, u16)>,
+    pub broken: Vec<(u16, u16)>,
+    pub changed: Vec<(u16, u16)>,
+}
+
+impl Reaction {
+    /// Clears the atom maps of every role, unbracketing atoms that needed
+    /// brackets only for their map.
+    pub fn strip_maps(&mut self) {
+        graph::strip_maps(&mut self.reactants);
+        graph::strip_maps(&mut self.agents);
+        graph::strip_maps(&mut self.products);
+    }
+
+    /// Renumbers atom maps sequentially from 1, in order of appearance
+    /// across reactants, agents and products. A map number shared between
+    /// roles stays shared.
+    pub fn renumber_maps(&mut self) {
+        let mut numbers = HashMap::new();
+
+        graph::renumber(&mut self.reactants, &mut numbers);
+        graph::renumber(&mut self.agents, &mut numbers);
+        graph::renumber(&mut self.products, &mut numbers);
+    }
+
+    /// Returns pairs of reactant and product indices for atoms sharing a
+    /// map number.
+    pub fn correspondence(&self) -> Vec<(usize, usize)> {
+        graph::correspondence(&self.reactants, &self.products)
+    }
+
+    /// Compares the bonds between atoms mapped in both reactants and
+    /// products. Aromatic bonds are distinguished from both single and
+    /// double bonds.
+    ///
+    /// ```
+    /// use yowl::reaction::{read, BondChanges};
+    ///
+    /// let reaction = read("[CH3:1][Br:2].[OH-:3]>>[CH3:1][OH:3].[Br-:2]").expect("read");
+    ///
+    /// assert_eq!(reaction.bond_changes(), BondChanges {
+    ///     formed: vec![(1, 3)],
+    ///     broken: vec![(1, 2)],
+    ///     changed: vec![]
+    /// })
+    /// ```
+    pub fn bond_changes(&self) -> BondChanges {
+        let before = mapped_bonds(&self.reactants);
+        let after = mapped_bonds(&self.products);
+        let reactant_maps = self
+            .reactants
+            .iter()
+            .filter_map(map_number)
+            .collect::<HashSet<_>>();
+        let product_maps = self
+            .products
+            .iter()
+            .filter_map(map_number)
+            .collect::<HashSet<_>>();
+        let in_both = |map: u16| reactant_maps.contains(&map) && product_maps.contains(&map);
+        let mut result = BondChanges::default();
+
+        for (&pair, &order) in &before {
+            match after.get(&pair) {
+                Some(&other) if other != order => result.changed.push(pair),
+                Some(_) => (),
+                None if in_both(pair.0) && in_both(pair.1) => result.broken.push(pair),
+                None => (),
+            }
+        }
+
+        for &pair in after.keys() {
+            if !before.contains_key(&pair) && in_both(pair.0) && in_both(pair.1) {
+                result.formed.push(pair);
+            }
+        }
+
+        result
+    }
+}
+
+/// Collects bonds between mapped atoms keyed by ascending map pair, with
+/// twice the bond order as value so that aromatic bonds get their own.
+fn mapped_bonds(atoms: &[Atom]) -> BTreeMap<(u16, u16), u8> {
+    let mut result = BTreeMap::new();
+
+    for (sid, atom) in atoms.iter().enumerate() {
+        let Some(left) = map_number(atom) else {
+            continue;
+        };
+
+        for bond in &atom.bonds {
+            let Some(right) = atoms.get(bond.tid).and_then(map_number) else {
+                continue;
+            };
+
+            if bond.tid < sid || left == right {
+                continue;
+            }
+
+            let aromatic = bond.kind == BondKind::Aromatic
+                || (bond.kind == BondKind::Elided
+                    && atom.is_aromatic()
+                    && atoms[bond.tid].is_aromatic());
+            let order = if aromatic { 3 } else { bond.order() * 2 };
+
+            result.insert((left.min(right), left.max(right)), order);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reaction::{read, write};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn strip_maps() {
+        let mut reaction = read("[CH3:1][OH:2]>[Na+:5]>[CH2:1]=[O:2]").unwrap();
+
+        reaction.strip_maps();
+
+        assert_eq!(write(&reaction), Ok("CO>[Na+]>C=O".to_string()))
+    }
+
+    #[test]
+    fn renumber_maps_shared() {
+        let mut reaction = read("[CH3:9][OH:4]>[Na+:6]>[CH2:9]=[O:4]").unwrap();
+
+        reaction.renumber_maps();
+
+        assert_eq!(
+            write(&reaction),
+            Ok("[CH3:1][OH:2]>[Na+:3]>[CH2:1]=[O:2]".to_string())
+        )
+    }
+
+    #[test]
+    fn correspondence() {
+        let reaction = read("[CH3:1]C[OH:2]>>[OH2:2].[CH4:1]").unwrap();
+
+        assert_eq!(reaction.correspondence(), vec![(0, 1), (2, 0)])
+    }
+
+    #[test]
+    fn changed_order() {
+        let reaction = read("[CH3:1][CH2:2][OH:3]>>[CH2:1]=[CH2:2].[OH2:3]").unwrap();
+
+        assert_eq!(
+            reaction.bond_changes(),
+            BondChanges {
+                formed: vec![],
+                broken: vec![(2, 3)],
+                changed: vec![(1, 2)]
+            }
+        )
+    }
+
+    #[test]
+    fn kekule_to_aromatic() {
+        let reaction = read(
+            "[CH:1]1=[CH:2][CH:3]=[CH:4][CH:5]=[CH:6]1>>[cH:1]1[cH:2][cH:3][cH:4][cH:5][cH:6]1",
+        )
+        .unwrap();
+
+        assert_eq!(reaction.bond_changes().changed.len(), 6)
+    }
+
+    #[test]
+    fn unmapped_in_products_ignored() {
+        let reaction = read("[CH4:1].[OH2:2]>>[CH3:1][OH:3]").unwrap();
+
+        assert_eq!(reaction.bond_changes(), BondChanges::default())
+    }
+}
@@ -0,0 +1,11 @@
+mod error;
+mod mapping;
+mod reader;
+mod roles;
+mod writer;
+
+pub use error::Error;
+pub use mapping::BondChanges;
+pub use reader::read;
+pub use roles::Reaction;
+pub use writer::write;
@@ -0,0 +1,170 @@
+use std::ops::Range;
+
+use super::{Error, Reaction};
+use crate::graph::{self, Atom, Builder};
+use crate::read::{self, ReadError, Trace};
+
+/// Reads a reaction SMILES string of the form
+/// `reactants>agents>products`, building each role with `Builder`. Atom
+/// maps are kept as written.
+///
+/// ```
+/// use yowl::reaction::{read, Error};
+///
+/// fn main() -> Result<(), Error> {
+///     let reaction = read("CC(=O)O.OCC>[H+]>CC(=O)OCC.O")?;
+///
+///     assert_eq!(reaction.reactants.len(), 7);
+///     assert_eq!(reaction.agents.len(), 1);
+///     assert_eq!(reaction.products.len(), 7);
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the string doesn't contain exactly three roles, or
+/// if a role isn't valid SMILES.
+pub fn read(reaction: &str) -> Result<Reaction, Error> {
+    let mut roles = Vec::new();
+    let mut start = 0;
+
+    for (cursor, byte) in reaction.bytes().enumerate() {
+        // the head of a dative bond `->` doesn't separate roles
+        if byte == b'>' && !reaction[..cursor].ends_with('-') {
+            if roles.len() == 2 {
+                return Err(Error::Character(cursor));
+            }
+
+            roles.push(start..cursor);
+            start = cursor + 1;
+        }
+    }
+
+    if roles.len() < 2 {
+        return Err(Error::EndOfLine);
+    }
+
+    roles.push(start..reaction.len());
+
+    let mut roles = roles.into_iter();
+    let mut next = || read_role(reaction, roles.next().expect("role"));
+
+    Ok(Reaction {
+        reactants: next()?,
+        agents: next()?,
+        products: next()?,
+    })
+}
+
+fn read_role(reaction: &str, range: Range<usize>) -> Result<Vec<Atom>, Error> {
+    if range.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = Builder::default();
+    let mut trace = Trace::default();
+
+    read::read(&reaction[range.clone()], &mut builder, Some(&mut trace)).map_err(|error| {
+        match error {
+            // a role ending early is cut off by the following '>'
+            ReadError::EndOfLine if range.end < reaction.len() => Error::Character(range.end),
+            ReadError::EndOfLine => Error::EndOfLine,
+            ReadError::Character(cursor) => Error::Character(range.start + cursor),
+        }
+    })?;
+
+    builder.build().map_err(|error| match error {
+        graph::Error::Rnum(rid) => Error::Rnum(range.start + trace.rnum(rid).expect("rnum").start),
+        graph::Error::Join(sid, tid) => {
+            Error::Join(range.start + trace.bond(sid, tid).expect("bond"))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::{AtomKind, Symbol};
+    use crate::Element;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn empty_roles() {
+        assert_eq!(read(">>"), Ok(Reaction::default()))
+    }
+
+    #[test]
+    fn missing_separator() {
+        assert_eq!(read("CC>CC"), Err(Error::EndOfLine))
+    }
+
+    #[test]
+    fn dative_bond() {
+        let reaction = read("N->[Cu]>>N.[Cu]").unwrap();
+
+        assert_eq!(reaction.reactants.len(), 2);
+        assert_eq!(reaction.agents.len(), 0);
+        assert_eq!(reaction.products.len(), 2)
+    }
+
+    #[test]
+    fn extra_separator() {
+        assert_eq!(read("C>C>C>C"), Err(Error::Character(5)))
+    }
+
+    #[test]
+    fn character_in_agents() {
+        assert_eq!(read("CC>C?>C"), Err(Error::Character(4)))
+    }
+
+    #[test]
+    fn open_branch_in_reactants() {
+        assert_eq!(read("C(>>C"), Err(Error::Character(2)))
+    }
+
+    #[test]
+    fn open_branch_in_products() {
+        assert_eq!(read("C>>C("), Err(Error::EndOfLine))
+    }
+
+    #[test]
+    fn unmatched_rnum_in_products() {
+        assert_eq!(read("C>>CC1CC"), Err(Error::Rnum(5)))
+    }
+
+    #[test]
+    fn incompatible_join_in_reactants() {
+        assert_eq!(read("C=1CC-1>>C"), Err(Error::Join(5)))
+    }
+
+    #[test]
+    fn products_only() {
+        let reaction = read(">>C").unwrap();
+
+        assert_eq!(reaction.reactants, vec![]);
+        assert_eq!(reaction.agents, vec![]);
+        assert_eq!(
+            reaction.products,
+            vec![Atom::new(AtomKind::Symbol(Symbol::Aliphatic(Element::C)))]
+        )
+    }
+
+    #[test]
+    fn maps_kept() {
+        let reaction = read("[CH4:1]>>[CH3:1][OH:2]").unwrap();
+        let maps = |atoms: &[Atom]| {
+            atoms
+                .iter()
+                .map(|atom| match atom.kind {
+                    AtomKind::Bracket { map, .. } => map,
+                    AtomKind::Symbol(_) => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(maps(&reaction.reactants), vec![Some(1)]);
+        assert_eq!(maps(&reaction.products), vec![Some(1), Some(2)])
+    }
+}
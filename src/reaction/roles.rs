@@ -0,0 +1,10 @@
+use crate::graph::Atom;
+
+/// The reactant, agent and product graphs of a reaction. Each role may
+/// hold several disconnected components, or none.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Reaction {
+    pub reactants: Vec<Atom>,
+    pub agents: Vec<Atom>,
+    pub products: Vec<Atom>,
+}
@@ -0,0 +1,67 @@
+use super::Reaction;
+use crate::graph::Atom;
+use crate::walk::{walk, Error};
+use crate::write::Writer;
+
+/// Writes `reaction` as reaction SMILES, walking each role with a
+/// `Writer`.
+///
+/// ```
+/// use yowl::reaction::{read, write};
+///
+/// let reaction = read("[CH3:1][OH:2]>>[CH2:1]=[O:2]").expect("read");
+///
+/// assert_eq!(write(&reaction), Ok("[CH3:1][OH:2]>>[CH2:1]=[O:2]".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if walking any role fails.
+pub fn write(reaction: &Reaction) -> Result<String, Error> {
+    Ok(format!(
+        "{}>{}>{}",
+        write_role(&reaction.reactants)?,
+        write_role(&reaction.agents)?,
+        write_role(&reaction.products)?
+    ))
+}
+
+fn write_role(atoms: &[Atom]) -> Result<String, Error> {
+    let mut writer = Writer::default();
+
+    walk(atoms, &mut writer)?;
+
+    Ok(writer.write())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::BondKind;
+    use crate::graph::Bond;
+    use crate::reaction::read;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn empty() {
+        assert_eq!(write(&Reaction::default()), Ok(">>".to_string()))
+    }
+
+    #[test]
+    fn roundtrip() {
+        let input = "CC(=O)O.OCC>[H+]>CC(=O)OCC.O";
+
+        assert_eq!(write(&read(input).unwrap()), Ok(input.to_string()))
+    }
+
+    #[test]
+    fn invalid_agents() {
+        let mut reaction = read("C>C>C").unwrap();
+
+        reaction.agents[0]
+            .bonds
+            .push(Bond::new(BondKind::Single, 3));
+
+        assert_eq!(write(&reaction), Err(Error::UnknownTarget(0, 3)))
+    }
+}
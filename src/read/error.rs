@@ -2,9 +2,28 @@ use thiserror::Error;
 
 /// An error that occurs when reading a SMILES string.
 #[derive(Debug, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReadError {
     #[error("Unexpected end of input")]
     EndOfLine,
     #[error("Unexpected character: {0}")]
     Character(usize),
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod serialization {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn representation() {
+        assert_eq!(
+            serde_json::to_string(&ReadError::Character(3)).unwrap(),
+            r#"{"Character":3}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<ReadError>(r#""EndOfLine""#).unwrap(),
+            ReadError::EndOfLine
+        )
+    }
+}
@@ -10,11 +10,16 @@ enum BondToken {
     Aromatic,
     Up,
     Down,
+    DativeRight,
+    DativeLeft,
+    Zero,
     Elided,
 }
 
 fn next_bond_token(scanner: &mut Scanner) -> BondToken {
     let tok = match scanner.peek() {
+        Some('-') if scanner.peek_nth(1) == Some('>') => BondToken::DativeRight,
+        Some('<') if scanner.peek_nth(1) == Some('-') => BondToken::DativeLeft,
         Some('-') => BondToken::Single,
         Some('=') => BondToken::Double,
         Some('#') => BondToken::Triple,
@@ -22,11 +27,19 @@ fn next_bond_token(scanner: &mut Scanner) -> BondToken {
         Some(':') => BondToken::Aromatic,
         Some('/') => BondToken::Up,
         Some('\\') => BondToken::Down,
+        Some('~') => BondToken::Zero,
         _ => BondToken::Elided,
     };
-    // only consume if it wasn’t elided
-    if tok != BondToken::Elided {
-        scanner.pop();
+    // only consume if it wasn’t elided, and both chars of arrows
+    match tok {
+        BondToken::Elided => (),
+        BondToken::DativeRight | BondToken::DativeLeft => {
+            scanner.pop();
+            scanner.pop();
+        }
+        _ => {
+            scanner.pop();
+        }
     }
     tok
 }
@@ -40,6 +53,9 @@ pub fn read_bond(scanner: &mut Scanner) -> BondKind {
         BondToken::Aromatic => BondKind::Aromatic,
         BondToken::Up => BondKind::Up,
         BondToken::Down => BondKind::Down,
+        BondToken::DativeRight => BondKind::DativeRight,
+        BondToken::DativeLeft => BondKind::DativeLeft,
+        BondToken::Zero => BondKind::Zero,
         BondToken::Elided => BondKind::Elided,
     }
 }
@@ -103,4 +119,35 @@ mod tests {
 
         assert_eq!(read_bond(&mut scanner), BondKind::Down);
     }
+
+    #[test]
+    fn dative_right() {
+        let mut scanner = Scanner::new("->");
+
+        assert_eq!(read_bond(&mut scanner), BondKind::DativeRight);
+        assert_eq!(scanner.cursor(), 2);
+    }
+
+    #[test]
+    fn dative_left() {
+        let mut scanner = Scanner::new("<-");
+
+        assert_eq!(read_bond(&mut scanner), BondKind::DativeLeft);
+        assert_eq!(scanner.cursor(), 2);
+    }
+
+    #[test]
+    fn lone_angle() {
+        let mut scanner = Scanner::new("<C");
+
+        assert_eq!(read_bond(&mut scanner), BondKind::Elided);
+        assert_eq!(scanner.cursor(), 0);
+    }
+
+    #[test]
+    fn zero() {
+        let mut scanner = Scanner::new("~");
+
+        assert_eq!(read_bond(&mut scanner), BondKind::Zero);
+    }
 }
@@ -45,7 +45,7 @@ pub fn read<F: Follower>(
 
 // <smiles> ::= <atom> <body>*
 fn read_smiles<F: Follower>(
-    input: Option<BondKind>,
+    input: Option<(BondKind, usize)>,
     scanner: &mut Scanner,
     follower: &mut F,
     trace: &mut Option<&mut Trace>,
@@ -55,13 +55,9 @@ fn read_smiles<F: Follower>(
         return Ok(None);
     };
 
-    if let Some(bond_kind) = input {
+    if let Some((bond_kind, bond_cursor)) = input {
         if let Some(trace) = trace {
-            if bond_kind == BondKind::Elided {
-                trace.extend(cursor, cursor..scanner.cursor());
-            } else {
-                trace.extend(cursor - 1, cursor..scanner.cursor());
-            }
+            trace.extend(bond_cursor, cursor..scanner.cursor())
         }
 
         follower.extend(bond_kind, atom_kind);
@@ -133,9 +129,10 @@ fn read_branch<F: Follower>(
             None => return Err(missing_character(scanner)),
         }
     } else {
+        let bond_cursor = scanner.cursor();
         let bond_kind = read_bond(scanner);
 
-        match read_smiles(Some(bond_kind), scanner, follower, trace)? {
+        match read_smiles(Some((bond_kind, bond_cursor)), scanner, follower, trace)? {
             Some(length) => length,
             None => return Err(missing_character(scanner)),
         }
@@ -184,7 +181,7 @@ fn read_union<F: Follower>(
     let bond_cursor = scanner.cursor();
     let bond_kind = read_bond(scanner);
 
-    if let Some(length) = read_smiles(Some(bond_kind), scanner, follower, trace)? {
+    if let Some(length) = read_smiles(Some((bond_kind, bond_cursor)), scanner, follower, trace)? {
         return Ok(Some(length));
     }
 
@@ -532,6 +529,41 @@ mod trace {
         assert_eq!(trace.bond(1, 0), Some(1))
     }
 
+    #[test]
+    fn p2_dative() {
+        let mut trace = Trace::default();
+        let mut writer = Writer::default();
+
+        read("*->*", &mut writer, Some(&mut trace)).unwrap();
+
+        assert_eq!(trace.atom(1), Some(3..4));
+        assert_eq!(trace.bond(0, 1), Some(1))
+    }
+
+    #[test]
+    fn p2_dative_quoted() {
+        let mut trace = Trace::default();
+        let mut writer = Writer::default();
+
+        //    01234
+        read("*-'>*", &mut writer, Some(&mut trace)).unwrap();
+
+        assert_eq!(trace.atom(1), Some(4..5));
+        assert_eq!(trace.bond(0, 1), Some(1))
+    }
+
+    #[test]
+    fn p3_dative_branch() {
+        let mut trace = Trace::default();
+        let mut writer = Writer::default();
+
+        //    0123456
+        read("*(<-*)*", &mut writer, Some(&mut trace)).unwrap();
+
+        assert_eq!(trace.atom(1), Some(4..5));
+        assert_eq!(trace.bond(0, 1), Some(2))
+    }
+
     #[test]
     fn p3_branched() {
         let mut trace = Trace::default();
@@ -51,6 +51,16 @@ impl<'a> Scanner<'a> {
         None
     }
 
+    /// Look ahead to the non‐quote char after the next `n` without consuming.
+    /// Returns None past EOF.
+    pub fn peek_nth(&self, n: usize) -> Option<char> {
+        self.buf[self.pos..]
+            .iter()
+            .filter(|&&b| b != b'\'')
+            .nth(n)
+            .map(|&b| b as char)
+    }
+
     /// The current byte‐index in the original string.
     pub fn cursor(&self) -> usize {
         self.pos
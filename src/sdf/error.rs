@@ -0,0 +1,25 @@
+use std::io;
+
+use thiserror::Error;
+
+/// An error that occurs when reading a record from an SD file. Every
+/// variant carries the one-based line number at which it occurs.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum Error {
+    #[error("I/O error at line {0}: {1}")]
+    Io(usize, io::ErrorKind),
+    #[error("Unexpected end of record at line {0}")]
+    EndOfRecord(usize),
+    #[error("Invalid counts line at line {0}")]
+    Counts(usize),
+    #[error("Unsupported molfile version at line {0}")]
+    Version(usize),
+    #[error("Invalid atom at line {0}")]
+    Atom(usize),
+    #[error("Invalid bond at line {0}")]
+    Bond(usize),
+    #[error("Invalid property at line {0}")]
+    Property(usize),
+    #[error("Invalid data header at line {0}")]
+    Data(usize),
+}
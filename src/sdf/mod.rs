@@ -0,0 +1,9 @@
+mod error;
+mod reader;
+mod record;
+mod writer;
+
+pub use error::Error;
+pub use reader::Reader;
+pub use record::Record;
+pub use writer::Writer;
@@ -0,0 +1,730 @@
+use std::io::{self, BufRead};
+use std::ops::Range;
+
+use super::{Error, Record};
+use crate::feature::{
+    element_from_symbol, standard_valences, AtomKind, BondKind, Charge, Symbol, ValenceModel,
+    ValenceTable, VirtualHydrogen,
+};
+use crate::graph::{Atom, Bond};
+use crate::{Element, Isotope};
+
+/// Iterates the `$$$$`-delimited records of an SD file.
+///
+/// Records are read one at a time, so arbitrarily large files can be
+/// processed. A malformed record yields an `Error` carrying the line at
+/// which the problem was found, after which reading resumes with the next
+/// record. Only V2000 connection tables are supported, and stereo is not
+/// perceived from coordinates.
+///
+/// An atom with a valence field has the hydrogens that make up its
+/// valence. Other atoms that need brackets are given the implicit
+/// hydrogens of the valence targets of the model, by default the
+/// standard `ValenceTable`.
+///
+/// ```
+/// use yowl::sdf::Reader;
+///
+/// let sdf = "\
+/// ethanol
+///
+///
+///   3  2  0  0  0  0  0  0  0  0999 V2000
+///     0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+///     0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+///     0.0000    0.0000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0
+///   1  2  1  0
+///   2  3  1  0
+/// M  END
+/// >  <ID>
+/// 702
+///
+/// $$$$
+/// ";
+///
+/// let records = Reader::new(sdf.as_bytes()).collect::<Vec<_>>();
+/// let record = records[0].as_ref().expect("record");
+///
+/// assert_eq!(record.title, "ethanol");
+/// assert_eq!(record.atoms.len(), 3);
+/// assert_eq!(record.get("ID"), Some("702"));
+/// ```
+#[derive(Debug)]
+pub struct Reader<R, M = &'static ValenceTable> {
+    input: R,
+    line: usize,
+    done: bool,
+    model: M,
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Constructs a Reader over `input`.
+    pub fn new(input: R) -> Self {
+        Self::with_model(input, standard_valences())
+    }
+}
+
+impl<R: BufRead, M: ValenceModel> Reader<R, M> {
+    /// Constructs a Reader over `input` giving hydrogens by `model`.
+    pub const fn with_model(input: R, model: M) -> Self {
+        Self {
+            input,
+            line: 0,
+            done: false,
+            model,
+        }
+    }
+
+    /// Returns the lines of the next record and the line number of the
+    /// first, or `None` if the input is exhausted.
+    fn next_lines(&mut self) -> Option<Result<(usize, Vec<String>), Error>> {
+        let first = self.line + 1;
+        let mut lines = Vec::new();
+        let mut failure = None;
+
+        loop {
+            let mut buffer = String::new();
+
+            match self.input.read_line(&mut buffer) {
+                Ok(0) => {
+                    self.done = true;
+
+                    break;
+                }
+                Ok(_) => {
+                    self.line += 1;
+
+                    let line = buffer.trim_end_matches(['\n', '\r']);
+
+                    if line.trim_end() == "$$$$" {
+                        break;
+                    }
+
+                    lines.push(line.to_string());
+                }
+                // The offending bytes were consumed, so the rest of the
+                // record can still be skipped.
+                Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                    self.line += 1;
+                    failure.get_or_insert(Error::Io(self.line, error.kind()));
+                }
+                Err(error) => {
+                    self.done = true;
+
+                    return Some(Err(Error::Io(self.line + 1, error.kind())));
+                }
+            }
+        }
+
+        if let Some(error) = failure {
+            return Some(Err(error));
+        }
+
+        if self.done && lines.iter().all(|line| line.trim().is_empty()) {
+            return None;
+        }
+
+        Some(Ok((first, lines)))
+    }
+}
+
+impl<R: BufRead, M: ValenceModel> Iterator for Reader<R, M> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        self.next_lines()
+            .map(|lines| lines.and_then(|(first, lines)| parse_record(first, &lines, &self.model)))
+    }
+}
+
+/// The element, isotope, charge and valence given to an atom by a molfile.
+struct Entry {
+    symbol: Symbol,
+    mass: Option<u32>,
+    charge: i8,
+    valence: Option<u8>,
+}
+
+fn parse_record<M: ValenceModel>(
+    first: usize,
+    lines: &[String],
+    model: &M,
+) -> Result<Record, Error> {
+    let line = |idx: usize| {
+        lines
+            .get(idx)
+            .map(String::as_str)
+            .ok_or(Error::EndOfRecord(first + lines.len()))
+    };
+    let title = line(0)?.trim().to_string();
+    let counts = line(3)?;
+    let atom_count = number(counts, 0..3).ok_or(Error::Counts(first + 3))?;
+    let bond_count = number(counts, 3..6).ok_or(Error::Counts(first + 3))?;
+
+    if field(counts, 33..39) == "V3000" {
+        return Err(Error::Version(first + 3));
+    }
+
+    let mut entries = Vec::with_capacity(atom_count);
+
+    for idx in 4..4 + atom_count {
+        entries.push(parse_atom(line(idx)?).ok_or(Error::Atom(first + idx))?);
+    }
+
+    let mut bonds = Vec::with_capacity(bond_count);
+
+    for idx in 4 + atom_count..4 + atom_count + bond_count {
+        let bond = parse_bond(line(idx)?, atom_count).ok_or(Error::Bond(first + idx))?;
+
+        bonds.push(bond);
+    }
+
+    let mut idx = 4 + atom_count + bond_count;
+
+    loop {
+        let text = line(idx)?;
+
+        if text.starts_with("M  END") {
+            idx += 1;
+
+            break;
+        }
+
+        idx += parse_property(text, &mut entries).ok_or(Error::Property(first + idx))?;
+    }
+
+    let data = parse_data(first, idx, lines)?;
+    let atoms = build_atoms(&entries, &bonds, model).map_err(|id| Error::Atom(first + 4 + id))?;
+
+    Ok(Record { title, atoms, data })
+}
+
+fn field(line: &str, range: Range<usize>) -> &str {
+    let end = range.end.min(line.len());
+
+    line.get(range.start.min(end)..end).unwrap_or("").trim()
+}
+
+fn number<T: std::str::FromStr>(line: &str, range: Range<usize>) -> Option<T> {
+    field(line, range).parse().ok()
+}
+
+fn parse_atom(line: &str) -> Option<Entry> {
+    let (symbol, mass) = match field(line, 31..34) {
+        "*" | "A" | "Q" | "L" | "R" | "R#" => (Symbol::Star, None),
+        "D" => (Symbol::Aliphatic(Element::H), Some(2)),
+        "T" => (Symbol::Aliphatic(Element::H), Some(3)),
+        text => (Symbol::Aliphatic(element_from_symbol(text)?), None),
+    };
+    let difference = match field(line, 34..36) {
+        "" => 0,
+        text => text.parse::<i32>().ok()?,
+    };
+    let charge = match field(line, 36..39) {
+        "" | "0" | "4" => 0,
+        "1" => 3,
+        "2" => 2,
+        "3" => 1,
+        "5" => -1,
+        "6" => -2,
+        "7" => -3,
+        _ => return None,
+    };
+    let valence = match field(line, 48..51) {
+        "" | "0" => None,
+        "15" => Some(0),
+        text => Some(text.parse::<u8>().ok().filter(|valence| *valence < 15)?),
+    };
+    let mass = match (symbol, difference) {
+        (_, 0) => mass,
+        (Symbol::Aliphatic(element), difference) => {
+            let weight = f64::from(element.atomic_weight()).round() as i32;
+
+            Some(u32::try_from(weight + difference).ok()?)
+        }
+        _ => return None,
+    };
+
+    Some(Entry {
+        symbol,
+        mass,
+        charge,
+        valence,
+    })
+}
+
+/// Returns the zero-based source and target of a bond, and whether the
+/// bond is aromatic (`None`) or has a given order.
+fn parse_bond(line: &str, atom_count: usize) -> Option<(usize, usize, Option<BondKind>)> {
+    let sid = number::<usize>(line, 0..3)?;
+    let tid = number::<usize>(line, 3..6)?;
+
+    if sid == tid || !(1..=atom_count).contains(&sid) || !(1..=atom_count).contains(&tid) {
+        return None;
+    }
+
+    let kind = match field(line, 6..9) {
+        "1" => Some(BondKind::Elided),
+        "2" => Some(BondKind::Double),
+        "3" => Some(BondKind::Triple),
+        "4" => None,
+        _ => return None,
+    };
+
+    Some((sid - 1, tid - 1, kind))
+}
+
+/// Applies a property line, returning the number of lines it spans.
+fn parse_property(line: &str, entries: &mut [Entry]) -> Option<usize> {
+    match line.get(..6) {
+        Some("M  CHG") => {
+            for entry in entries.iter_mut() {
+                entry.charge = 0;
+            }
+
+            for (idx, value) in property_pairs(line, entries.len())? {
+                entries[idx].charge = i8::try_from(value)
+                    .ok()
+                    .filter(|&charge| Charge::new(charge).is_some())?;
+            }
+
+            Some(1)
+        }
+        Some("M  ISO") => {
+            for entry in entries.iter_mut() {
+                entry.mass = None;
+            }
+
+            for (idx, value) in property_pairs(line, entries.len())? {
+                entries[idx].mass = Some(u32::try_from(value).ok()?);
+            }
+
+            Some(1)
+        }
+        // Atom aliases and group abbreviations span a second line.
+        _ if line.starts_with("A  ") || line.starts_with("G  ") => Some(2),
+        Some("S  SKP") => Some(1 + number::<usize>(line, 6..9)?),
+        _ => Some(1),
+    }
+}
+
+fn property_pairs(line: &str, atom_count: usize) -> Option<Vec<(usize, i32)>> {
+    let mut tokens = line.get(6..)?.split_whitespace();
+    let count = tokens.next()?.parse::<usize>().ok()?;
+    let mut result = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let idx = tokens.next()?.parse::<usize>().ok()?;
+        let value = tokens.next()?.parse::<i32>().ok()?;
+
+        if !(1..=atom_count).contains(&idx) {
+            return None;
+        }
+
+        result.push((idx - 1, value));
+    }
+
+    Some(result)
+}
+
+fn parse_data(
+    first: usize,
+    start: usize,
+    lines: &[String],
+) -> Result<Vec<(String, String)>, Error> {
+    let mut data = Vec::new();
+    let mut idx = start;
+
+    while idx < lines.len() {
+        let header = &lines[idx];
+
+        if header.trim().is_empty() {
+            idx += 1;
+
+            continue;
+        }
+
+        let name = header
+            .strip_prefix('>')
+            .and_then(|rest| rest.split_once('<'))
+            .and_then(|(_, rest)| rest.split_once('>'))
+            .map(|(name, _)| name.to_string())
+            .ok_or(Error::Data(first + idx))?;
+        let mut value = Vec::new();
+
+        idx += 1;
+
+        while let Some(line) = lines.get(idx) {
+            if line.is_empty() {
+                break;
+            }
+
+            value.push(line.as_str());
+            idx += 1;
+        }
+
+        data.push((name, value.join("\n")));
+    }
+
+    Ok(data)
+}
+
+/// Returns the atoms of a record, or the index of an atom with an unknown
+/// isotope or a valence below the order of its bonds.
+fn build_atoms<M: ValenceModel>(
+    entries: &[Entry],
+    bonds: &[(usize, usize, Option<BondKind>)],
+    model: &M,
+) -> Result<Vec<Atom>, usize> {
+    let mut aromatic = vec![false; entries.len()];
+
+    for &(sid, tid, kind) in bonds {
+        if kind.is_none() {
+            aromatic[sid] = can_be_aromatic(&entries[sid].symbol);
+            aromatic[tid] = can_be_aromatic(&entries[tid].symbol);
+        }
+    }
+
+    let mut atoms = entries
+        .iter()
+        .zip(&aromatic)
+        .map(|(entry, &aromatic)| {
+            Atom::new(AtomKind::Symbol(match entry.symbol {
+                Symbol::Aliphatic(element) if aromatic => Symbol::Aromatic(element),
+                symbol => symbol,
+            }))
+        })
+        .collect::<Vec<_>>();
+
+    for &(sid, tid, kind) in bonds {
+        let kind = kind.unwrap_or(if aromatic[sid] && aromatic[tid] {
+            BondKind::Elided
+        } else {
+            BondKind::Aromatic
+        });
+
+        atoms[sid].bonds.push(Bond::new(kind, tid));
+        atoms[tid].bonds.push(Bond::new(kind, sid));
+    }
+
+    for (id, (atom, entry)) in atoms.iter_mut().zip(entries).enumerate() {
+        let AtomKind::Symbol(symbol) = atom.kind else {
+            unreachable!("unbracketed atom")
+        };
+
+        if symbol.is_organic()
+            && entry.charge == 0
+            && entry.mass.is_none()
+            && entry.valence.is_none()
+        {
+            continue;
+        }
+
+        let isotope = match (symbol, entry.mass) {
+            (Symbol::Aliphatic(element) | Symbol::Aromatic(element), Some(mass)) => Some(
+                *Isotope::list()
+                    .iter()
+                    .find(|iso| iso.element() == element && iso.mass_number() == mass)
+                    .ok_or(id)?,
+            ),
+            _ => None,
+        };
+
+        atom.kind = AtomKind::Bracket {
+            isotope,
+            symbol,
+            configuration: None,
+            hcount: None,
+            charge: (entry.charge != 0).then(|| Charge::new(entry.charge).expect("charge")),
+            map: None,
+        };
+
+        let hydrogens = match entry.valence {
+            Some(valence) => valence
+                .checked_sub(atom.bonds.iter().map(Bond::order).sum())
+                .ok_or(id)?,
+            None if atom.is_aromatic() => atom.subvalence_with(model).saturating_sub(1),
+            None => atom.subvalence_with(model),
+        };
+
+        if let AtomKind::Bracket { hcount, .. } = &mut atom.kind {
+            *hcount = VirtualHydrogen::try_from(hydrogens)
+                .ok()
+                .filter(|hcount| !hcount.is_zero());
+        }
+    }
+
+    Ok(atoms)
+}
+
+const fn can_be_aromatic(symbol: &Symbol) -> bool {
+    matches!(
+        symbol,
+        Symbol::Aliphatic(
+            Element::B
+                | Element::C
+                | Element::N
+                | Element::O
+                | Element::P
+                | Element::S
+                | Element::As
+                | Element::Se
+                | Element::Si
+                | Element::Te
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Element;
+    use pretty_assertions::assert_eq;
+
+    const ATOM_C: &str = "    0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0";
+    const ATOM_O: &str = "    0.0000    0.0000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0";
+
+    fn methanol(title: &str) -> String {
+        format!(
+            "{title}\n  test\n\n  2  1  0  0  0  0  0  0  0  0999 V2000\n\
+             {ATOM_C}\n{ATOM_O}\n  1  2  1  0\nM  END\n"
+        )
+    }
+
+    fn read_all(input: &str) -> Vec<Result<Record, Error>> {
+        Reader::new(input.as_bytes()).collect()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(read_all(""), vec![])
+    }
+
+    #[test]
+    fn trailing_blank_line() {
+        let input = methanol("a") + "$$$$\n\n";
+
+        assert_eq!(read_all(&input).len(), 1)
+    }
+
+    #[test]
+    fn methanol_record() {
+        let input = methanol("methanol") + "$$$$\n";
+
+        assert_eq!(
+            read_all(&input),
+            vec![Ok(Record {
+                title: "methanol".to_string(),
+                atoms: vec![
+                    Atom {
+                        kind: AtomKind::Symbol(Symbol::Aliphatic(Element::C)),
+                        bonds: vec![Bond::new(BondKind::Elided, 1)]
+                    },
+                    Atom {
+                        kind: AtomKind::Symbol(Symbol::Aliphatic(Element::O)),
+                        bonds: vec![Bond::new(BondKind::Elided, 0)]
+                    }
+                ],
+                data: vec![]
+            })]
+        )
+    }
+
+    #[test]
+    fn missing_terminator() {
+        let input = methanol("methanol");
+
+        assert_eq!(read_all(&input).len(), 1)
+    }
+
+    #[test]
+    fn windows_line_endings() {
+        let input = (methanol("methanol") + ">  <ID>\n42\n\n$$$$\n").replace('\n', "\r\n");
+        let records = read_all(&input);
+        let record = records[0].as_ref().unwrap();
+
+        assert_eq!(record.title, "methanol");
+        assert_eq!(record.get("ID"), Some("42"))
+    }
+
+    #[test]
+    fn data_items() {
+        let input = methanol("a") + ">  <ID>  (1)\n1\n\n> <NOTE>\nfirst\nsecond\n\n$$$$\n";
+        let records = read_all(&input);
+
+        assert_eq!(
+            records[0].as_ref().unwrap().data,
+            vec![
+                ("ID".to_string(), "1".to_string()),
+                ("NOTE".to_string(), "first\nsecond".to_string())
+            ]
+        )
+    }
+
+    #[test]
+    fn malformed_record_continues() {
+        let bad = "bad\n\n\n  2  1  0  0  0  0  0  0  0  0999 V2000\n\
+                   {ATOM_C}\n  1  2  1  0\nM  END\n$$$$\n"
+            .replace("{ATOM_C}", ATOM_C);
+        let input = bad + &methanol("good") + "$$$$\n";
+        let records = read_all(&input);
+
+        assert_eq!(records[0], Err(Error::Atom(6)));
+        assert_eq!(records[1].as_ref().unwrap().title, "good")
+    }
+
+    #[test]
+    fn truncated_record() {
+        assert_eq!(read_all("title\n\n\n"), vec![Err(Error::EndOfRecord(4))])
+    }
+
+    #[test]
+    fn invalid_counts() {
+        assert_eq!(
+            read_all("title\n\n\n  x  1\nM  END\n$$$$\n"),
+            vec![Err(Error::Counts(4))]
+        )
+    }
+
+    #[test]
+    fn v3000() {
+        let input = "t\n\n\n  0  0  0     0  0            999 V3000\nM  END\n$$$$\n";
+
+        assert_eq!(read_all(input), vec![Err(Error::Version(4))])
+    }
+
+    #[test]
+    fn bond_out_of_range() {
+        let input = methanol("a").replace("  1  2  1  0", "  1  3  1  0");
+
+        assert_eq!(read_all(&input), vec![Err(Error::Bond(7))])
+    }
+
+    #[test]
+    fn bad_data_header() {
+        let input = methanol("a") + "ID\n1\n\n$$$$\n";
+
+        assert_eq!(read_all(&input), vec![Err(Error::Data(9))])
+    }
+
+    #[test]
+    fn valence_property() {
+        let input = methanol("a").replace(" C   0  0  0  0  0  0", " C   0  0  0  0  0  3");
+        let records = read_all(&input);
+
+        assert_eq!(
+            records[0].as_ref().unwrap().atoms[0].kind.to_string(),
+            "[CH2]"
+        )
+    }
+
+    #[test]
+    fn valence_below_bonds() {
+        let input =
+            methanol("a").replace(" O   0  0  0  0  0  0", " O   0  0  0  0  0 15") + "$$$$\n";
+
+        assert_eq!(read_all(&input), vec![Err(Error::Atom(6))])
+    }
+
+    #[test]
+    fn charge_out_of_range() {
+        let input = methanol("a").replace("M  END", "M  CHG  1   2  16\nM  END") + "$$$$\n";
+
+        assert_eq!(read_all(&input), vec![Err(Error::Property(8))])
+    }
+
+    #[test]
+    fn charge_property() {
+        let input = methanol("a").replace("M  END", "M  CHG  1   2  -1\nM  END");
+        let records = read_all(&input);
+
+        assert_eq!(
+            records[0].as_ref().unwrap().atoms[1].kind,
+            AtomKind::Bracket {
+                isotope: None,
+                symbol: Symbol::Aliphatic(Element::O),
+                configuration: None,
+                hcount: None,
+                charge: Charge::new(-1),
+                map: None
+            }
+        )
+    }
+
+    #[test]
+    fn model() {
+        let input = methanol("a").replace("M  END", "M  CHG  1   2   1\nM  END");
+        let standard = read_all(&input);
+        let empty = Reader::with_model(input.as_bytes(), ValenceTable::empty()).collect::<Vec<_>>();
+
+        assert_eq!(
+            standard[0].as_ref().unwrap().atoms[1].kind.to_string(),
+            "[OH2+]"
+        );
+        assert_eq!(empty[0].as_ref().unwrap().atoms[1].kind.to_string(), "[O+]")
+    }
+
+    #[test]
+    fn isotope_property() {
+        let input = methanol("a").replace("M  END", "M  ISO  1   1  13\nM  END");
+        let records = read_all(&input);
+
+        assert_eq!(
+            records[0].as_ref().unwrap().atoms[0].kind.to_string(),
+            "[13CH3]"
+        )
+    }
+
+    #[test]
+    fn unknown_isotope() {
+        let input = methanol("a").replace("M  END", "M  ISO  1   2  99\nM  END") + "$$$$\n";
+
+        assert_eq!(read_all(&input), vec![Err(Error::Atom(6))])
+    }
+
+    #[test]
+    fn mass_difference() {
+        let input = methanol("a").replace(" C   0  0", " C   1  0");
+        let records = read_all(&input);
+
+        assert_eq!(
+            records[0].as_ref().unwrap().atoms[0].kind.to_string(),
+            "[13CH3]"
+        )
+    }
+
+    #[test]
+    fn aromatic_bonds() {
+        let mut input = String::from("benzene\n\n\n  6  6  0  0  0  0  0  0  0  0999 V2000\n");
+
+        for _ in 0..6 {
+            input.push_str(ATOM_C);
+            input.push('\n');
+        }
+
+        for idx in 1..=6 {
+            input.push_str(&format!("{idx:>3}{:>3}  4  0\n", idx % 6 + 1));
+        }
+
+        input.push_str("M  END\n$$$$\n");
+
+        let records = read_all(&input);
+        let atoms = &records[0].as_ref().unwrap().atoms;
+
+        assert_eq!(
+            atoms[0].kind,
+            AtomKind::Symbol(Symbol::Aromatic(Element::C))
+        );
+        assert_eq!(
+            atoms[0].bonds,
+            vec![
+                Bond::new(BondKind::Elided, 1),
+                Bond::new(BondKind::Elided, 5)
+            ]
+        )
+    }
+}
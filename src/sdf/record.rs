@@ -0,0 +1,51 @@
+use crate::graph::Atom;
+
+/// A molecule together with the title and data items of one SD file
+/// record.
+#[derive(Debug, PartialEq, Default)]
+pub struct Record {
+    pub title: String,
+    pub atoms: Vec<Atom>,
+    pub data: Vec<(String, String)>,
+}
+
+impl Record {
+    /// Constructs a Record without title or data items.
+    pub const fn new(atoms: Vec<Atom>) -> Self {
+        Self {
+            title: String::new(),
+            atoms,
+            data: Vec::new(),
+        }
+    }
+
+    /// Returns the value of the first data item called `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.data
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_missing() {
+        let record = Record::default();
+
+        assert_eq!(record.get("ID"), None)
+    }
+
+    #[test]
+    fn get_first() {
+        let mut record = Record::default();
+
+        record.data.push(("ID".to_string(), "1".to_string()));
+        record.data.push(("ID".to_string(), "2".to_string()));
+
+        assert_eq!(record.get("ID"), Some("1"))
+    }
+}
@@ -0,0 +1,324 @@
+use std::io::{self, Write};
+
+use super::Record;
+use crate::feature::{AtomKind, BondKind, Symbol};
+use crate::graph::Atom;
+
+/// Writes records in SD file format, one V2000 connection table plus
+/// data items per record.
+///
+/// Coordinates are written as zero. Charges and isotopes are written as
+/// `M  CHG` and `M  ISO` properties, bracket atoms carry their valence,
+/// counting hydrogens, in the valence field, and bonds joining aromatic
+/// atoms are written with the aromatic bond type. Stereo isn't written:
+/// tetrahedral configurations and `Up` and `Down` bonds are discarded.
+///
+/// ```
+/// use yowl::graph::Builder;
+/// use yowl::read::read;
+/// use yowl::sdf::{Reader, Record, Writer};
+///
+/// let mut builder = Builder::default();
+///
+/// read("CC[O-]", &mut builder, None).expect("read");
+///
+/// let mut record = Record::new(builder.build().expect("atoms"));
+///
+/// record.data.push(("ID".to_string(), "7".to_string()));
+///
+/// let mut writer = Writer::new(Vec::new());
+///
+/// writer.write(&record).expect("write");
+///
+/// let output = writer.into_inner();
+/// let records = Reader::new(output.as_slice()).collect::<Vec<_>>();
+///
+/// assert_eq!(records, vec![Ok(record)]);
+/// ```
+#[derive(Debug)]
+pub struct Writer<W> {
+    output: W,
+}
+
+impl<W: Write> Writer<W> {
+    /// Constructs a Writer over `output`.
+    pub const fn new(output: W) -> Self {
+        Self { output }
+    }
+
+    /// Writes `record`, including its `$$$$` terminator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `InvalidInput` if the graph has more than
+    /// 999 atoms or bonds, contains a quadruple, dative or zero-order
+    /// bond, a bracket atom with a valence above 14, or refers to an
+    /// unknown atom. Otherwise, returns any error raised by the output.
+    pub fn write(&mut self, record: &Record) -> io::Result<()> {
+        let bonds = bonds(&record.atoms)?;
+        let valences = record
+            .atoms
+            .iter()
+            .map(valence)
+            .collect::<io::Result<Vec<_>>>()?;
+
+        if record.atoms.len() > 999 || bonds.len() > 999 {
+            return Err(invalid("connection table exceeds 999 entries"));
+        }
+
+        writeln!(self.output, "{}", record.title)?;
+        writeln!(self.output, "  yowl")?;
+        writeln!(self.output)?;
+        writeln!(
+            self.output,
+            "{:>3}{:>3}  0  0  0  0  0  0  0  0999 V2000",
+            record.atoms.len(),
+            bonds.len()
+        )?;
+
+        let mut charges = Vec::new();
+        let mut isotopes = Vec::new();
+
+        for (idx, (atom, valence)) in record.atoms.iter().zip(valences).enumerate() {
+            let symbol = match atom.kind {
+                AtomKind::Symbol(symbol) => symbol,
+                AtomKind::Bracket {
+                    isotope,
+                    symbol,
+                    charge,
+                    ..
+                } => {
+                    if let Some(charge) = charge.filter(|charge| charge.value() != 0) {
+                        charges.push((idx + 1, i32::from(charge.value())));
+                    }
+
+                    if let Some(isotope) = isotope {
+                        isotopes.push((idx + 1, isotope.mass_number()));
+                    }
+
+                    symbol
+                }
+            };
+            let symbol = match symbol {
+                Symbol::Star => "*",
+                Symbol::Aliphatic(element) | Symbol::Aromatic(element) => element.symbol(),
+            };
+
+            writeln!(
+                self.output,
+                "    0.0000    0.0000    0.0000 {symbol:<3} 0  0  0  0  0{valence:>3}  0  0  0  0  0  0"
+            )?;
+        }
+
+        for (sid, tid, order) in bonds {
+            writeln!(self.output, "{:>3}{:>3}{order:>3}  0", sid + 1, tid + 1)?;
+        }
+
+        for chunk in charges.chunks(8) {
+            self.write_property("CHG", chunk)?;
+        }
+
+        for chunk in isotopes.chunks(8) {
+            self.write_property("ISO", chunk)?;
+        }
+
+        writeln!(self.output, "M  END")?;
+
+        for (name, value) in &record.data {
+            writeln!(self.output, ">  <{name}>")?;
+            writeln!(self.output, "{value}")?;
+            writeln!(self.output)?;
+        }
+
+        writeln!(self.output, "$$$$")
+    }
+
+    /// Returns the underlying output.
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+
+    fn write_property<T: std::fmt::Display>(
+        &mut self,
+        name: &str,
+        entries: &[(usize, T)],
+    ) -> io::Result<()> {
+        write!(self.output, "M  {name}{:>3}", entries.len())?;
+
+        for (idx, value) in entries {
+            write!(self.output, " {idx:>3} {value:>3}")?;
+        }
+
+        writeln!(self.output)
+    }
+}
+
+/// Collects each bond once, as source, target and molfile bond type.
+fn bonds(atoms: &[Atom]) -> io::Result<Vec<(usize, usize, u8)>> {
+    let mut result = Vec::new();
+
+    for (sid, atom) in atoms.iter().enumerate() {
+        for bond in &atom.bonds {
+            let target = atoms
+                .get(bond.tid)
+                .ok_or_else(|| invalid("unknown bond target"))?;
+
+            if bond.tid < sid {
+                continue;
+            }
+
+            let order = match bond.kind {
+                BondKind::Elided if atom.is_aromatic() && target.is_aromatic() => 4,
+                BondKind::Elided | BondKind::Single | BondKind::Up | BondKind::Down => 1,
+                BondKind::Double => 2,
+                BondKind::Triple => 3,
+                BondKind::Aromatic => 4,
+                BondKind::Quadruple => return Err(invalid("quadruple bonds are unsupported")),
+                BondKind::DativeRight | BondKind::DativeLeft => {
+                    return Err(invalid("dative bonds are unsupported"))
+                }
+                BondKind::Zero => return Err(invalid("zero-order bonds are unsupported")),
+            };
+
+            result.push((sid, bond.tid, order));
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns the valence field of an atom: zero for an unbracketed atom,
+/// and otherwise its bond order plus hydrogens, with 15 for none.
+fn valence(atom: &Atom) -> io::Result<u8> {
+    let AtomKind::Bracket { hcount, .. } = &atom.kind else {
+        return Ok(0);
+    };
+    let valence = atom
+        .bonds
+        .iter()
+        .fold(hcount.as_ref().map_or(0, u8::from), |sum, bond| {
+            sum.saturating_add(bond.order())
+        });
+
+    match valence {
+        0 => Ok(15),
+        1..=14 => Ok(valence),
+        _ => Err(invalid("valence exceeds 14")),
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Builder;
+    use crate::read::read;
+    use crate::sdf::Reader;
+    use pretty_assertions::assert_eq;
+
+    fn record(smiles: &str) -> Record {
+        let mut builder = Builder::default();
+
+        read(smiles, &mut builder, None).unwrap();
+
+        Record::new(builder.build().unwrap())
+    }
+
+    fn write(record: &Record) -> String {
+        let mut writer = Writer::new(Vec::new());
+
+        writer.write(record).unwrap();
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn methanol() {
+        let mut record = record("CO");
+
+        record.title = "methanol".to_string();
+        record.data.push(("ID".to_string(), "1".to_string()));
+
+        assert_eq!(
+            write(&record),
+            "methanol\n  yowl\n\n  2  1  0  0  0  0  0  0  0  0999 V2000\n\
+             \x20   0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0\n\
+             \x20   0.0000    0.0000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0\n\
+             \x20 1  2  1  0\nM  END\n>  <ID>\n1\n\n$$$$\n"
+        )
+    }
+
+    #[test]
+    fn charge_and_isotope() {
+        let output = write(&record("[13CH3][NH3+]"));
+
+        assert!(output.contains("M  CHG  1   2   1\n"));
+        assert!(output.contains("M  ISO  1   1  13\n"))
+    }
+
+    #[test]
+    fn aromatic_roundtrip() {
+        let record = record("c1ccccc1");
+        let output = write(&record);
+        let records = Reader::new(output.as_bytes()).collect::<Vec<_>>();
+        let atoms = &records[0].as_ref().unwrap().atoms;
+
+        assert!(output.contains("  1  2  4  0\n"));
+
+        for (left, right) in atoms.iter().zip(&record.atoms) {
+            let mut left_tids = left.bonds.iter().map(|b| b.tid).collect::<Vec<_>>();
+            let mut right_tids = right.bonds.iter().map(|b| b.tid).collect::<Vec<_>>();
+
+            left_tids.sort_unstable();
+            right_tids.sort_unstable();
+
+            assert_eq!(left.kind, right.kind);
+            assert_eq!(left_tids, right_tids)
+        }
+    }
+
+    #[test]
+    fn radical_roundtrip() {
+        for smiles in ["[CH2]", "[OH]", "[C]", "C[CH]C", "[NH4+]"] {
+            let record = record(smiles);
+            let output = write(&record);
+            let records = Reader::new(output.as_bytes()).collect::<Vec<_>>();
+
+            assert_eq!(records, vec![Ok(record)], "{smiles}")
+        }
+    }
+
+    #[test]
+    fn valence_field() {
+        assert!(write(&record("[CH2]")).contains(" C   0  0  0  0  0  2  0"));
+        assert!(write(&record("[C]")).contains(" C   0  0  0  0  0 15  0"));
+        assert!(write(&record("C")).contains(" C   0  0  0  0  0  0  0"))
+    }
+
+    #[test]
+    fn stereo_discarded() {
+        assert_eq!(write(&record("F/C=C/F")), write(&record("FC=CF")));
+        assert_eq!(write(&record("C[C@H](O)F")), write(&record("C[CH](O)F")))
+    }
+
+    #[test]
+    fn quadruple() {
+        let mut writer = Writer::new(Vec::new());
+        let error = writer.write(&record("C$C")).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput)
+    }
+
+    #[test]
+    fn dative_and_zero_order() {
+        for smiles in ["N->[Cu]", "[Cu]<-N", "C~C"] {
+            let mut writer = Writer::new(Vec::new());
+            let error = writer.write(&record(smiles)).unwrap_err();
+
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{smiles}")
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use std::ops::Range;
+
+use super::Error;
+use crate::graph::Atom;
+
+/// One SMILES line of a SMILES file.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    /// The one-based line number.
+    pub line_number: usize,
+    /// The line, without its terminator.
+    pub line: String,
+    /// The byte range of the SMILES within `line`.
+    pub span: Range<usize>,
+    /// The text following the SMILES, trimmed of surrounding whitespace.
+    pub title: String,
+    pub atoms: Result<Vec<Atom>, Error>,
+}
+
+impl Entry {
+    /// Returns the SMILES within `line`.
+    pub fn smiles(&self) -> &str {
+        &self.line[self.span.clone()]
+    }
+}
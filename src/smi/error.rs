@@ -0,0 +1,19 @@
+use std::io;
+
+use thiserror::Error;
+
+/// An error that occurs when reading a line of a SMILES file. Cursors
+/// refer to the whole line.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(io::ErrorKind),
+    #[error("Unexpected end of SMILES")]
+    EndOfLine,
+    #[error("Unexpected character: {0}")]
+    Character(usize),
+    #[error("Unmatched ring closure: {0}")]
+    Rnum(usize),
+    #[error("Incompatible ring closure bond: {0}")]
+    Join(usize),
+}
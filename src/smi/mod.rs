@@ -0,0 +1,7 @@
+mod entry;
+mod error;
+mod reader;
+
+pub use entry::Entry;
+pub use error::Error;
+pub use reader::Reader;
@@ -0,0 +1,267 @@
+use std::io::{self, BufRead};
+
+use super::{Entry, Error};
+use crate::graph::{self, Atom, Builder};
+use crate::read::{read, ReadError, Trace};
+
+/// Reads SMILES files, in which each line holds a SMILES string
+/// optionally followed by whitespace and a title. Blank lines and lines
+/// starting with `#` are skipped, and both `\n` and `\r\n` line endings
+/// are accepted.
+///
+/// A line failing to read doesn't stop iteration, nor does a line that
+/// isn't valid UTF-8. Any other I/O error ends it.
+///
+/// ```
+/// use yowl::smi::{Error, Reader};
+///
+/// let file = "# solvents\nCCO ethanol\r\n\nC(C acetone?\n";
+/// let entries = Reader::new(file.as_bytes()).collect::<Vec<_>>();
+///
+/// assert_eq!(entries.len(), 2);
+/// assert_eq!(entries[0].line_number, 2);
+/// assert_eq!(entries[0].smiles(), "CCO");
+/// assert_eq!(entries[0].title, "ethanol");
+/// assert_eq!(entries[0].atoms.as_ref().map(Vec::len), Ok(3));
+/// assert_eq!(entries[1].atoms, Err(Error::Character(3)));
+/// ```
+#[derive(Debug)]
+pub struct Reader<R> {
+    input: R,
+    line_number: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Constructs a Reader over `input`.
+    pub const fn new(input: R) -> Self {
+        Self {
+            input,
+            line_number: 0,
+            done: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+
+        while !self.done {
+            line.clear();
+
+            match self.input.read_line(&mut line) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    self.line_number += 1;
+
+                    if line.ends_with('\n') {
+                        line.pop();
+
+                        if line.ends_with('\r') {
+                            line.pop();
+                        }
+                    }
+
+                    let trimmed = line.trim_start();
+
+                    if trimmed.is_empty() || trimmed.starts_with('#') {
+                        continue;
+                    }
+
+                    return Some(entry(self.line_number, line));
+                }
+                // The offending bytes were consumed, so the next line can
+                // still be read.
+                Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                    self.line_number += 1;
+
+                    return Some(Entry {
+                        line_number: self.line_number,
+                        line: String::new(),
+                        span: 0..0,
+                        title: String::new(),
+                        atoms: Err(Error::Io(error.kind())),
+                    });
+                }
+                Err(error) => {
+                    self.done = true;
+
+                    return Some(Entry {
+                        line_number: self.line_number + 1,
+                        line: String::new(),
+                        span: 0..0,
+                        title: String::new(),
+                        atoms: Err(Error::Io(error.kind())),
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+fn entry(line_number: usize, line: String) -> Entry {
+    let start = line.len() - line.trim_start().len();
+    let end = line[start..]
+        .find(char::is_whitespace)
+        .map_or(line.len(), |end| start + end);
+    let title = line[end..].trim().to_string();
+    let atoms = read_atoms(&line, start, end);
+
+    Entry {
+        line_number,
+        span: start..end,
+        title,
+        atoms,
+        line,
+    }
+}
+
+fn read_atoms(line: &str, start: usize, end: usize) -> Result<Vec<Atom>, Error> {
+    let smiles = &line[start..end];
+
+    // the scanner only reads ASCII
+    if let Some(offset) = smiles.bytes().position(|byte| !byte.is_ascii()) {
+        return Err(Error::Character(start + offset));
+    }
+
+    let mut builder = Builder::default();
+    let mut trace = Trace::default();
+
+    read(smiles, &mut builder, Some(&mut trace)).map_err(|error| match error {
+        // the SMILES is cut off by the whitespace before a title
+        ReadError::EndOfLine if end < line.len() => Error::Character(end),
+        ReadError::EndOfLine => Error::EndOfLine,
+        ReadError::Character(cursor) => Error::Character(start + cursor),
+    })?;
+
+    builder.build().map_err(|error| match error {
+        graph::Error::Rnum(rid) => Error::Rnum(start + trace.rnum(rid).expect("rnum").start),
+        graph::Error::Join(sid, tid) => Error::Join(start + trace.bond(sid, tid).expect("bond")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io;
+
+    fn entries(file: &str) -> Vec<Entry> {
+        Reader::new(file.as_bytes()).collect()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(entries(""), vec![])
+    }
+
+    #[test]
+    fn comments_and_blank_lines() {
+        let entries = entries("# header\n\n   \n\t# indented\nC\n");
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].line_number, 5)
+    }
+
+    #[test]
+    fn crlf_without_title() {
+        let entries = entries("CC\r\nO\r\n");
+
+        assert_eq!(entries[0].line, "CC");
+        assert_eq!(entries[0].smiles(), "CC");
+        assert_eq!(entries[0].title, "");
+        assert_eq!(entries[1].line_number, 2);
+        assert_eq!(entries[1].smiles(), "O")
+    }
+
+    #[test]
+    fn missing_final_newline() {
+        let entries = entries("C methane");
+
+        assert_eq!(entries[0].title, "methane")
+    }
+
+    #[test]
+    fn leading_whitespace_and_long_title() {
+        let entries = entries("  c1ccccc1\t benzene, dry  \n");
+
+        assert_eq!(entries[0].span, 2..10);
+        assert_eq!(entries[0].title, "benzene, dry")
+    }
+
+    #[test]
+    fn character_error_relative_to_line() {
+        let entries = entries("C\n  CC?C name\n");
+
+        assert_eq!(entries[1].atoms, Err(Error::Character(4)))
+    }
+
+    #[test]
+    fn end_of_line_at_title() {
+        let entries = entries("C( name");
+
+        assert_eq!(entries[0].atoms, Err(Error::Character(2)))
+    }
+
+    #[test]
+    fn end_of_line() {
+        let entries = entries("C(\n");
+
+        assert_eq!(entries[0].atoms, Err(Error::EndOfLine))
+    }
+
+    #[test]
+    fn unmatched_rnum() {
+        let entries = entries(" CC1CC ring\n");
+
+        assert_eq!(entries[0].atoms, Err(Error::Rnum(3)))
+    }
+
+    #[test]
+    fn continues_after_error() {
+        let entries = entries("C?\nCC\n");
+
+        assert!(entries[0].atoms.is_err());
+        assert!(entries[1].atoms.is_ok())
+    }
+
+    #[test]
+    fn invalid_utf8_continues() {
+        let entries = Reader::new(&b"C\nC\xffC bad\nO\n"[..]).collect::<Vec<_>>();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1].line_number, 2);
+        assert_eq!(entries[1].atoms, Err(Error::Io(io::ErrorKind::InvalidData)));
+        assert_eq!(entries[2].line_number, 3);
+        assert_eq!(entries[2].smiles(), "O")
+    }
+
+    #[test]
+    fn non_ascii_continues() {
+        let entries = entries(" Cé name\nO\n");
+
+        assert_eq!(entries[0].atoms, Err(Error::Character(2)));
+        assert_eq!(entries[1].smiles(), "O")
+    }
+
+    struct Failing;
+
+    impl io::Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("failed"))
+        }
+    }
+
+    #[test]
+    fn io_error_ends_iteration() {
+        let entries = Reader::new(io::BufReader::new(Failing)).collect::<Vec<_>>();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].atoms, Err(Error::Io(io::ErrorKind::Other)))
+    }
+}
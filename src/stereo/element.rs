@@ -0,0 +1,495 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::feature::{AtomKind, BondKind, Configuration, Symbol, ValenceModel};
+use crate::graph::{bracket, cis_trans, is_isomorphic_with, Atom, Bond};
+use crate::Element;
+
+/// Smallest ring in which a double bond can be trans.
+const MIN_RING: usize = 8;
+
+/// An atom or double bond whose configuration can be specified.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StereoElement {
+    /// An atom with four distinct places around it, for `TH1` or `TH2`.
+    Tetrahedral(usize),
+    /// The double bond between two atoms, the lower first, for `Up` and
+    /// `Down` bonds at either end.
+    DoubleBond(usize, usize),
+}
+
+/// An element with its state, as for `assign`.
+pub(crate) type State = (StereoElement, bool);
+
+impl StereoElement {
+    /// Returns true if atom `id` belongs to this element.
+    pub fn contains(&self, id: usize) -> bool {
+        match *self {
+            Self::Tetrahedral(center) => center == id,
+            Self::DoubleBond(sid, tid) => sid == id || tid == id,
+        }
+    }
+
+    /// Returns the state of this element in `atoms`, as for `assign`, or
+    /// `None` if unspecified.
+    pub(crate) fn state(&self, atoms: &[Atom]) -> Option<bool> {
+        match *self {
+            Self::Tetrahedral(id) => {
+                tetrahedral(&atoms[id]).map(|configuration| configuration == Configuration::TH1)
+            }
+            Self::DoubleBond(sid, tid) => cis_trans(
+                atoms,
+                sid,
+                tid,
+                first_neighbor(atoms, sid, tid),
+                first_neighbor(atoms, tid, sid),
+            ),
+        }
+    }
+}
+
+/// Returns every atom and double bond of `atoms` that could be a
+/// stereocenter by its own neighborhood, in atom order, without regard to
+/// symmetry.
+///
+/// Tetrahedral candidates are aliphatic atoms with four singly bonded
+/// neighbors, at most one of them hydrogen, or P, As, S and Se with three
+/// neighbors and a lone pair. Nitrogen with hydrogen inverts, so isn't
+/// one. Double bond candidates have one or two singly bonded neighbors at
+/// each end, and lie in no ring smaller than eight atoms.
+pub(crate) fn candidates<M: ValenceModel + ?Sized>(
+    atoms: &[Atom],
+    model: &M,
+) -> Vec<StereoElement> {
+    let mut result = Vec::new();
+
+    for (sid, atom) in atoms.iter().enumerate() {
+        if is_tetrahedral(atom, model) {
+            result.push(StereoElement::Tetrahedral(sid))
+        }
+
+        for bond in &atom.bonds {
+            if sid < bond.tid && is_double_bond(atoms, sid, bond, model) {
+                result.push(StereoElement::DoubleBond(sid, bond.tid))
+            }
+        }
+    }
+
+    result
+}
+
+/// Returns a copy of `atoms` with each element set to its paired state:
+/// `TH1` or cis if true, relative to the first neighbors in stored order.
+/// Returns `None` if the directional bonds needed for double bonds
+/// conflict with each other or with those in `atoms`.
+pub(crate) fn assign(atoms: &[Atom], states: &[State]) -> Option<Vec<Atom>> {
+    let mut result = atoms.to_vec();
+    let mut bonds = Vec::new();
+
+    for &(element, state) in states {
+        match element {
+            StereoElement::Tetrahedral(id) => {
+                let atom = &mut result[id];
+                let valence = atom.bonds.iter().map(Bond::order).sum();
+
+                atom.kind = bracket(&atom.kind, valence);
+
+                if let AtomKind::Bracket { configuration, .. } = &mut atom.kind {
+                    *configuration = Some(if state {
+                        Configuration::TH1
+                    } else {
+                        Configuration::TH2
+                    })
+                }
+            }
+            StereoElement::DoubleBond(sid, tid) => bonds.push((sid, tid, state)),
+        }
+    }
+
+    for (sid, tid, kind) in directions(atoms, &bonds)? {
+        set_bond(&mut result, sid, tid, kind)
+    }
+
+    Some(result)
+}
+
+/// Drops each element of `states` whose inversion alone gives the same
+/// molecule, returning the elements left and `atoms` assigned them, or
+/// `None` if the double bonds can't be written together.
+pub(crate) fn reduce<M: ValenceModel + ?Sized>(
+    atoms: &[Atom],
+    mut states: Vec<State>,
+    model: &M,
+) -> Option<(Vec<State>, Vec<Atom>)> {
+    let mut result = assign(atoms, &states)?;
+
+    while let Some(idx) = (0..states.len()).find(|&idx| {
+        let mut inverted = states.clone();
+
+        inverted[idx].1 = !inverted[idx].1;
+
+        assign(atoms, &inverted)
+            .is_some_and(|inverted| is_isomorphic_with(&inverted, &result, true, model))
+    }) {
+        states.remove(idx);
+        result = assign(atoms, &states)?;
+    }
+
+    Some((states, result))
+}
+
+/// Removes tetrahedral configurations from atoms other than those of
+/// `kept`, and directional bonds other than those at the ends of its
+/// double bonds. Atoms left needing no brackets by `model` lose them.
+pub(crate) fn retain<M: ValenceModel + ?Sized>(
+    atoms: &mut [Atom],
+    kept: &[StereoElement],
+    model: &M,
+) {
+    let ends = kept
+        .iter()
+        .filter_map(|element| match *element {
+            StereoElement::DoubleBond(sid, tid) => Some([(sid, tid), (tid, sid)]),
+            StereoElement::Tetrahedral(_) => None,
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+    let at_end = |sid: usize, tid: usize| {
+        ends.iter()
+            .any(|&(end, partner)| end == sid && partner != tid)
+    };
+
+    for sid in 0..atoms.len() {
+        if !kept.contains(&StereoElement::Tetrahedral(sid)) {
+            if let AtomKind::Bracket {
+                configuration: configuration @ Some(Configuration::TH1 | Configuration::TH2),
+                ..
+            } = &mut atoms[sid].kind
+            {
+                *configuration = None;
+                unbracket(&mut atoms[sid], model)
+            }
+        }
+
+        for idx in 0..atoms[sid].bonds.len() {
+            let bond = &atoms[sid].bonds[idx];
+            let tid = bond.tid;
+
+            if bond.is_directional() && !at_end(sid, tid) && !at_end(tid, sid) {
+                set_bond(atoms, sid, tid, BondKind::Elided)
+            }
+        }
+    }
+}
+
+/// Writes `atom` as a symbol if that gives the same hydrogens by `model`.
+fn unbracket<M: ValenceModel + ?Sized>(atom: &mut Atom, model: &M) {
+    let AtomKind::Bracket {
+        isotope: None,
+        symbol,
+        configuration: None,
+        hcount,
+        charge: None,
+        map: None,
+    } = atom.kind
+    else {
+        return;
+    };
+    let unbracketed = Atom {
+        kind: AtomKind::Symbol(symbol),
+        bonds: atom.bonds.clone(),
+    };
+
+    if symbol.is_organic()
+        && unbracketed.suppressed_hydrogens_with(model) == hcount.as_ref().map_or(0, u8::from)
+    {
+        atom.kind = unbracketed.kind
+    }
+}
+
+fn is_tetrahedral<M: ValenceModel + ?Sized>(atom: &Atom, model: &M) -> bool {
+    let element = match atom.kind {
+        AtomKind::Symbol(Symbol::Aliphatic(element))
+        | AtomKind::Bracket {
+            symbol: Symbol::Aliphatic(element),
+            ..
+        } => element,
+        _ => return false,
+    };
+    let hydrogens = usize::from(atom.suppressed_hydrogens_with(model));
+
+    if hydrogens > 1 || (element == Element::N && hydrogens > 0) {
+        return false;
+    }
+
+    if atom.bonds.len() + hydrogens == 4 && atom.bonds.iter().all(is_single) {
+        return true;
+    }
+
+    // the lone pair is the fourth neighbor
+    matches!(element, Element::P | Element::As | Element::S | Element::Se)
+        && atom.bonds.len() == 3
+        && hydrogens == 0
+}
+
+fn is_double_bond<M: ValenceModel + ?Sized>(
+    atoms: &[Atom],
+    sid: usize,
+    bond: &Bond,
+    model: &M,
+) -> bool {
+    let tid = bond.tid;
+
+    bond.kind == BondKind::Double
+        && [(sid, tid), (tid, sid)].iter().all(|&(end, partner)| {
+            let atom = &atoms[end];
+            let count = atom.bonds.len() - 1;
+
+            !atom.is_aromatic()
+                && atom.kind != AtomKind::Symbol(Symbol::Star)
+                && matches!(count, 1 | 2)
+                && count + usize::from(atom.suppressed_hydrogens_with(model)) <= 2
+                && atom
+                    .bonds
+                    .iter()
+                    .all(|bond| bond.tid == partner || is_single(bond))
+        })
+        && !in_small_ring(atoms, sid, tid)
+}
+
+fn is_single(bond: &Bond) -> bool {
+    bond.order() == 1 && !bond.is_aromatic()
+}
+
+/// Returns true if the bond from `sid` to `tid` is in a ring smaller than
+/// `MIN_RING`.
+fn in_small_ring(atoms: &[Atom], sid: usize, tid: usize) -> bool {
+    let mut distances = HashMap::from([(sid, 0)]);
+    let mut queue = VecDeque::from([sid]);
+
+    while let Some(id) = queue.pop_front() {
+        let distance = distances[&id];
+
+        if distance + 2 >= MIN_RING {
+            break;
+        }
+
+        for bond in &atoms[id].bonds {
+            if (id == sid && bond.tid == tid) || distances.contains_key(&bond.tid) {
+                continue;
+            }
+
+            if bond.tid == tid {
+                return true;
+            }
+
+            distances.insert(bond.tid, distance + 1);
+            queue.push_back(bond.tid);
+        }
+    }
+
+    false
+}
+
+fn tetrahedral(atom: &Atom) -> Option<Configuration> {
+    match atom.kind {
+        AtomKind::Bracket {
+            configuration: Some(configuration @ (Configuration::TH1 | Configuration::TH2)),
+            ..
+        } => Some(configuration),
+        _ => None,
+    }
+}
+
+fn set_bond(atoms: &mut [Atom], sid: usize, tid: usize, kind: BondKind) {
+    for (sid, tid, kind) in [(sid, tid, kind), (tid, sid, kind.reverse())] {
+        if let Some(bond) = atoms[sid].bonds.iter_mut().find(|bond| bond.tid == tid) {
+            bond.kind = kind
+        }
+    }
+}
+
+/// Returns the kinds of directional bonds, each from the lower atom, that
+/// give each double bond its geometry: cis if true, relative to the first
+/// neighbor of either end. Bonds already directional keep their kind.
+fn directions(
+    atoms: &[Atom],
+    double_bonds: &[(usize, usize, bool)],
+) -> Option<Vec<(usize, usize, BondKind)>> {
+    let ends = double_bonds
+        .iter()
+        .flat_map(|&(sid, tid, _)| [sid, tid])
+        .collect::<Vec<_>>();
+    let mut parity = Parity::default();
+
+    for &(sid, tid, cis) in double_bonds {
+        let x = reference(atoms, &ends, sid, tid);
+        let y = reference(atoms, &ends, tid, sid);
+        let first = first_neighbor(atoms, sid, tid);
+        let second = first_neighbor(atoms, tid, sid);
+        // sides of the first neighbors, then of the references
+        let cis = cis ^ (x != first) ^ (y != second);
+
+        parity.side_by_side(atoms, (sid, x), (tid, y), !cis)?;
+    }
+
+    // other directional bonds at the same end are on the other side
+    for &(sid, tid, _) in double_bonds {
+        for (end, partner) in [(sid, tid), (tid, sid)] {
+            let x = reference(atoms, &ends, end, partner);
+
+            for bond in &atoms[end].bonds {
+                if bond.tid != partner && bond.tid != x && parity.contains(end, bond.tid) {
+                    parity.side_by_side(atoms, (end, x), (end, bond.tid), true)?;
+                }
+            }
+        }
+    }
+
+    Some(parity.kinds())
+}
+
+fn first_neighbor(atoms: &[Atom], sid: usize, tid: usize) -> usize {
+    atoms[sid]
+        .bonds
+        .iter()
+        .find(|bond| bond.tid != tid)
+        .expect("neighbor")
+        .tid
+}
+
+/// Returns the neighbor of `sid` away from `tid` whose bond sets the side
+/// of the end: the first already directional, then the first that ends
+/// another double bond in `ends`, then the first.
+fn reference(atoms: &[Atom], ends: &[usize], sid: usize, tid: usize) -> usize {
+    let neighbors = atoms[sid]
+        .bonds
+        .iter()
+        .filter(|bond| bond.tid != tid && is_single(bond))
+        .collect::<Vec<_>>();
+
+    neighbors
+        .iter()
+        .find(|bond| bond.is_directional())
+        .or_else(|| neighbors.iter().find(|bond| ends.contains(&bond.tid)))
+        .or_else(|| neighbors.first())
+        .expect("neighbor")
+        .tid
+}
+
+/// Relations between directional bonds, each `Up` or `Down` from the
+/// lower atom, as a union-find with the parity of each node to its parent.
+/// Node 0 is a constant `Up`, and always a root.
+#[derive(Default)]
+struct Parity {
+    bonds: HashMap<(usize, usize), usize>,
+    parents: Vec<(usize, bool)>,
+}
+
+impl Parity {
+    fn contains(&self, sid: usize, tid: usize) -> bool {
+        self.bonds.contains_key(&(sid.min(tid), sid.max(tid)))
+    }
+
+    /// Requires the sides of `x` on `sid` and `y` on `tid`, as read from
+    /// the bonds to them, to differ or not. Returns `None` on a conflict.
+    fn side_by_side(
+        &mut self,
+        atoms: &[Atom],
+        (sid, x): (usize, usize),
+        (tid, y): (usize, usize),
+        differ: bool,
+    ) -> Option<()> {
+        let left = self.node(atoms, sid, x);
+        let right = self.node(atoms, tid, y);
+
+        // a bond read from its higher atom is reversed
+        self.union(left, right, differ ^ (sid > x) ^ (tid > y))
+    }
+
+    /// Returns the node of the bond between `sid` and `tid`, tied to the
+    /// constant if already directional.
+    fn node(&mut self, atoms: &[Atom], sid: usize, tid: usize) -> usize {
+        let key = (sid.min(tid), sid.max(tid));
+
+        if let Some(&node) = self.bonds.get(&key) {
+            return node;
+        }
+
+        if self.parents.is_empty() {
+            self.parents.push((0, false))
+        }
+
+        let node = self.parents.len();
+        let kind = atoms[key.0]
+            .bonds
+            .iter()
+            .find(|bond| bond.tid == key.1)
+            .expect("bond")
+            .kind;
+
+        self.parents.push((node, false));
+        self.bonds.insert(key, node);
+
+        if matches!(kind, BondKind::Up | BondKind::Down) {
+            self.union(node, 0, kind == BondKind::Down)
+                .expect("new node");
+        }
+
+        node
+    }
+
+    fn find(&mut self, node: usize) -> (usize, bool) {
+        let (parent, parity) = self.parents[node];
+
+        if parent == node {
+            return (node, false);
+        }
+
+        let (root, rest) = self.find(parent);
+
+        self.parents[node] = (root, parity != rest);
+
+        (root, parity != rest)
+    }
+
+    fn union(&mut self, left: usize, right: usize, differ: bool) -> Option<()> {
+        let (left_root, left_parity) = self.find(left);
+        let (right_root, right_parity) = self.find(right);
+
+        if left_root == right_root {
+            return (left_parity ^ right_parity == differ).then_some(());
+        }
+
+        // the constant stays a root
+        let (root, child) = if right_root == 0 {
+            (right_root, left_root)
+        } else {
+            (left_root, right_root)
+        };
+
+        self.parents[child] = (root, left_parity ^ right_parity ^ differ);
+
+        Some(())
+    }
+
+    fn kinds(&mut self) -> Vec<(usize, usize, BondKind)> {
+        let mut bonds = self
+            .bonds
+            .iter()
+            .map(|(&key, &node)| (key, node))
+            .collect::<Vec<_>>();
+
+        bonds.sort_unstable();
+
+        bonds
+            .into_iter()
+            .map(|((sid, tid), node)| {
+                // roots are Up, as is the constant
+                let (_, down) = self.find(node);
+
+                (sid, tid, if down { BondKind::Down } else { BondKind::Up })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,7 @@
+mod element;
+mod perception;
+mod stereoisomers;
+
+pub use element::StereoElement;
+pub use perception::{stereo_elements, stereo_elements_with, strip_spurious, strip_spurious_with};
+pub use stereoisomers::{StereoisomerOptions, Stereoisomers};
@@ -0,0 +1,376 @@
+use std::collections::VecDeque;
+
+use super::element::{candidates, reduce, retain, StereoElement};
+use crate::feature::{standard_valences, AtomKind, Symbol, ValenceModel};
+use crate::graph::{classes, Atom};
+use crate::Element;
+
+/// Returns the atoms and double bonds of `atoms` that can be stereogenic,
+/// whether or not their configuration is given, in atom order.
+///
+/// An element is stereogenic if the places around it, or around each end
+/// of a double bond, are in different symmetry classes. It can also be if
+/// just two places are alike, but their branches hold another element
+/// that can be, as do the centers of 1,4-disubstituted cyclohexanes and
+/// the pseudoasymmetric center of pentane-2,3,4-triol.
+///
+/// ```
+/// use yowl::graph::Builder;
+/// use yowl::read::read;
+/// use yowl::stereo::{stereo_elements, StereoElement};
+///
+/// let mut builder = Builder::default();
+///
+/// read("CC1CCC(C)CC1C=CC", &mut builder, None).expect("read");
+///
+/// let atoms = builder.build().expect("atoms");
+///
+/// assert_eq!(
+///     stereo_elements(&atoms),
+///     vec![
+///         StereoElement::Tetrahedral(1),
+///         StereoElement::Tetrahedral(4),
+///         StereoElement::Tetrahedral(7),
+///         StereoElement::DoubleBond(8, 9)
+///     ]
+/// );
+/// ```
+pub fn stereo_elements(atoms: &[Atom]) -> Vec<StereoElement> {
+    stereo_elements_with(atoms, standard_valences())
+}
+
+/// Performs `stereo_elements`, counting implicit hydrogens by the targets
+/// of `model`.
+pub fn stereo_elements_with<M: ValenceModel + ?Sized>(
+    atoms: &[Atom],
+    model: &M,
+) -> Vec<StereoElement> {
+    let (classes, _) = classes(atoms, &[], model);
+    let mut result = candidates(atoms, model);
+
+    // elements that can't be stereogenic may leave others that can't
+    loop {
+        let next = result
+            .iter()
+            .copied()
+            .filter(|&element| is_stereogenic(atoms, &classes, element, &result, model))
+            .collect::<Vec<_>>();
+
+        if next.len() == result.len() {
+            return result;
+        }
+
+        result = next;
+    }
+}
+
+/// Removes tetrahedral configurations and `Up` and `Down` bonds that
+/// don't specify a stereogenic element, such as those of `[C@H](C)(C)C`
+/// or `F/C=C(/F)F`. Atoms left needing no brackets lose them. Other
+/// configurations are kept.
+///
+/// A configuration is kept only if inverting it alone gives a different
+/// molecule, given the others kept, so a mark on one center of
+/// 1,4-dimethylcyclohexane is removed while marks on both are kept.
+///
+/// ```
+/// use yowl::graph::Builder;
+/// use yowl::read::read;
+/// use yowl::stereo::strip_spurious;
+/// use yowl::walk::walk;
+/// use yowl::write::Writer;
+///
+/// let mut builder = Builder::default();
+///
+/// read("C[C@H](C)/C=C/C(F)=C/F", &mut builder, None).expect("read");
+///
+/// let mut atoms = builder.build().expect("atoms");
+/// let mut writer = Writer::default();
+///
+/// strip_spurious(&mut atoms);
+/// walk(atoms, &mut writer).expect("walk");
+///
+/// assert_eq!(writer.write(), "CC(C)/C=C/C(F)=C/F");
+/// ```
+pub fn strip_spurious(atoms: &mut [Atom]) {
+    strip_spurious_with(atoms, standard_valences())
+}
+
+/// Performs `strip_spurious`, counting implicit hydrogens by the targets
+/// of `model`.
+pub fn strip_spurious_with<M: ValenceModel + ?Sized>(atoms: &mut [Atom], model: &M) {
+    let states = candidates(atoms, model)
+        .into_iter()
+        .filter_map(|element| Some((element, element.state(atoms)?)))
+        .collect::<Vec<_>>();
+    let mut base = atoms.to_vec();
+
+    retain(&mut base, &[], model);
+
+    // double bonds that can't be written together are kept as given
+    let kept = match reduce(&base, states.clone(), model) {
+        Some((kept, _)) => kept,
+        None => states,
+    };
+
+    retain(
+        atoms,
+        &kept
+            .into_iter()
+            .map(|(element, _)| element)
+            .collect::<Vec<_>>(),
+        model,
+    )
+}
+
+/// A place around a stereogenic atom.
+#[derive(PartialEq, Eq)]
+enum Place {
+    Neighbor(usize, usize),
+    Hydrogen,
+    LonePair,
+}
+
+impl Place {
+    fn new(atoms: &[Atom], classes: &[usize], id: usize) -> Self {
+        match atoms[id].kind {
+            AtomKind::Bracket {
+                symbol: Symbol::Aliphatic(Element::H),
+                isotope: None,
+                charge: None,
+                ..
+            } => Self::Hydrogen,
+            _ => Self::Neighbor(id, classes[id]),
+        }
+    }
+
+    fn class(&self) -> Option<usize> {
+        match self {
+            Self::Neighbor(_, class) => Some(*class),
+            Self::Hydrogen => Some(usize::MAX),
+            Self::LonePair => None,
+        }
+    }
+}
+
+fn is_stereogenic<M: ValenceModel + ?Sized>(
+    atoms: &[Atom],
+    classes: &[usize],
+    element: StereoElement,
+    possible: &[StereoElement],
+    model: &M,
+) -> bool {
+    let ends = match element {
+        StereoElement::Tetrahedral(id) => vec![(id, None, 4)],
+        StereoElement::DoubleBond(sid, tid) => vec![(sid, Some(tid), 2), (tid, Some(sid), 2)],
+    };
+
+    ends.into_iter().all(|(id, partner, count)| {
+        let mut places = atoms[id]
+            .bonds
+            .iter()
+            .filter(|bond| Some(bond.tid) != partner)
+            .map(|bond| Place::new(atoms, classes, bond.tid))
+            .collect::<Vec<_>>();
+
+        places.extend((0..atoms[id].suppressed_hydrogens_with(model)).map(|_| Place::Hydrogen));
+
+        while places.len() < count {
+            places.push(Place::LonePair)
+        }
+
+        let alike = places
+            .iter()
+            .filter(|place| {
+                places
+                    .iter()
+                    .filter(|other| other.class() == place.class())
+                    .count()
+                    > 1
+            })
+            .collect::<Vec<_>>();
+
+        match alike[..] {
+            [] => true,
+            [Place::Neighbor(nid, _), Place::Neighbor(..)] => {
+                holds_other(atoms, *nid, element, possible)
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Returns true if the branch from atom `start`, away from `element`,
+/// holds an atom of another element of `possible`.
+fn holds_other(
+    atoms: &[Atom],
+    start: usize,
+    element: StereoElement,
+    possible: &[StereoElement],
+) -> bool {
+    let mut seen = vec![false; atoms.len()];
+    let mut queue = VecDeque::from([start]);
+
+    seen[start] = true;
+
+    while let Some(id) = queue.pop_front() {
+        if possible
+            .iter()
+            .any(|other| *other != element && other.contains(id))
+        {
+            return true;
+        }
+
+        for bond in &atoms[id].bonds {
+            if !seen[bond.tid] && !element.contains(bond.tid) {
+                seen[bond.tid] = true;
+                queue.push_back(bond.tid);
+            }
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::ValenceTable;
+    use crate::testing::atoms;
+    use crate::walk::walk;
+    use crate::write::Writer;
+    use pretty_assertions::assert_eq;
+
+    fn elements(smiles: &str) -> Vec<StereoElement> {
+        stereo_elements(&atoms(smiles))
+    }
+
+    fn strip(smiles: &str) -> String {
+        let mut atoms = atoms(smiles);
+        let mut writer = Writer::default();
+
+        strip_spurious(&mut atoms);
+        walk(atoms, &mut writer).unwrap();
+
+        writer.write()
+    }
+
+    #[test]
+    fn centers() {
+        assert_eq!(elements("CC(O)CC"), [StereoElement::Tetrahedral(1)]);
+        assert_eq!(elements("CC(C)O"), []);
+        assert_eq!(elements("C(C)(C)C"), []);
+        assert_eq!(elements("OC(O)([2H])C"), []);
+        assert_eq!(elements("OC([H])([2H])C"), [StereoElement::Tetrahedral(1)]);
+        assert_eq!(elements("CS(=O)CC"), [StereoElement::Tetrahedral(1)]);
+        assert_eq!(elements("CN(C)CC"), [])
+    }
+
+    #[test]
+    fn with_model() {
+        let mut table = ValenceTable::default();
+
+        table.insert(Element::P, 0, vec![5]);
+
+        assert_eq!(elements("FP(Cl)Br"), [StereoElement::Tetrahedral(1)]);
+        assert_eq!(stereo_elements_with(&atoms("FP(Cl)Br"), &table), [])
+    }
+
+    #[test]
+    fn ring_centers() {
+        assert_eq!(
+            elements("CC1CCC(C)CC1"),
+            [StereoElement::Tetrahedral(1), StereoElement::Tetrahedral(4)]
+        );
+        assert_eq!(elements("CC1CCCCC1"), []);
+        assert_eq!(elements("C1CC1"), []);
+        assert_eq!(
+            elements("OC1CCC(CC1)=CC"),
+            [
+                StereoElement::Tetrahedral(1),
+                StereoElement::DoubleBond(4, 7)
+            ]
+        )
+    }
+
+    #[test]
+    fn pseudoasymmetric() {
+        assert_eq!(
+            elements("CC(O)C(O)C(O)C"),
+            [
+                StereoElement::Tetrahedral(1),
+                StereoElement::Tetrahedral(3),
+                StereoElement::Tetrahedral(5)
+            ]
+        );
+        assert_eq!(elements("CCC(O)CC"), [])
+    }
+
+    #[test]
+    fn double_bonds() {
+        assert_eq!(elements("CC=CC"), [StereoElement::DoubleBond(1, 2)]);
+        assert_eq!(elements("CC=C(C)C"), []);
+        assert_eq!(elements("C=CC"), []);
+        assert_eq!(elements("CC=NO"), [StereoElement::DoubleBond(1, 2)]);
+        assert_eq!(elements("C1CCC=CC1"), []);
+        assert_eq!(elements("c1ccccc1"), [])
+    }
+
+    #[test]
+    fn strip_centers() {
+        assert_eq!(strip("[C@H](C)(C)C"), "C(C)(C)C");
+        assert_eq!(strip("C[C@H](O)CC"), "C[C@H](O)CC");
+        assert_eq!(strip("C[C@@H]1CCCCC1"), "CC(CCCCC1)1");
+        assert_eq!(strip("[C@H2](O)C"), "C(O)C");
+        assert_eq!(strip("c1cc[c@H]cc1"), "c(ccccc1)1")
+    }
+
+    #[test]
+    fn strip_keeps_needed_brackets() {
+        assert_eq!(strip("[CH3][C@H](C)C"), "[CH3]C(C)C");
+        assert_eq!(strip("[13C@H](C)(C)C"), "[13CH](C)(C)C");
+        assert_eq!(strip("[C@H:1](C)(C)C"), "[CH:1](C)(C)C")
+    }
+
+    #[test]
+    fn strip_with_model() {
+        let mut atoms = atoms("[C@H](C)(C)C");
+        let mut table = ValenceTable::default();
+        let mut writer = Writer::default();
+
+        table.insert(Element::C, 0, vec![5]);
+        strip_spurious_with(&mut atoms, &table);
+        walk(atoms, &mut writer).unwrap();
+
+        assert_eq!(writer.write(), "[CH](C)(C)C")
+    }
+
+    #[test]
+    fn strip_ring_centers() {
+        assert_eq!(strip("C[C@H]1CC[C@@H](C)CC1"), "C[C@H](CC[C@@H](CC1)C)1");
+        assert_eq!(strip("C[C@H]1CCC(C)CC1"), "CC(CCC(CC1)C)1")
+    }
+
+    #[test]
+    fn strip_pseudoasymmetric() {
+        // the middle center is r or s only between unlike neighbors
+        assert_eq!(
+            strip("C[C@@H](O)[C@H](O)[C@@H](O)C"),
+            "C[C@@H](O)[C@H](O)[C@@H](O)C"
+        );
+        assert_eq!(
+            strip("C[C@@H](O)[C@H](O)[C@H](O)C"),
+            "C[C@@H](O)C(O)[C@H](O)C"
+        )
+    }
+
+    #[test]
+    fn strip_double_bonds() {
+        assert_eq!(strip("F/C=C/F"), "F/C=C/F");
+        assert_eq!(strip("F/C=C(/F)F"), "FC=C(F)F");
+        assert_eq!(strip("C/C(C)=C/F"), "CC(C)=CF");
+        assert_eq!(strip("C1CC/C=C/CC1"), "C(CCC=CCC1)1");
+        assert_eq!(strip("C/C=C/C=C(/C)C"), "C/C=C/C=C(C)C");
+        assert_eq!(strip("C/C"), "CC")
+    }
+}
@@ -0,0 +1,322 @@
+use super::element::{reduce, retain, StereoElement};
+use super::stereo_elements;
+use crate::feature::standard_valences;
+use crate::graph::{is_isomorphic, Atom};
+
+/// Options for `Stereoisomers`. The default enumerates every stereocenter
+/// and stereogenic double bond, replacing configurations given, without a
+/// limit.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct StereoisomerOptions {
+    /// Keeps the configurations given, enumerating only the others.
+    pub unassigned_only: bool,
+    /// Stops after this many stereoisomers.
+    pub limit: Option<usize>,
+}
+
+/// Enumerates the stereoisomers of a graph, lazily.
+///
+/// Atoms and double bonds from `stereo_elements` are given `TH1` or `TH2`,
+/// or `Up` and `Down` bonds at both ends, in every combination. Elements
+/// whose inversion alone gives the same molecule, such as the middle
+/// center of pentane-2,3,4-triol between like neighbors, are left
+/// unspecified, and stereoisomers already yielded up to symmetry are
+/// skipped, so meso forms appear once. Memory and time grow with the
+/// number of stereoisomers yielded.
+///
+/// A graph without stereo elements yields itself.
+///
+/// ```
+/// use yowl::graph::Builder;
+/// use yowl::read::read;
+/// use yowl::stereo::{StereoisomerOptions, Stereoisomers};
+/// use yowl::write::Writer;
+/// use yowl::walk::walk;
+///
+/// let mut builder = Builder::default();
+///
+/// read("OC(=O)C(O)C(O)C(=O)O", &mut builder, None).expect("read");
+///
+/// let atoms = builder.build().expect("atoms");
+/// let isomers = Stereoisomers::new(&atoms, StereoisomerOptions::default())
+///     .map(|isomer| {
+///         let mut writer = Writer::default();
+///
+///         walk(isomer, &mut writer).expect("walk");
+///
+///         writer.write()
+///     })
+///     .collect::<Vec<_>>();
+///
+/// // meso, (R,R) and (S,S) tartaric acid
+/// assert_eq!(
+///     isomers,
+///     vec![
+///         "OC(=O)[C@H](O)[C@H](O)C(=O)O",
+///         "OC(=O)[C@H](O)[C@@H](O)C(=O)O",
+///         "OC(=O)[C@@H](O)[C@H](O)C(=O)O"
+///     ]
+/// );
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Stereoisomers {
+    atoms: Vec<Atom>,
+    elements: Vec<StereoElement>,
+    states: Option<Vec<bool>>,
+    found: Vec<Vec<Atom>>,
+    limit: Option<usize>,
+}
+
+impl Stereoisomers {
+    /// Constructs a Stereoisomers over `atoms`.
+    pub fn new(atoms: &[Atom], options: StereoisomerOptions) -> Self {
+        let mut base = atoms.to_vec();
+        let mut elements = stereo_elements(atoms);
+
+        if options.unassigned_only {
+            elements.retain(|element| element.state(atoms).is_none())
+        } else {
+            retain(&mut base, &[], standard_valences())
+        }
+
+        Self {
+            atoms: base,
+            states: (!atoms.is_empty()).then(|| vec![false; elements.len()]),
+            elements,
+            found: Vec::new(),
+            limit: options.limit,
+        }
+    }
+
+    /// Returns the graph with elements set to `states`, without those
+    /// whose inversion gives the same molecule, or `None` if the double
+    /// bonds can't be written together.
+    fn isomer(&self, states: &[bool]) -> Option<Vec<Atom>> {
+        let states = self
+            .elements
+            .iter()
+            .copied()
+            .zip(states.iter().copied())
+            .collect();
+
+        reduce(&self.atoms, states, standard_valences()).map(|(_, atoms)| atoms)
+    }
+}
+
+impl Iterator for Stereoisomers {
+    type Item = Vec<Atom>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.limit.is_some_and(|limit| self.found.len() >= limit) {
+            return None;
+        }
+
+        while let Some(states) = self.states.take() {
+            self.states = successor(&states);
+
+            let Some(isomer) = self.isomer(&states) else {
+                continue;
+            };
+
+            if self
+                .found
+                .iter()
+                .any(|found| is_isomorphic(found, &isomer, true))
+            {
+                continue;
+            }
+
+            self.found.push(isomer.clone());
+
+            return Some(isomer);
+        }
+
+        None
+    }
+}
+
+/// Returns the combination of states after `states`, counting in binary
+/// with the last element fastest, or `None` after the last.
+fn successor(states: &[bool]) -> Option<Vec<bool>> {
+    let mut result = states.to_vec();
+
+    for state in result.iter_mut().rev() {
+        *state = !*state;
+
+        if *state {
+            return Some(result);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cip::{centers, double_bonds};
+    use crate::testing::atoms;
+    use crate::walk::walk;
+    use crate::write::Writer;
+    use pretty_assertions::assert_eq;
+
+    fn write(atoms: Vec<Atom>) -> String {
+        let mut writer = Writer::default();
+
+        walk(atoms, &mut writer).unwrap();
+
+        writer.write()
+    }
+
+    fn isomers(smiles: &str, options: StereoisomerOptions) -> Vec<String> {
+        Stereoisomers::new(&atoms(smiles), options)
+            .map(write)
+            .collect()
+    }
+
+    fn count(smiles: &str) -> usize {
+        Stereoisomers::new(&atoms(smiles), StereoisomerOptions::default()).count()
+    }
+
+    #[test]
+    fn no_elements() {
+        assert_eq!(isomers("CCO", StereoisomerOptions::default()), ["CCO"]);
+        assert_eq!(
+            Stereoisomers::new(&[], StereoisomerOptions::default()).count(),
+            0
+        )
+    }
+
+    #[test]
+    fn one_center() {
+        assert_eq!(
+            isomers("CC(O)CC", StereoisomerOptions::default()),
+            ["C[C@H](O)CC", "C[C@@H](O)CC"]
+        );
+
+        let descriptors = Stereoisomers::new(&atoms("CC(O)CC"), StereoisomerOptions::default())
+            .map(|isomer| centers(&isomer)[0].descriptor.unwrap().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(descriptors, ["S", "R"])
+    }
+
+    #[test]
+    fn symmetric_center() {
+        assert_eq!(
+            isomers("CC(C)O", StereoisomerOptions::default()),
+            ["CC(C)O"]
+        );
+        assert_eq!(
+            isomers("[C@H](C)(C)C", StereoisomerOptions::default()),
+            ["C(C)(C)C"]
+        )
+    }
+
+    #[test]
+    fn lone_pair() {
+        assert_eq!(count("CS(=O)CC"), 2);
+        assert_eq!(count("CS(=O)C"), 1);
+        assert_eq!(count("CN(O)CC"), 1)
+    }
+
+    #[test]
+    fn meso() {
+        // pentane-2,3,4-triol has two meso forms and a pair of enantiomers
+        assert_eq!(count("CC(O)C(O)C(O)C"), 4);
+        assert_eq!(count("OC(=O)C(O)C(O)C(=O)O"), 3)
+    }
+
+    #[test]
+    fn ring_centers() {
+        // cis and trans 1,4-dimethylcyclohexane
+        assert_eq!(count("CC1CCC(C)CC1"), 2);
+        assert_eq!(count("CC1CCCCC1"), 1);
+        assert_eq!(count("CC1CCC(O)C(C)C1"), 8)
+    }
+
+    #[test]
+    fn double_bond() {
+        let isomers = isomers("CC=CC", StereoisomerOptions::default());
+        let descriptors = isomers
+            .iter()
+            .map(|smiles| {
+                double_bonds(&atoms(smiles))[0]
+                    .descriptor
+                    .unwrap()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(isomers, ["C/C=C/C", "C/C=C\\C"]);
+        assert_eq!(descriptors, ["E", "Z"]);
+        assert_eq!(count("CC=C(C)C"), 1);
+        assert_eq!(count("C1CCC=CC1"), 1);
+        assert_eq!(count("C1CCCCCC=C1"), 2);
+        assert_eq!(count("CC=NO"), 2)
+    }
+
+    #[test]
+    fn conjugated() {
+        // hexa-2,4-diene is EE, ZZ or EZ, and octatriene has six
+        assert_eq!(
+            isomers("CC=CC=CC", StereoisomerOptions::default()),
+            ["C/C=C/C=C/C", "C/C=C/C=C\\C", "C/C=C\\C=C/C"]
+        );
+        assert_eq!(count("CC=CC=CC=CC"), 6);
+
+        for isomer in Stereoisomers::new(&atoms("CC=CC=CC=CC"), StereoisomerOptions::default()) {
+            let descriptors = double_bonds(&isomer);
+
+            assert_eq!(descriptors.len(), 3);
+            assert!(descriptors.iter().all(|bond| bond.descriptor.is_some()))
+        }
+    }
+
+    #[test]
+    fn mixed() {
+        assert_eq!(count("CC=CC(O)CC"), 4);
+        assert_eq!(count("CC(O)C=CC(O)C"), 6)
+    }
+
+    #[test]
+    fn unassigned_only() {
+        let options = StereoisomerOptions {
+            unassigned_only: true,
+            ..StereoisomerOptions::default()
+        };
+
+        assert_eq!(
+            isomers("C[C@@H](O)C(F)C", options),
+            ["C[C@@H](O)[C@H](F)C", "C[C@@H](O)[C@@H](F)C"]
+        );
+        assert_eq!(count("C[C@@H](O)C(F)C"), 4);
+        assert_eq!(
+            isomers("F/C=C/C=CC", options),
+            ["F/C=C/C=C/C", "F/C=C/C=C\\C"]
+        );
+        assert_eq!(isomers("F/C=CF", options), ["F/C=C/F", "F/C=C\\F"])
+    }
+
+    #[test]
+    fn replaces_given() {
+        assert_eq!(
+            isomers("C[C@@H](O)CC", StereoisomerOptions::default()),
+            ["C[C@H](O)CC", "C[C@@H](O)CC"]
+        );
+        assert_eq!(count("F/C=C/F"), 2)
+    }
+
+    #[test]
+    fn limit() {
+        let options = StereoisomerOptions {
+            limit: Some(2),
+            ..StereoisomerOptions::default()
+        };
+
+        assert_eq!(
+            Stereoisomers::new(&atoms("CC(O)C(O)C(O)C"), options).count(),
+            2
+        )
+    }
+}
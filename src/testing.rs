@@ -0,0 +1,11 @@
+use crate::graph::{Atom, Builder};
+use crate::read::read;
+
+/// Returns the atoms of `smiles`, which must be valid.
+pub(crate) fn atoms(smiles: &str) -> Vec<Atom> {
+    let mut builder = Builder::default();
+
+    read(smiles, &mut builder, None).unwrap();
+
+    builder.build().unwrap()
+}
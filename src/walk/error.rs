@@ -3,6 +3,7 @@ use thiserror::Error;
 /// An error resulting from depth-first traversal of a graph
 /// representation.
 #[derive(Debug, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Error {
     #[error("A bond is missing its counterpart: ({0}, {1})")]
     HalfBond(usize, usize),
@@ -14,4 +15,10 @@ pub enum Error {
     IncompatibleBond(usize, usize),
     #[error("A loop was detected at node: {0}")]
     Loop(usize),
+    #[error("The root is unknown: {0}")]
+    UnknownRoot(usize),
+    #[error("Neighbor ordering is not a permutation at node: {0}")]
+    Order(usize),
+    #[error("A configuration other than tetrahedral is reordered at node: {0}")]
+    Configuration(usize),
 }
@@ -36,3 +36,21 @@ pub trait Follower {
     /// Panics given depth exceeds the length of the current path.
     fn pop(&mut self, depth: usize);
 }
+
+impl<F: Follower + ?Sized> Follower for &mut F {
+    fn root(&mut self, root: AtomKind) {
+        (**self).root(root);
+    }
+
+    fn extend(&mut self, bond_kind: BondKind, atom_kind: AtomKind) {
+        (**self).extend(bond_kind, atom_kind);
+    }
+
+    fn join(&mut self, bond_kind: BondKind, rnum: Rnum) {
+        (**self).join(bond_kind, rnum);
+    }
+
+    fn pop(&mut self, depth: usize) {
+        (**self).pop(depth);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::feature::AtomKind;
+use crate::graph::{Atom, Bond};
+
+/// An adjacency representation that `walk` can traverse. Atoms are
+/// identified by index, from zero up to the atom count.
+pub trait Graph {
+    /// Returns the number of atoms.
+    fn atom_count(&self) -> usize;
+
+    /// Returns the kind of atom `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of range.
+    fn kind(&self, id: usize) -> AtomKind;
+
+    /// Returns the bonds of atom `id`, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of range.
+    fn bonds(&self, id: usize) -> &[Bond];
+}
+
+impl Graph for [Atom] {
+    fn atom_count(&self) -> usize {
+        self.len()
+    }
+
+    fn kind(&self, id: usize) -> AtomKind {
+        self[id].kind
+    }
+
+    fn bonds(&self, id: usize) -> &[Bond] {
+        &self[id].bonds
+    }
+}
+
+impl Graph for Vec<Atom> {
+    fn atom_count(&self) -> usize {
+        self.len()
+    }
+
+    fn kind(&self, id: usize) -> AtomKind {
+        self[id].kind
+    }
+
+    fn bonds(&self, id: usize) -> &[Bond] {
+        &self[id].bonds
+    }
+}
+
+impl<G: Graph + ?Sized> Graph for &G {
+    fn atom_count(&self) -> usize {
+        (**self).atom_count()
+    }
+
+    fn kind(&self, id: usize) -> AtomKind {
+        (**self).kind(id)
+    }
+
+    fn bonds(&self, id: usize) -> &[Bond] {
+        (**self).bonds(id)
+    }
+}
@@ -1,7 +1,10 @@
 mod error;
 mod follower;
+mod graph;
 mod walker;
 
 pub use error::Error;
 pub use follower::Follower;
-pub use walker::walk;
+pub use graph::Graph;
+pub(crate) use walker::{is_odd, walk_steps, Step};
+pub use walker::{walk, walk_with};
@@ -1,81 +1,314 @@
-use super::{Error, Follower};
+use super::{Error, Follower, Graph};
 use crate::{
-    feature::BondKind,
-    graph::{Atom, Bond, JoinPool},
+    feature::{AtomKind, BondKind, Configuration},
+    graph::{Bond, JoinPool},
 };
 
 /// Performs a full SMILES depth-first search (DFS) `graph` of atoms, emitting SMILES via [`Follower`].
-pub fn walk<F: Follower>(graph: Vec<Atom>, follower: &mut F) -> Result<(), Error> {
-    SmilesWalker::new(graph, follower).traverse()
+pub fn walk<G: Graph, F: Follower>(graph: G, follower: &mut F) -> Result<(), Error> {
+    SmilesWalker::new(&graph, follower, &[], &mut |_, _| {}, None).traverse()
+}
+
+/// Performs `walk`, but starting components at `roots` and visiting
+/// neighbors in the order set by `order`.
+///
+/// Components are walked from each of `roots` in turn, skipping roots in a
+/// component already walked, and then from the lowest-numbered atom of
+/// each remaining component. Before walking onward from an atom, `order`
+/// is called with its identifier and the identifiers of its neighbors
+/// other than the one it was reached from, and may reorder them.
+/// Tetrahedral configurations are adjusted to match the order in which
+/// neighbors are written. Other configurations can't be, so writing the
+/// neighbors of such an atom out of stored order is an error.
+///
+/// ```
+/// use yowl::graph::Builder;
+/// use yowl::read::read;
+/// use yowl::walk::walk_with;
+/// use yowl::write::Writer;
+///
+/// let mut builder = Builder::default();
+///
+/// read("N[C@@H](C)C(=O)O", &mut builder, None).expect("read");
+///
+/// let atoms = builder.build().expect("atoms");
+/// let mut writer = Writer::default();
+///
+/// walk_with(&atoms, &mut writer, &[5], |_, neighbors| neighbors.reverse())
+///     .expect("walk");
+///
+/// assert_eq!(writer.write(), "OC(=O)[C@H](C)N");
+/// ```
+pub fn walk_with<G: Graph, F: Follower, O: FnMut(usize, &mut [usize])>(
+    graph: G,
+    follower: &mut F,
+    roots: &[usize],
+    mut order: O,
+) -> Result<(), Error> {
+    SmilesWalker::new(&graph, follower, roots, &mut order, None).traverse()
+}
+
+/// Performs `walk`, also recording in `steps` the atom identifiers behind
+/// each `root`, `extend` and `join` sent to `follower`.
+pub(crate) fn walk_steps<G: Graph, F: Follower>(
+    graph: G,
+    follower: &mut F,
+    steps: &mut Vec<Step>,
+) -> Result<(), Error> {
+    SmilesWalker::new(&graph, follower, &[], &mut |_, _| {}, Some(steps)).traverse()
+}
+
+/// The atom identifiers behind a `Follower` call, other than `pop`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Step {
+    Root(usize),
+    Extend(usize, usize),
+    Join(usize, usize),
 }
 
 /// Encapsulates all global state for a SMILES traversal.
-struct SmilesWalker<'a, F: Follower> {
-    /// Remaining atoms to visit. `None` means already consumed.
-    atoms: Vec<Option<Atom>>,
+struct SmilesWalker<'a, G: Graph + ?Sized, F: Follower, O: FnMut(usize, &mut [usize]) + ?Sized> {
+    /// The graph being traversed.
+    graph: &'a G,
+    /// Atoms already visited.
+    visited: Vec<bool>,
     /// Pool of ring‐closure trackers.
     pool: JoinPool,
     /// Sink for SMILES events.
     follower: &'a mut F,
     /// Total number of atoms (for bounds checks).
     num_atoms: usize,
+    /// Preferred component roots.
+    roots: &'a [usize],
+    /// Neighbor ordering callback.
+    order: &'a mut O,
+    /// Neighbor identifiers and bond indices, reused across atoms.
+    neighbors: Vec<usize>,
+    indices: Vec<usize>,
+    /// Optional record of the atoms behind each follower call.
+    steps: Option<&'a mut Vec<Step>>,
 }
 
-impl<'a, F: Follower> SmilesWalker<'a, F> {
-    /// Build a walker from the raw atom list and the follower.
-    pub fn new(graph: Vec<Atom>, follower: &'a mut F) -> Self {
-        let num_atoms = graph.len();
-        let atoms = graph.into_iter().map(Some).collect();
+impl<'a, G: Graph + ?Sized, F: Follower, O: FnMut(usize, &mut [usize]) + ?Sized>
+    SmilesWalker<'a, G, F, O>
+{
+    /// Build a walker from the graph and the follower.
+    pub fn new(
+        graph: &'a G,
+        follower: &'a mut F,
+        roots: &'a [usize],
+        order: &'a mut O,
+        steps: Option<&'a mut Vec<Step>>,
+    ) -> Self {
+        let num_atoms = graph.atom_count();
+
         SmilesWalker {
-            atoms,
+            graph,
+            visited: vec![false; num_atoms],
             pool: JoinPool::new(),
             follower,
             num_atoms,
+            roots,
+            order,
+            neighbors: Vec::new(),
+            indices: Vec::new(),
+            steps,
         }
     }
 
-    /// Iterate each root ID in order, invoking DFS from any atom still present.
+    /// Iterate each root ID in order, invoking DFS from any atom not yet visited.
     pub fn traverse(&mut self) -> Result<(), Error> {
+        for &id in self.roots {
+            if id >= self.num_atoms {
+                return Err(Error::UnknownRoot(id));
+            }
+
+            if !self.visited[id] {
+                self.visited[id] = true;
+                self.dfs_from_root(id)?;
+            }
+        }
+
         for id in 0..self.num_atoms {
-            if let Some(root) = self.atoms[id].take() {
-                self.dfs_from_root(id, root)?;
+            if !self.visited[id] {
+                self.visited[id] = true;
+                self.dfs_from_root(id)?;
             }
         }
         Ok(())
     }
 
     /// Handle one connected component starting at `root_id`.
-    fn dfs_from_root(&mut self, root_id: usize, root_atom: Atom) -> Result<(), Error> {
-        // Prepare per-path state
+    fn dfs_from_root(&mut self, root_id: usize) -> Result<(), Error> {
+        let graph = self.graph;
+        // Prepare per-path state. The stack holds (source, bond index) pairs.
         let mut stack = Vec::new();
         let mut chain = vec![root_id];
+        let mut kind = graph.kind(root_id);
 
         // Seed stack
-        for bond in root_atom.bonds.into_iter().rev() {
-            stack.push((root_id, bond));
-        }
-        self.follower.root(root_atom.kind);
+        let written = self.order_bonds(root_id, None)?;
+
+        reorder_configuration(&mut kind, &written).ok_or(Error::Configuration(root_id))?;
+        stack.extend(self.indices.iter().rev().map(|&idx| (root_id, idx)));
+        self.record(Step::Root(root_id));
+        self.follower.root(kind);
 
         // Standard DFS loop
-        while let Some((sid, bond)) = stack.pop() {
+        while let Some((sid, idx)) = stack.pop() {
+            let bond = &graph.bonds(sid)[idx];
+
             validate_bond_indices(sid, bond.tid, self.num_atoms)?;
             backtrack_and_pop(sid, &mut chain, self.follower);
 
-            if let Some(mut child) = self.atoms[bond.tid].take() {
-                process_tree_edge(
-                    sid,
-                    &bond,
-                    &mut child,
-                    self.follower,
-                    &mut stack,
-                    &mut chain,
-                )?;
+            if self.visited[bond.tid] {
+                self.record(Step::Join(sid, bond.tid));
+                process_ring_edge(sid, bond, &mut self.pool, self.follower);
             } else {
-                process_ring_edge(sid, &bond, &mut self.pool, self.follower);
+                self.visited[bond.tid] = true;
+                self.process_tree_edge(sid, bond, &mut stack, &mut chain)?;
+                self.record(Step::Extend(sid, bond.tid));
             }
         }
         Ok(())
     }
+
+    /// Handle a tree edge: find the back-bond, check stereochemistry, push new bonds, and extend.
+    fn process_tree_edge(
+        &mut self,
+        sid: usize,
+        bond: &Bond,
+        stack: &mut Vec<(usize, usize)>,
+        chain: &mut Vec<usize>,
+    ) -> Result<(), Error> {
+        let graph = self.graph;
+        let mut kind = graph.kind(bond.tid);
+        let back_idx = graph
+            .bonds(bond.tid)
+            .iter()
+            .position(|out| out.tid == sid)
+            .ok_or(Error::HalfBond(sid, bond.tid))?;
+        let back = &graph.bonds(bond.tid)[back_idx];
+
+        let written = self.order_bonds(bond.tid, Some((back_idx, sid)))?;
+
+        reorder_configuration(&mut kind, &written).ok_or(Error::Configuration(bond.tid))?;
+
+        check_bond_compatibility(bond, back)?;
+
+        stack.extend(self.indices.iter().rev().map(|&idx| (bond.tid, idx)));
+        chain.push(bond.tid);
+
+        // we elide single bonds, but keep the rest
+        match bond.kind {
+            BondKind::Single => self.follower.extend(BondKind::Elided, kind),
+            _ => self.follower.extend(bond.kind, kind),
+        }
+
+        Ok(())
+    }
+
+    /// Sets `indices` to the indices of the bonds of atom `id` that don't
+    /// lead back to the parent given by `back`, as (bond index, parent),
+    /// in the order given by the ordering callback. Returns the positions
+    /// in stored order of the neighbors as written, with any virtual
+    /// hydrogens first.
+    fn order_bonds(
+        &mut self,
+        id: usize,
+        back: Option<(usize, usize)>,
+    ) -> Result<Vec<usize>, Error> {
+        let bonds = self.graph.bonds(id);
+        let parent = back.map(|(_, parent)| parent);
+
+        self.neighbors.clear();
+        self.neighbors.extend(
+            bonds
+                .iter()
+                .map(|bond| bond.tid)
+                .filter(|&tid| Some(tid) != parent),
+        );
+
+        (self.order)(id, &mut self.neighbors);
+
+        self.indices.clear();
+
+        for &tid in &self.neighbors {
+            let idx = (0..bonds.len())
+                .find(|&idx| {
+                    bonds[idx].tid == tid && Some(tid) != parent && !self.indices.contains(&idx)
+                })
+                .ok_or(Error::Order(id))?;
+
+            self.indices.push(idx);
+        }
+
+        // positions in stored order, counting virtual hydrogens as zero
+        let offset = usize::from(has_hydrogens(&self.graph.kind(id)));
+        let mut written = Vec::with_capacity(bonds.len() + 1);
+
+        written.extend(back.map(|(idx, _)| idx + offset));
+
+        if offset == 1 {
+            written.push(0);
+        }
+
+        written.extend(self.indices.iter().map(|idx| idx + offset));
+
+        Ok(written)
+    }
+
+    fn record(&mut self, step: Step) {
+        if let Some(steps) = self.steps.as_mut() {
+            steps.push(step);
+        }
+    }
+}
+
+/// Returns true if the atom kind carries at least one virtual hydrogen.
+fn has_hydrogens(kind: &AtomKind) -> bool {
+    matches!(
+        kind,
+        AtomKind::Bracket {
+            hcount: Some(hcount),
+            ..
+        } if !hcount.is_zero()
+    )
+}
+
+/// Returns true if `sequence` has an odd number of inversions.
+pub(crate) fn is_odd(sequence: &[usize]) -> bool {
+    let mut inversions = 0;
+
+    for (i, left) in sequence.iter().enumerate() {
+        inversions += sequence[i + 1..]
+            .iter()
+            .filter(|right| *right < left)
+            .count();
+    }
+
+    inversions % 2 == 1
+}
+
+/// Swaps `TH1` and `TH2` if `written` is an odd permutation of stored
+/// positions. Returns `None` if another configuration is written out of
+/// stored order.
+fn reorder_configuration(kind: &mut AtomKind, written: &[usize]) -> Option<()> {
+    if let AtomKind::Bracket {
+        configuration: Some(configuration),
+        ..
+    } = kind
+    {
+        match *configuration {
+            Configuration::TH1 if is_odd(written) => *configuration = Configuration::TH2,
+            Configuration::TH2 if is_odd(written) => *configuration = Configuration::TH1,
+            Configuration::TH1 | Configuration::TH2 => (),
+            _ if written.windows(2).any(|pair| pair[0] > pair[1]) => return None,
+            _ => (),
+        }
+    }
+
+    Some(())
 }
 
 /// Validate basic bond errors: unknown target or self-loop.
@@ -101,54 +334,12 @@ fn backtrack_and_pop<F: Follower>(sid: usize, chain: &mut Vec<usize>, follower:
     }
 }
 
-/// Handle a tree edge: remove the back-bond, check stereochemistry, push new bonds, and extend.
-fn process_tree_edge<F: Follower>(
-    sid: usize,
-    bond: &Bond,
-    child: &mut Atom,
-    follower: &mut F,
-    stack: &mut Vec<(usize, Bond)>,
-    chain: &mut Vec<usize>,
-) -> Result<(), Error> {
-    let mut back_bond = None;
-    for (idx, out) in child.bonds.drain(..).enumerate().rev() {
-        if out.tid == sid {
-            // Stereochemistry inversion on even index
-            if idx % 2 == 0 {
-                child.kind.invert_configuration();
-            }
-            back_bond = Some(out);
-        } else {
-            stack.push((bond.tid, out));
-        }
-    }
-    let back = back_bond.ok_or(Error::HalfBond(sid, bond.tid))?;
-
-    check_bond_compatibility(bond, &back)?;
-
-    chain.push(bond.tid);
-
-    // we elide single bonds, but keep the rest
-    match bond.kind {
-        BondKind::Single => follower.extend(BondKind::Elided, child.kind),
-        _ => follower.extend(bond.kind, child.kind),
-    }
-
-    Ok(())
-}
-
 /// Ensure the forward and back bonds match, respecting directionality.
 fn check_bond_compatibility(fwd: &Bond, back: &Bond) -> Result<(), Error> {
-    if fwd.is_directional() {
-        if fwd.kind == back.kind.reverse() {
-            Ok(())
-        } else {
-            Err(Error::IncompatibleBond(fwd.tid, back.tid))
-        }
-    } else if fwd.kind != back.kind {
-        Err(Error::IncompatibleBond(fwd.tid, back.tid))
-    } else {
+    if fwd.kind == back.kind.reverse() {
         Ok(())
+    } else {
+        Err(Error::IncompatibleBond(fwd.tid, back.tid))
     }
 }
 
@@ -168,7 +359,7 @@ mod tests {
 
     use super::*;
     use crate::feature::{AtomKind, BondKind, Symbol};
-    use crate::graph::Bond;
+    use crate::graph::{Atom, Bond};
     use crate::write::Writer;
 
     /// Simple linear C–O: should emit "CO"
@@ -363,3 +554,118 @@ mod tests {
         assert_eq!(writer.write(), "*/*");
     }
 }
+
+#[cfg(test)]
+mod walk_with {
+    use super::*;
+    use crate::graph::{is_isomorphic, Atom};
+    use crate::testing::atoms;
+    use crate::write::Writer;
+    use pretty_assertions::assert_eq;
+
+    fn write<O: FnMut(usize, &mut [usize])>(atoms: &[Atom], roots: &[usize], order: O) -> String {
+        let mut writer = Writer::default();
+
+        walk_with(atoms, &mut writer, roots, order).unwrap();
+
+        writer.write()
+    }
+
+    #[test]
+    fn root() {
+        assert_eq!(write(&atoms("CCO"), &[2], |_, _| {}), "OCC");
+        assert_eq!(write(&atoms("CC(N)O"), &[2], |_, _| {}), "NC(C)O")
+    }
+
+    #[test]
+    fn component_roots() {
+        let atoms = atoms("CC.O.N");
+
+        assert_eq!(write(&atoms, &[3, 1], |_, _| {}), "N.CC.O");
+        assert_eq!(write(&atoms, &[1, 0], |_, _| {}), "CC.O.N")
+    }
+
+    #[test]
+    fn neighbor_order() {
+        let atoms = atoms("CC(N)(O)S");
+
+        assert_eq!(
+            write(&atoms, &[], |_, neighbors| neighbors.reverse()),
+            "CC(S)(O)N"
+        )
+    }
+
+    #[test]
+    fn unknown_root() {
+        let mut writer = Writer::default();
+
+        assert_eq!(
+            walk_with(atoms("C"), &mut writer, &[1], |_, _| {}),
+            Err(Error::UnknownRoot(1))
+        )
+    }
+
+    #[test]
+    fn not_a_permutation() {
+        let mut writer = Writer::default();
+
+        assert_eq!(
+            walk_with(atoms("CC(N)O"), &mut writer, &[], |_, neighbors| {
+                neighbors.fill(0)
+            }),
+            Err(Error::Order(0))
+        )
+    }
+
+    #[test]
+    fn non_tetrahedral_reordered() {
+        for smiles in [
+            "F[Pt@SP1](Cl)(Br)I",
+            "S[As@TB1](F)(Cl)(Br)N",
+            "C[Co@OH1](F)(Cl)(Br)(I)N",
+        ] {
+            let mut writer = Writer::default();
+
+            assert_eq!(write(&atoms(smiles), &[], |_, _| {}), smiles);
+            assert_eq!(
+                walk_with(atoms(smiles), &mut writer, &[], |_, neighbors| {
+                    neighbors.reverse()
+                }),
+                Err(Error::Configuration(1)),
+                "{smiles}"
+            )
+        }
+    }
+
+    #[test]
+    fn stereo_survives_reordering() {
+        for smiles in [
+            "[NH2:1][C@@H:2]([CH3:3])[C:4](=[O:5])[OH:6]",
+            "[F:1][C@:2]([Cl:3])([Br:4])[I:5]",
+            "[CH3:1][C@H:2]1[CH2:3][CH2:4][C@@:5]1([OH:6])[NH2:7]",
+            "[C@@:1]1([F:2])([Br:10])[CH2:3][CH2:4][C@H:5]([Cl:6])[CH2:7][CH2:8]1.[Na+:9]",
+            "[O:1]=[C:2]1[C@@H:3]2[CH2:4][C@H:5]1[CH2:6]2",
+        ] {
+            let original = atoms(smiles);
+
+            for root in 0..original.len() {
+                for rotation in 0..4 {
+                    let rewritten = write(&original, &[root], |id, neighbors| {
+                        let len = neighbors.len().max(1);
+
+                        neighbors.rotate_left((rotation + id) % len);
+
+                        if rotation % 2 == 1 {
+                            neighbors.reverse()
+                        }
+                    });
+
+                    assert!(
+                        is_isomorphic(&atoms(&rewritten), &original, true),
+                        "{smiles} -> {rewritten}"
+                    )
+                }
+            }
+        }
+    }
+}
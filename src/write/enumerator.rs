@@ -0,0 +1,294 @@
+use std::collections::HashSet;
+
+use super::Writer;
+use crate::graph::Atom;
+use crate::walk::{walk_with, Error};
+
+/// Enumerates every distinct SMILES string for a graph, lazily.
+///
+/// Strings differ in component order, root atoms and neighbor orders, and
+/// with them the bonds written as ring closures. Each order is walked in
+/// turn, and strings already yielded are skipped, so memory grows with the
+/// number of strings yielded. Use `take` to stop at a limit.
+///
+/// ```
+/// use yowl::graph::Builder;
+/// use yowl::read::read;
+/// use yowl::write::Enumerator;
+///
+/// let mut builder = Builder::default();
+///
+/// read("CCO", &mut builder, None).expect("read");
+///
+/// let atoms = builder.build().expect("atoms");
+/// let strings = Enumerator::new(&atoms)
+///     .collect::<Result<Vec<_>, _>>()
+///     .expect("strings");
+///
+/// assert_eq!(strings, vec!["CCO", "C(C)O", "C(O)C", "OCC"]);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Enumerator {
+    atoms: Vec<Atom>,
+    components: Vec<Vec<usize>>,
+    choices: Vec<Choice>,
+    seen: HashSet<String>,
+    done: bool,
+}
+
+impl Enumerator {
+    /// Constructs an Enumerator over `atoms`.
+    pub fn new(atoms: &[Atom]) -> Self {
+        Self {
+            components: components(atoms),
+            atoms: atoms.to_vec(),
+            choices: Vec::new(),
+            seen: HashSet::new(),
+            done: atoms.is_empty(),
+        }
+    }
+
+    /// Walks the graph with the current choices, recording choices not yet
+    /// made as their first option.
+    fn walk(&mut self) -> Result<String, Error> {
+        let mut choices = Choices {
+            made: &mut self.choices,
+            cursor: 0,
+        };
+        let mut components = self.components.iter().collect::<Vec<_>>();
+
+        choices.permute(&mut components);
+
+        let roots = components
+            .iter()
+            .map(|component| component[choices.choose(component.len())])
+            .collect::<Vec<_>>();
+        let mut writer = Writer::default();
+
+        walk_with(&self.atoms, &mut writer, &roots, |_, neighbors| {
+            choices.permute(neighbors)
+        })?;
+
+        Ok(writer.write())
+    }
+
+    /// Moves on to the next combination of choices, returning false after
+    /// the last.
+    fn advance(&mut self) -> bool {
+        while let Some(choice) = self.choices.last_mut() {
+            if choice.index + 1 < choice.count {
+                choice.index += 1;
+
+                return true;
+            }
+
+            self.choices.pop();
+        }
+
+        false
+    }
+}
+
+impl Iterator for Enumerator {
+    type Item = Result<String, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let result = self.walk();
+
+            self.done = result.is_err() || !self.advance();
+
+            match result {
+                Ok(smiles) => {
+                    if self.seen.insert(smiles.clone()) {
+                        return Some(Ok(smiles));
+                    }
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
+
+        None
+    }
+}
+
+/// One decision among `count` options.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Choice {
+    index: usize,
+    count: usize,
+}
+
+/// Replays recorded choices in order, recording the first option for any
+/// choice beyond them.
+struct Choices<'a> {
+    made: &'a mut Vec<Choice>,
+    cursor: usize,
+}
+
+impl Choices<'_> {
+    fn choose(&mut self, count: usize) -> usize {
+        if count < 2 {
+            return 0;
+        }
+
+        if self.cursor == self.made.len() {
+            self.made.push(Choice { index: 0, count });
+        }
+
+        self.cursor += 1;
+
+        self.made[self.cursor - 1].index
+    }
+
+    /// Applies the chosen permutation of `items`, decoded from its index
+    /// in the factorial number system.
+    fn permute<T>(&mut self, items: &mut [T]) {
+        let count = count_permutations(items.len());
+        let mut index = self.choose(count);
+
+        for start in 0..items.len() {
+            let remaining = items.len() - start;
+            let block = count_permutations(remaining - 1);
+
+            items[start..=start + index / block].rotate_right(1);
+            index %= block;
+        }
+    }
+}
+
+fn count_permutations(size: usize) -> usize {
+    (1..=size).fold(1usize, |product, n| product.saturating_mul(n))
+}
+
+/// Returns the atoms of each connected component.
+pub(crate) fn components(atoms: &[Atom]) -> Vec<Vec<usize>> {
+    let mut seen = vec![false; atoms.len()];
+    let mut result = Vec::new();
+
+    for root in 0..atoms.len() {
+        if seen[root] {
+            continue;
+        }
+
+        let mut component = vec![root];
+        let mut stack = vec![root];
+
+        seen[root] = true;
+
+        while let Some(sid) = stack.pop() {
+            for bond in &atoms[sid].bonds {
+                if bond.tid < atoms.len() && !seen[bond.tid] {
+                    seen[bond.tid] = true;
+                    component.push(bond.tid);
+                    stack.push(bond.tid);
+                }
+            }
+        }
+
+        result.push(component);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature::BondKind;
+    use crate::graph::{is_isomorphic, Bond};
+    use crate::testing::atoms;
+    use pretty_assertions::assert_eq;
+
+    fn strings(smiles: &str) -> Vec<String> {
+        Enumerator::new(&atoms(smiles))
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn permutations() {
+        let mut choices = Vec::new();
+        let mut seen = HashSet::new();
+
+        loop {
+            let mut items = vec![0, 1, 2, 3];
+            let mut replay = Choices {
+                made: &mut choices,
+                cursor: 0,
+            };
+
+            replay.permute(&mut items);
+            seen.insert(items);
+
+            let mut enumerator = Enumerator::new(&[]);
+
+            enumerator.choices = choices;
+
+            if !enumerator.advance() {
+                break;
+            }
+
+            choices = enumerator.choices;
+        }
+
+        assert_eq!(seen.len(), 24)
+    }
+
+    #[test]
+    fn single_atom() {
+        assert_eq!(strings("[Na+]"), vec!["[Na+]"]);
+        assert_eq!(Enumerator::new(&[]).count(), 0)
+    }
+
+    #[test]
+    fn fragments() {
+        assert_eq!(strings("C.O.N").len(), 6);
+        assert_eq!(strings("O.O"), vec!["O.O"])
+    }
+
+    #[test]
+    fn symmetric() {
+        // every root gives the same strings up to neighbor order
+        assert_eq!(strings("C(C)(C)C"), vec!["C(C)(C)C", "CC(C)C"])
+    }
+
+    #[test]
+    fn ring() {
+        let strings = strings("C1CC1O");
+
+        assert!(strings.contains(&"C(CC1O)1".to_string()));
+        assert!(strings.iter().all(|smiles| !atoms(smiles).is_empty()))
+    }
+
+    #[test]
+    fn all_equivalent() {
+        let smiles = "[CH3:1][C@H:2]1[CH2:3][N:4]([C:5](=[O:6])[OH:7])[CH2:8]/[CH:9]=[CH:10]/1";
+        let expected = atoms(smiles);
+        let strings = strings(smiles);
+
+        assert!(strings.len() > 50);
+
+        for string in strings {
+            assert!(is_isomorphic(&atoms(&string), &expected, true), "{string}")
+        }
+    }
+
+    #[test]
+    fn limit() {
+        assert_eq!(Enumerator::new(&atoms("c1ccccc1C(=O)O")).take(5).count(), 5)
+    }
+
+    #[test]
+    fn invalid_graph() {
+        let atoms = vec![Atom {
+            kind: atoms("C")[0].kind,
+            bonds: vec![Bond::new(BondKind::Single, 2)],
+        }];
+
+        assert_eq!(
+            Enumerator::new(&atoms).collect::<Vec<_>>(),
+            vec![Err(Error::UnknownTarget(0, 2))]
+        )
+    }
+}
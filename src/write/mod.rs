@@ -1,3 +1,11 @@
+mod enumerator;
+mod random;
+mod style;
+mod trace;
 mod writer;
 
+pub use enumerator::Enumerator;
+pub use random::{RandomOptions, Randomizer};
+pub use style::{Fragments, Style};
+pub use trace::{trace, trace_with, Trace};
 pub use writer::Writer;
@@ -0,0 +1,234 @@
+use super::enumerator::components;
+use super::{Style, Writer};
+use crate::graph::{kekulize, Atom, KekulizeError};
+use crate::walk::{walk_with, Error};
+
+/// Options for a `Randomizer`. The default writes atoms and bonds as they
+/// were built, including stereochemistry.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct RandomOptions {
+    /// Writes aromatic systems as alternating single and double bonds.
+    pub kekule: bool,
+    /// Omits configurations and directional bonds.
+    pub strip_stereo: bool,
+}
+
+/// Generates random equivalent SMILES strings for a graph, choosing a
+/// random root atom for each component, a random component order, and
+/// random neighbor orders, which in turn decide the bonds written as ring
+/// closures.
+///
+/// The sequence of strings depends only on the graph, the options and the
+/// seed.
+///
+/// ```
+/// use yowl::graph::Builder;
+/// use yowl::read::read;
+/// use yowl::write::{RandomOptions, Randomizer};
+///
+/// let mut builder = Builder::default();
+///
+/// read("c1ccccc1O", &mut builder, None).expect("read");
+///
+/// let atoms = builder.build().expect("atoms");
+/// let options = RandomOptions {
+///     kekule: true,
+///     ..RandomOptions::default()
+/// };
+/// let first = Randomizer::new(&atoms, options, 7)
+///     .expect("randomizer")
+///     .take(3)
+///     .collect::<Result<Vec<_>, _>>()
+///     .expect("smiles");
+/// let second = Randomizer::new(&atoms, options, 7)
+///     .expect("randomizer")
+///     .take(3)
+///     .collect::<Result<Vec<_>, _>>()
+///     .expect("smiles");
+///
+/// assert_eq!(first, second);
+/// assert!(first.iter().all(|smiles| !smiles.contains('c')));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Randomizer {
+    atoms: Vec<Atom>,
+    components: Vec<Vec<usize>>,
+    style: Style,
+    rng: SplitMix,
+}
+
+impl Randomizer {
+    /// Constructs a Randomizer over `atoms`, returning an error if Kekulé
+    /// output is requested and `atoms` has no Kekulé structure.
+    pub fn new(atoms: &[Atom], options: RandomOptions, seed: u64) -> Result<Self, KekulizeError> {
+        let mut atoms = atoms.to_vec();
+
+        if options.kekule {
+            kekulize(&mut atoms)?;
+        }
+
+        Ok(Self {
+            components: components(&atoms),
+            atoms,
+            style: Style {
+                strip_stereo: options.strip_stereo,
+                ..Style::default()
+            },
+            rng: SplitMix(seed),
+        })
+    }
+}
+
+impl Iterator for Randomizer {
+    type Item = Result<String, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rng = &mut self.rng;
+        let mut roots = self
+            .components
+            .iter()
+            .map(|component| component[rng.below(component.len())])
+            .collect::<Vec<_>>();
+
+        rng.shuffle(&mut roots);
+
+        let mut writer = Writer::new(self.style);
+
+        Some(
+            walk_with(&self.atoms, &mut writer, &roots, |_, neighbors| {
+                rng.shuffle(neighbors)
+            })
+            .map(|()| writer.write()),
+        )
+    }
+}
+
+/// The SplitMix64 generator, chosen so that a seed gives the same strings
+/// on every platform and release.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.0;
+
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        ((u128::from(self.next_u64()) * bound as u128) >> 64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.below(idx + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::feature::BondKind;
+    use crate::graph::is_isomorphic;
+    use crate::testing::atoms;
+    use pretty_assertions::assert_eq;
+
+    fn strings(smiles: &str, options: RandomOptions, seed: u64, count: usize) -> Vec<String> {
+        Randomizer::new(&atoms(smiles), options, seed)
+            .unwrap()
+            .take(count)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn same_seed_same_strings() {
+        let smiles = "CC(=O)Nc1ccc(O)cc1.Cl";
+
+        assert_eq!(
+            strings(smiles, RandomOptions::default(), 42, 20),
+            strings(smiles, RandomOptions::default(), 42, 20)
+        );
+        assert_ne!(
+            strings(smiles, RandomOptions::default(), 42, 20),
+            strings(smiles, RandomOptions::default(), 43, 20)
+        )
+    }
+
+    #[test]
+    fn varied() {
+        let unique = strings("CC(=O)Nc1ccc(O)cc1", RandomOptions::default(), 1, 50)
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        assert!(unique.len() > 10)
+    }
+
+    #[test]
+    fn equivalent() {
+        let smiles = "[CH3:1][C@H:2]([NH2:3])[C:4](=[O:5])[NH:6][c:7]1[cH:8][cH:9][c:10](/[CH:11]=[CH:12]/[Cl:13])[cH:14][cH:15]1.[Na+:16]";
+        let expected = atoms(smiles);
+
+        for random in strings(smiles, RandomOptions::default(), 9, 50) {
+            assert!(is_isomorphic(&atoms(&random), &expected, true), "{random}")
+        }
+    }
+
+    #[test]
+    fn kekule() {
+        let options = RandomOptions {
+            kekule: true,
+            ..RandomOptions::default()
+        };
+
+        for random in strings("c1ccc2[nH]ccc2c1", options, 3, 20) {
+            assert!(!random.contains(['c', 'n']), "{random}");
+            assert_eq!(
+                atoms(&random)
+                    .iter()
+                    .flat_map(|atom| &atom.bonds)
+                    .filter(|bond| bond.kind == BondKind::Double)
+                    .count(),
+                8,
+                "{random}"
+            )
+        }
+
+        assert_eq!(
+            Randomizer::new(&atoms("c1cccc1"), options, 0),
+            Err(KekulizeError::Unmatched(3))
+        )
+    }
+
+    #[test]
+    fn strip_stereo() {
+        let options = RandomOptions {
+            strip_stereo: true,
+            ..RandomOptions::default()
+        };
+
+        for random in strings("F/C=C/[C@H](O)Cl", options, 5, 20) {
+            assert!(!random.contains(['@', '/', '\\']), "{random}")
+        }
+    }
+
+    #[test]
+    fn shuffle_is_permutation() {
+        let mut rng = SplitMix(0);
+        let mut items = (0..10).collect::<Vec<_>>();
+
+        rng.shuffle(&mut items);
+        items.sort_unstable();
+
+        assert_eq!(items, (0..10).collect::<Vec<_>>())
+    }
+}
@@ -0,0 +1,50 @@
+/// Options for the SMILES dialect written by a `Writer`. The default
+/// writes single and aromatic bonds implicitly, and atoms the way their
+/// `AtomKind` was built.
+///
+/// ```
+/// use yowl::read::read;
+/// use yowl::write::{Style, Writer};
+///
+/// let mut writer = Writer::new(Style {
+///     explicit_single: true,
+///     strip_stereo: true,
+///     ..Style::default()
+/// });
+///
+/// read("F/C=C/[C@H](O)Cl", &mut writer, None).expect("read");
+///
+/// assert_eq!(writer.write(), "F-C=C-[CH](-O)-Cl");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Style {
+    /// Writes `-` for single bonds that would otherwise be elided.
+    pub explicit_single: bool,
+    /// Writes `:` for bonds between aromatic atoms that would otherwise be
+    /// elided.
+    pub aromatic_bonds: bool,
+    /// Writes every atom in brackets, with its hydrogen count.
+    pub brackets: bool,
+    /// Omits atom map numbers.
+    pub strip_maps: bool,
+    /// Omits isotopes.
+    pub strip_isotopes: bool,
+    /// Omits configurations and writes directional bonds as plain bonds.
+    pub strip_stereo: bool,
+    /// Writes tetrahedral and allenal configurations as `@TH1` and `@TH2`
+    /// or `@AL1` and `@AL2`, rather than `@` and `@@`.
+    pub explicit_chirality: bool,
+    /// How disconnected fragments are separated.
+    pub fragments: Fragments,
+}
+
+/// The separation of disconnected fragments.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Fragments {
+    /// Writes `.` between fragments, as in `C.O`.
+    #[default]
+    Dot,
+    /// Writes each fragment after the first as a branch beginning with
+    /// `.`, as in `C(.O)`.
+    Branch,
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use super::{Style, Writer};
+use crate::walk::{walk_steps, Error, Graph, Step};
+
+/// Maps the atoms and bonds of an adjacency representation to byte ranges
+/// within the string representation written for it.
+///
+/// ```
+/// use yowl::graph::Builder;
+/// use yowl::read::read;
+/// use yowl::write::trace;
+///
+/// let mut builder = Builder::default();
+///
+/// read("C(=O)N", &mut builder, None).expect("read");
+///
+/// let atoms = builder.build().expect("atoms");
+/// let (smiles, trace) = trace(&atoms).expect("trace");
+///
+/// assert_eq!(smiles, "C(=O)N");
+/// assert_eq!(trace.atom(1), Some(3..4));
+/// assert_eq!(trace.bond(0, 1), Some(2..3));
+/// assert_eq!(trace.bond(0, 2), Some(5..5));
+/// assert_eq!(trace.order(), &[0, 1, 2]);
+/// ```
+#[derive(Debug, PartialEq, Default)]
+pub struct Trace {
+    atoms: Vec<Range<usize>>,
+    bonds: HashMap<(usize, usize), Range<usize>>,
+    order: Vec<usize>,
+}
+
+impl Trace {
+    /// Returns the byte range of the atom with identifier `id`.
+    pub fn atom(&self, id: usize) -> Option<Range<usize>> {
+        self.atoms.get(id).cloned()
+    }
+
+    /// Returns the byte range of the bond between `sid` and `tid`. The
+    /// range is empty when the bond symbol is elided. For a join (ring
+    /// closure), the range also covers the ring closure number, and
+    /// (`sid`, `tid`) and (`tid`, `sid`) report the two different ends.
+    pub fn bond(&self, sid: usize, tid: usize) -> Option<Range<usize>> {
+        self.bonds.get(&(sid, tid)).cloned()
+    }
+
+    /// Returns atom identifiers in the order they were written.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+}
+
+/// Writes the string representation of `graph`, together with a `Trace`
+/// relating it back to atom identifiers.
+pub fn trace<G: Graph>(graph: G) -> Result<(String, Trace), Error> {
+    trace_with(graph, Style::default())
+}
+
+/// Like `trace`, but writing in `style`.
+pub fn trace_with<G: Graph>(graph: G, style: Style) -> Result<(String, Trace), Error> {
+    let mut writer = Writer::tracing(style);
+    let mut steps = Vec::new();
+
+    walk_steps(&graph, &mut writer, &mut steps)?;
+
+    let mut result = Trace {
+        atoms: vec![0..0; graph.atom_count()],
+        ..Trace::default()
+    };
+    let locator = writer.locator();
+    let locate = |range: &Range<usize>| locator.locate(range.clone());
+
+    for (step, mark) in steps.iter().zip(writer.marks()) {
+        match *step {
+            Step::Root(id) => {
+                result.atoms[id] = locate(&mark.target);
+                result.order.push(id);
+            }
+            Step::Extend(sid, tid) => {
+                let bond = locate(&mark.bond);
+
+                result.atoms[tid] = locate(&mark.target);
+                result.bonds.insert((sid, tid), bond.clone());
+                result.bonds.insert((tid, sid), bond);
+                result.order.push(tid);
+            }
+            Step::Join(sid, tid) => {
+                let bond = locate(&(mark.bond.start..mark.target.end));
+
+                result.bonds.insert((sid, tid), bond);
+            }
+        }
+    }
+
+    Ok((writer.write(), result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Atom;
+    use crate::testing::atoms;
+    use crate::write::Fragments;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn atoms_match_output() {
+        for smiles in [
+            "CC(=O)N",
+            "C1CC1O",
+            "c1ccccc1-c1ccccc1.[Na+]",
+            "CC(C(C)(C)C)(C(C)C)C",
+        ] {
+            let graph = atoms(smiles);
+            let (output, trace) = trace(&graph).unwrap();
+
+            for (id, atom) in graph.iter().enumerate() {
+                let range = trace.atom(id).unwrap();
+
+                assert_eq!(&output[range], atom.kind.to_string(), "{smiles}")
+            }
+
+            let mut order = trace.order().to_vec();
+
+            order.sort_unstable();
+
+            assert_eq!(order, (0..graph.len()).collect::<Vec<_>>())
+        }
+    }
+
+    #[test]
+    fn bracket_atoms() {
+        let (output, trace) = trace(atoms("F[C@](Cl)(Br)I")).unwrap();
+
+        assert_eq!(output, "F[C@](Cl)(Br)I");
+        assert_eq!(trace.atom(1), Some(1..5));
+        assert_eq!(trace.atom(3), Some(10..12));
+        assert_eq!(trace.atom(4), Some(13..14))
+    }
+
+    #[test]
+    fn ring_closure() {
+        let graph = atoms("C1CC=1");
+        let (output, trace) = trace(&graph).unwrap();
+
+        assert_eq!(output, "C(=CC1)1");
+        assert_eq!(trace.order(), &[0, 2, 1]);
+        assert_eq!(trace.bond(0, 2), Some(2..3));
+        assert_eq!(trace.bond(2, 1), Some(4..4));
+        assert_eq!(trace.bond(1, 0), Some(5..6));
+        assert_eq!(trace.bond(0, 1), Some(7..8));
+        assert_eq!(trace.atom(1), Some(4..5))
+    }
+
+    #[test]
+    fn components() {
+        let (output, trace) = trace(atoms("C.[OH-]")).unwrap();
+
+        assert_eq!(output, "C.[OH-]");
+        assert_eq!(trace.atom(1), Some(2..7));
+        assert_eq!(trace.atom(2), None);
+        assert_eq!(trace.bond(0, 1), None)
+    }
+
+    #[test]
+    fn styled() {
+        let graph = atoms("C1CC1(C)O.N");
+        let style = Style {
+            brackets: true,
+            explicit_single: true,
+            fragments: Fragments::Branch,
+            ..Style::default()
+        };
+        let (output, trace) = trace_with(&graph, style).unwrap();
+
+        assert_eq!(output, "[CH2](-[C](-[CH2]-1)(-[CH3])-[OH])-1(.[NH3])");
+
+        for (id, text) in ["[CH2]", "[CH2]", "[C]", "[CH3]", "[OH]", "[NH3]"]
+            .iter()
+            .enumerate()
+        {
+            assert_eq!(&output[trace.atom(id).unwrap()], *text)
+        }
+
+        assert_eq!(&output[trace.bond(2, 4).unwrap()], "-");
+        assert_eq!(trace.bond(1, 0), Some(17..19));
+        assert_eq!(trace.bond(0, 1), Some(34..36))
+    }
+
+    #[test]
+    fn walk_error() {
+        let graph = vec![Atom {
+            kind: atoms("C")[0].kind,
+            bonds: vec![crate::graph::Bond::new(crate::feature::BondKind::Single, 3)],
+        }];
+
+        assert_eq!(trace(graph), Err(Error::UnknownTarget(0, 3)))
+    }
+}
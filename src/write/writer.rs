@@ -1,4 +1,10 @@
-use crate::feature::{AtomKind, BondKind, Rnum};
+use std::fmt::{self, Write as _};
+use std::io;
+use std::ops::Range;
+
+use super::{Fragments, Style};
+use crate::feature::{AtomKind, BondKind, Configuration, Rnum};
+use crate::graph::{bracket, Bond};
 use crate::walk::Follower;
 
 /// A `Follower` that builds a string SMILEs representation.
@@ -17,42 +23,482 @@ use crate::walk::Follower;
 /// ```
 #[derive(Debug, PartialEq, Eq, Default)]
 pub struct Writer {
-    stack: Vec<String>,
+    style: Style,
+    buffer: String,
+    segments: Vec<Segment>,
+    stack: Vec<usize>,
+    pending: Vec<Pending>,
+    opens: Vec<Option<(usize, BondKind)>>,
+    fragment: bool,
+    marks: Option<Vec<Mark>>,
+}
+
+/// Buffer ranges written by a `Follower` call: a bond symbol, which may be
+/// empty, followed by an atom or ring closure number.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct Mark {
+    pub bond: Range<usize>,
+    pub target: Range<usize>,
+}
+
+/// The text written for one atom starts at `start`, and `opens` branches
+/// open immediately before it.
+#[derive(Debug, PartialEq, Eq)]
+struct Segment {
+    start: usize,
+    opens: usize,
+    aromatic: bool,
+    pending: Option<usize>,
+}
+
+/// An atom written to `range` of the buffer without brackets, to be
+/// replaced with a bracket atom once all of its bonds are known.
+#[derive(Debug, PartialEq, Eq)]
+struct Pending {
+    range: Range<usize>,
+    kind: AtomKind,
+    valence: u8,
 }
 
 impl Writer {
+    /// Constructs a Writer using `style`.
+    pub fn new(style: Style) -> Self {
+        Self {
+            style,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the string representation.
     pub fn write(self) -> String {
-        self.stack.join("")
+        let mut result = String::with_capacity(self.buffer.len() + self.segments.len());
+
+        self.write_to(&mut result).expect("write to string");
+
+        result
+    }
+
+    /// Writes the string representation to `sink`.
+    pub fn write_to<W: fmt::Write>(&self, sink: &mut W) -> fmt::Result {
+        let mut cursor = 0;
+        let mut pending = self.pending.iter().peekable();
+
+        for segment in &self.segments {
+            while let Some(atom) = pending.next_if(|atom| atom.range.start < segment.start) {
+                sink.write_str(&self.buffer[cursor..atom.range.start])?;
+                self.write_pending(sink, atom)?;
+
+                cursor = atom.range.end;
+            }
+
+            if segment.opens > 0 {
+                sink.write_str(&self.buffer[cursor..segment.start])?;
+
+                for _ in 0..segment.opens {
+                    sink.write_char('(')?;
+                }
+
+                cursor = segment.start;
+            }
+        }
+
+        for atom in pending {
+            sink.write_str(&self.buffer[cursor..atom.range.start])?;
+            self.write_pending(sink, atom)?;
+
+            cursor = atom.range.end;
+        }
+
+        sink.write_str(&self.buffer[cursor..])?;
+
+        if self.fragment {
+            sink.write_char(')')?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the string representation to `sink` as UTF-8.
+    pub fn write_io<W: io::Write>(&self, sink: W) -> io::Result<()> {
+        let mut adapter = IoAdapter { sink, error: None };
+
+        match self.write_to(&mut adapter) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(adapter
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error"))),
+        }
+    }
+
+    /// Clears written output, retaining the style and allocations for reuse.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.segments.clear();
+        self.stack.clear();
+        self.pending.clear();
+        self.opens.iter_mut().for_each(|open| *open = None);
+        self.fragment = false;
+
+        if let Some(marks) = self.marks.as_mut() {
+            marks.clear();
+        }
+    }
+
+    /// Returns a Writer that also records a `Mark` for each `root`,
+    /// `extend` and `join`.
+    pub(crate) fn tracing(style: Style) -> Self {
+        Self {
+            marks: Some(Vec::new()),
+            ..Self::new(style)
+        }
+    }
+
+    pub(crate) fn marks(&self) -> &[Mark] {
+        self.marks.as_deref().unwrap_or_default()
+    }
+
+    /// Returns a `Locator` from buffer ranges to output ranges.
+    pub(crate) fn locator(&self) -> Locator {
+        let mut total = 0;
+        let opens = self
+            .segments
+            .iter()
+            .filter(|segment| segment.opens > 0)
+            .map(|segment| {
+                total += segment.opens;
+
+                (segment.start, total)
+            })
+            .collect();
+        let mut total = 0;
+        let pending = self
+            .pending
+            .iter()
+            .map(|atom| {
+                total += self.pending_len(atom) - atom.range.len();
+
+                (atom.range.end, total)
+            })
+            .collect();
+
+        Locator { opens, pending }
+    }
+
+    fn push(&mut self, kind: &AtomKind) {
+        self.stack.push(self.segments.len());
+        self.segments.push(Segment {
+            start: self.buffer.len(),
+            opens: 0,
+            aromatic: kind.is_aromatic(),
+            pending: None,
+        });
+    }
+
+    /// Writes `kind` for the atom at the top of the stack, and returns its
+    /// buffer range.
+    fn write_atom(&mut self, kind: AtomKind, valence: u8) -> Range<usize> {
+        let start = self.buffer.len();
+        let kind = self.restyle_atom(kind);
+
+        write_kind(&mut self.buffer, &kind, self.style.explicit_chirality)
+            .expect("write to string");
+
+        let range = start..self.buffer.len();
+
+        if self.style.brackets && matches!(kind, AtomKind::Symbol(_)) {
+            let segment = *self.stack.last().expect("last");
+
+            self.segments[segment].pending = Some(self.pending.len());
+            self.pending.push(Pending {
+                range: range.clone(),
+                kind,
+                valence,
+            });
+        }
+
+        range
+    }
+
+    fn write_bond(&mut self, kind: BondKind) -> Range<usize> {
+        let start = self.buffer.len();
+
+        write!(self.buffer, "{kind}").expect("write to string");
+
+        start..self.buffer.len()
+    }
+
+    fn write_pending<W: fmt::Write>(&self, sink: &mut W, atom: &Pending) -> fmt::Result {
+        write_kind(sink, &bracket(&atom.kind, atom.valence), false)
+    }
+
+    fn pending_len(&self, atom: &Pending) -> usize {
+        let mut text = String::new();
+
+        self.write_pending(&mut text, atom)
+            .expect("write to string");
+
+        text.len()
+    }
+
+    fn add_valence(&mut self, segment: usize, kind: BondKind) {
+        if let Some(pending) = self.segments[segment].pending {
+            self.pending[pending].valence += Bond::new(kind, 0).order();
+        }
+    }
+
+    fn restyle_atom(&self, mut kind: AtomKind) -> AtomKind {
+        if let AtomKind::Bracket {
+            isotope,
+            configuration,
+            map,
+            ..
+        } = &mut kind
+        {
+            if self.style.strip_isotopes {
+                *isotope = None;
+            }
+
+            if self.style.strip_stereo {
+                *configuration = None;
+            }
+
+            if self.style.strip_maps {
+                *map = None;
+            }
+        }
+
+        kind
+    }
+
+    fn restyle_bond(&self, kind: BondKind, aromatic: bool) -> BondKind {
+        match kind {
+            BondKind::Up | BondKind::Down if self.style.strip_stereo => {
+                self.restyle_bond(BondKind::Elided, aromatic)
+            }
+            BondKind::Elided if aromatic => {
+                if self.style.aromatic_bonds {
+                    BondKind::Aromatic
+                } else {
+                    BondKind::Elided
+                }
+            }
+            BondKind::Elided if self.style.explicit_single => BondKind::Single,
+            _ => kind,
+        }
+    }
+
+    fn mark(&mut self, bond: Range<usize>, target: Range<usize>) {
+        if let Some(marks) = self.marks.as_mut() {
+            marks.push(Mark { bond, target });
+        }
     }
 }
 
 impl Follower for Writer {
     fn root(&mut self, root: AtomKind) {
-        if self.stack.is_empty() {
-            self.stack.push(root.to_string());
-        } else {
-            self.stack.push(".".to_string() + &root.to_string());
+        let first = self.stack.is_empty();
+
+        if !first && self.style.fragments == Fragments::Branch && self.fragment {
+            self.buffer.push(')');
+        }
+
+        self.push(&root);
+
+        if !first {
+            match self.style.fragments {
+                Fragments::Dot => self.buffer.push('.'),
+                Fragments::Branch => {
+                    self.buffer.push_str("(.");
+                    self.fragment = true;
+                }
+            }
         }
+
+        let target = self.write_atom(root, 0);
+
+        self.mark(target.start..target.start, target);
     }
 
     fn extend(&mut self, bond_kind: BondKind, atom_kind: AtomKind) {
-        self.stack
-            .push(bond_kind.to_string() + &atom_kind.to_string());
+        let sid = *self.stack.last().expect("last on stack");
+        let kind = self.restyle_bond(
+            bond_kind,
+            self.segments[sid].aromatic && atom_kind.is_aromatic(),
+        );
+
+        self.add_valence(sid, kind);
+        self.push(&atom_kind);
+
+        let bond = self.write_bond(kind);
+        let target = self.write_atom(atom_kind, Bond::new(kind.reverse(), 0).order());
+
+        self.mark(bond, target);
     }
 
     fn join(&mut self, bond_kind: BondKind, rnum: Rnum) {
-        let last = self.stack.last_mut().expect("last");
+        let sid = *self.stack.last().expect("last");
+        let slot = usize::from(rnum.value());
+
+        if self.opens.len() <= slot {
+            self.opens.resize(slot + 1, None);
+        }
 
-        last.push_str(&(bond_kind.to_string() + &rnum.to_string()));
+        let kind = match self.opens[slot].take() {
+            Some((tid, open_kind)) => {
+                let kind = self.restyle_bond(
+                    bond_kind,
+                    self.segments[sid].aromatic && self.segments[tid].aromatic,
+                );
+                let order_kind = match kind {
+                    BondKind::Elided => open_kind.reverse(),
+                    _ => kind,
+                };
+
+                self.add_valence(sid, order_kind);
+                self.add_valence(tid, order_kind.reverse());
+
+                kind
+            }
+            None => {
+                // an elided bond from an aromatic atom may be aromatic, so
+                // its symbol waits for the closing end
+                let elided = match bond_kind {
+                    BondKind::Elided => true,
+                    BondKind::Up | BondKind::Down => self.style.strip_stereo,
+                    _ => false,
+                };
+                let kind = if elided && self.segments[sid].aromatic {
+                    BondKind::Elided
+                } else {
+                    self.restyle_bond(bond_kind, false)
+                };
+
+                self.opens[slot] = Some((sid, kind));
+
+                kind
+            }
+        };
+        let bond = self.write_bond(kind);
+        let target = self.buffer.len();
+
+        write!(self.buffer, "{rnum}").expect("write to string");
+        self.mark(bond, target..self.buffer.len());
     }
 
     fn pop(&mut self, depth: usize) {
         assert!(depth < self.stack.len(), "overpop");
 
-        let chain = self.stack.split_off(self.stack.len() - depth);
-        let last = self.stack.last_mut().expect("last");
+        if depth == 0 {
+            self.buffer.push_str("()");
+
+            return;
+        }
+
+        let first = self.stack[self.stack.len() - depth];
 
-        last.push_str(&("(".to_string() + &chain.join("") + ")"));
+        self.stack.truncate(self.stack.len() - depth);
+        self.segments[first].opens += 1;
+        self.buffer.push(')');
+    }
+}
+
+/// Maps buffer ranges of a `Writer` to ranges of its written output.
+pub(crate) struct Locator {
+    opens: Vec<(usize, usize)>,
+    pending: Vec<(usize, usize)>,
+}
+
+impl Locator {
+    /// Returns the output range of buffer `range`, which must either cover
+    /// whole atoms or lie outside of them.
+    pub fn locate(&self, range: Range<usize>) -> Range<usize> {
+        let start = range.start
+            + self.opens_until(|at| at <= range.start)
+            + self.pending_until(range.start);
+
+        if range.is_empty() {
+            start..start
+        } else {
+            start..range.end + self.opens_until(|at| at < range.end) + self.pending_until(range.end)
+        }
+    }
+
+    fn opens_until<P: Fn(usize) -> bool>(&self, predicate: P) -> usize {
+        match self.opens.partition_point(|&(at, _)| predicate(at)) {
+            0 => 0,
+            end => self.opens[end - 1].1,
+        }
+    }
+
+    fn pending_until(&self, cursor: usize) -> usize {
+        match self.pending.partition_point(|&(end, _)| end <= cursor) {
+            0 => 0,
+            end => self.pending[end - 1].1,
+        }
+    }
+}
+
+/// Adapts an `io::Write` to `fmt::Write`, keeping the first I/O error.
+struct IoAdapter<W> {
+    sink: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<W> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        self.sink.write_all(text.as_bytes()).map_err(|error| {
+            self.error.get_or_insert(error);
+
+            fmt::Error
+        })
+    }
+}
+
+fn write_kind<W: fmt::Write>(
+    sink: &mut W,
+    kind: &AtomKind,
+    explicit_chirality: bool,
+) -> fmt::Result {
+    match kind {
+        AtomKind::Bracket {
+            isotope,
+            symbol,
+            configuration: Some(configuration),
+            hcount,
+            charge,
+            map,
+        } if explicit_chirality => {
+            sink.write_char('[')?;
+
+            if let Some(isotope) = isotope {
+                write!(sink, "{}", isotope.mass_number())?;
+            }
+
+            write!(sink, "{}", AtomKind::Symbol(*symbol))?;
+
+            match configuration {
+                Configuration::TH1 => sink.write_str("@TH1")?,
+                Configuration::TH2 => sink.write_str("@TH2")?,
+                Configuration::AL1 => sink.write_str("@AL1")?,
+                Configuration::AL2 => sink.write_str("@AL2")?,
+                other => write!(sink, "{other}")?,
+            }
+
+            if let Some(hcount) = hcount {
+                write!(sink, "{hcount}")?;
+            }
+
+            if let Some(charge) = charge {
+                write!(sink, "{charge}")?;
+            }
+
+            if let Some(map) = map {
+                write!(sink, ":{map}")?;
+            }
+
+            sink.write_char(']')
+        }
+        _ => write!(sink, "{kind}"),
     }
 }
 
@@ -163,4 +609,185 @@ mod write {
 
         assert_eq!(writer.write(), "*(*(-*)*)=*")
     }
+
+    #[test]
+    fn branched_components() {
+        let mut writer = Writer::default();
+
+        writer.root(AtomKind::Symbol(Symbol::Star));
+        writer.extend(BondKind::Elided, AtomKind::Symbol(Symbol::Star));
+        writer.pop(1);
+        writer.extend(BondKind::Elided, AtomKind::Symbol(Symbol::Star));
+        writer.root(AtomKind::Symbol(Symbol::Star));
+        writer.extend(BondKind::Double, AtomKind::Symbol(Symbol::Star));
+        writer.pop(1);
+        writer.extend(BondKind::Elided, AtomKind::Symbol(Symbol::Star));
+
+        assert_eq!(writer.write(), "*(*)*.*(=*)*")
+    }
+
+    #[test]
+    fn sinks() {
+        let mut writer = Writer::default();
+
+        writer.root(AtomKind::Symbol(Symbol::Star));
+        writer.extend(BondKind::Elided, AtomKind::Symbol(Symbol::Star));
+        writer.extend(BondKind::Elided, AtomKind::Symbol(Symbol::Star));
+        writer.pop(2);
+        writer.extend(BondKind::Elided, AtomKind::Symbol(Symbol::Star));
+
+        let mut text = String::from(">");
+        let mut bytes = Vec::new();
+
+        writer.write_to(&mut text).unwrap();
+        writer.write_io(&mut bytes).unwrap();
+
+        assert_eq!(text, ">*(**)*");
+        assert_eq!(bytes, b"*(**)*")
+    }
+
+    #[test]
+    fn clear() {
+        let mut writer = Writer::default();
+
+        writer.root(AtomKind::Symbol(Symbol::Star));
+        writer.extend(BondKind::Elided, AtomKind::Symbol(Symbol::Star));
+        writer.pop(1);
+        writer.clear();
+        writer.root(AtomKind::Symbol(Symbol::Star));
+
+        assert_eq!(writer.write(), "*")
+    }
+}
+
+#[cfg(test)]
+mod style {
+    use super::*;
+    use crate::read::read;
+    use pretty_assertions::assert_eq;
+
+    fn write(smiles: &str, style: Style) -> String {
+        let mut writer = Writer::new(style);
+
+        read(smiles, &mut writer, None).unwrap();
+
+        writer.write()
+    }
+
+    #[test]
+    fn default() {
+        assert_eq!(
+            write("[13CH3:1]/C=C/c1ccccc1", Style::default()),
+            "[13CH3:1]/C=C/c1ccccc1"
+        )
+    }
+
+    #[test]
+    fn explicit_single() {
+        let style = Style {
+            explicit_single: true,
+            ..Style::default()
+        };
+
+        assert_eq!(write("CC(O)C1CC1", style), "C-C(-O)-C-1-C-C-1");
+        assert_eq!(write("c1ccccc1C", style), "c1ccccc1-C");
+        assert_eq!(write("Cc1ccccc1", style), "C-c1ccccc1")
+    }
+
+    #[test]
+    fn aromatic_bonds() {
+        let style = Style {
+            aromatic_bonds: true,
+            ..Style::default()
+        };
+
+        assert_eq!(write("c1ccccc1C", style), "c1:c:c:c:c:c:1C");
+        assert_eq!(write("C1CC1", style), "C1CC1")
+    }
+
+    #[test]
+    fn brackets() {
+        let style = Style {
+            brackets: true,
+            ..Style::default()
+        };
+
+        assert_eq!(write("CC(=O)[O-]", style), "[CH3][C](=[O])[O-]");
+        assert_eq!(write("c1ccncc1*", style), "[cH]1[cH][cH][n][cH][c]1[*]");
+        assert_eq!(write("C1CC1.Cl", style), "[CH2]1[CH2][CH2]1.[ClH]")
+    }
+
+    #[test]
+    fn brackets_dative() {
+        let style = Style {
+            brackets: true,
+            ..Style::default()
+        };
+
+        assert_eq!(write("N->[Fe]<-N", style), "[NH3]->[Fe]<-[NH3]");
+        assert_eq!(
+            write("[Cu]1<-NCCN->1", style),
+            "[Cu]1<-[NH2][CH2][CH2][NH2]->1"
+        );
+        assert_eq!(write("[Cu]<-1.N1", style), "[Cu]<-1.[NH3]1");
+        assert_eq!(write("C~C", style), "[CH4]~[CH4]")
+    }
+
+    #[test]
+    fn strip() {
+        let style = Style {
+            strip_maps: true,
+            strip_isotopes: true,
+            strip_stereo: true,
+            ..Style::default()
+        };
+
+        assert_eq!(
+            write("[13CH3:1]/C=C/[C@H](O)Cl", style),
+            "[CH3]C=C[CH](O)Cl"
+        )
+    }
+
+    #[test]
+    fn explicit_chirality() {
+        let style = Style {
+            explicit_chirality: true,
+            ..Style::default()
+        };
+
+        assert_eq!(
+            write("F[C@H](Cl)[C@@H](Br)I.[13C@TB1](F)(F)(F)(F)F", style),
+            "F[C@TH1H](Cl)[C@TH2H](Br)I.[13C@TB1](F)(F)(F)(F)F"
+        )
+    }
+
+    #[test]
+    fn branch_fragments() {
+        let style = Style {
+            fragments: Fragments::Branch,
+            ..Style::default()
+        };
+
+        assert_eq!(write("CC.O.[Na+]", style), "CC(.O)(.[Na+])");
+        assert_eq!(write("C", style), "C")
+    }
+
+    #[test]
+    fn combined_io() {
+        let mut writer = Writer::new(Style {
+            brackets: true,
+            explicit_single: true,
+            fragments: Fragments::Branch,
+            ..Style::default()
+        });
+        let mut bytes = Vec::new();
+
+        read("C(C)C.O", &mut writer, None).unwrap();
+        writer.write_io(&mut bytes).unwrap();
+
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "[CH2](-[CH3])-[CH3](.[OH2])"
+        )
+    }
 }
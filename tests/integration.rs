@@ -1,10 +1,12 @@
 extern crate yowl;
 
-use std::fs;
+use std::fs::File;
+use std::io::BufReader;
 
 use yowl::feature::{AtomKind, BondKind, Symbol};
-use yowl::graph::{Atom, Bond, Builder};
+use yowl::graph::{is_isomorphic, Atom, Bond, Builder};
 use yowl::read::read;
+use yowl::smi::Reader;
 use yowl::walk::walk;
 use yowl::write::Writer;
 use yowl::Element;
@@ -91,10 +93,11 @@ fn roundtripping_smiles_strings() {
 
 #[test]
 fn bunch_of_smiles() {
-    let contents =
-        fs::read_to_string("tests/parsed_smiles.smi").expect("'parse_smiles.smi' available");
-    for smiles in contents.split_whitespace() {
-        roundtrip_smiles!(smiles);
+    let file = File::open("tests/parsed_smiles.smi").expect("'parse_smiles.smi' available");
+
+    for entry in Reader::new(BufReader::new(file)) {
+        assert!(entry.atoms.is_ok(), "line {}", entry.line_number);
+        roundtrip_smiles!(entry.smiles());
     }
 }
 
@@ -179,3 +182,33 @@ fn error_reporting_with_quotes_matches_original_input() {
     let err = read(smiles, &mut writer, None).unwrap_err();
     assert_eq!(err, ReadError::Character(19));
 }
+
+#[test]
+fn roundtripping_dative_and_zero_order_bonds() {
+    let all_smiles = [
+        ("N->[Fe]<-N", "N->[Fe]<-N"),
+        ("[NH3]->[Pt](<-[NH3])(Cl)Cl", "[NH3]->[Pt](<-[NH3])(Cl)Cl"),
+        ("[Cu]1<-NCCN->1", "[Cu](<-NCCN->1)<-1"),
+        ("[Na+]~[Cl-]", "[Na+]~[Cl-]"),
+        ("O~O", "O~O"),
+    ];
+
+    for (smiles, walked) in all_smiles {
+        roundtrip_smiles!(smiles);
+
+        let mut builder = Builder::default();
+        let mut writer = Writer::default();
+
+        read(smiles, &mut builder, None).unwrap();
+
+        let atoms = builder.build().unwrap();
+
+        walk(atoms.clone(), &mut writer).unwrap();
+        assert_eq!(writer.write(), walked);
+
+        let mut builder = Builder::default();
+
+        read(walked, &mut builder, None).unwrap();
+        assert!(is_isomorphic(&builder.build().unwrap(), &atoms, true));
+    }
+}